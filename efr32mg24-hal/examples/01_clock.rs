@@ -25,6 +25,7 @@ use cortex_m_rt::entry;
 use efr32mg24_hal::{
     clock::{ClockConfig, Clocks, HfxoConfig, LfxoConfig},
     pac,
+    prelude::*,
 };
 use panic_halt as _;
 
@@ -52,18 +53,18 @@ fn main() -> ! {
     let (_clocks_external, _cmu) = Clocks::new(
         dp.cmu_s,
         ClockConfig {
-            hfxo: Some(HfxoConfig::new(39_000_000)), // 39 MHz HFXO
-            lfxo: Some(LfxoConfig::default()),       // 32.768 kHz LFXO
+            hfxo: Some(HfxoConfig::new(39.MHz())), // 39 MHz HFXO
+            lfxo: Some(LfxoConfig::default()),     // 32.768 kHz LFXO
         },
     )
     .expect("Clock configuration failed");
 
     // Access the frozen clock frequencies
     // In a real application, you would use these for peripheral configuration
-    // let hfclk = clocks_external.hfclk().0;  // 39,000,000 Hz
-    // let lfclk = clocks_external.lfclk().0;  // 32,768 Hz
-    // let pclk = clocks_external.pclk().0;    // 39,000,000 Hz (same as HFCLK)
-    // let sysclk = clocks_external.sysclk().0; // 39,000,000 Hz (same as HFCLK)
+    // let hfclk = clocks_external.hfclk().to_Hz();  // 39,000,000 Hz
+    // let lfclk = clocks_external.lfclk().to_Hz();  // 32,768 Hz
+    // let pclk = clocks_external.pclk().to_Hz();    // 39,000,000 Hz (same as HFCLK)
+    // let sysclk = clocks_external.sysclk().to_Hz(); // 39,000,000 Hz (same as HFCLK)
 
     // For subsequent examples, we need more CMU peripherals
     // In a real application, you would only configure clocks once
@@ -84,8 +85,8 @@ fn main() -> ! {
     // let clocks_mixed = Clocks::new(
     //     dp.cmu_s,
     //     ClockConfig {
-    //         hfxo: Some(HfxoConfig::new(39_000_000)), // External 39 MHz
-    //         lfxo: None,                               // Use internal LFRCO
+    //         hfxo: Some(HfxoConfig::new(39.MHz())), // External 39 MHz
+    //         lfxo: None,                            // Use internal LFRCO
     //     }
     // );
 
@@ -97,7 +98,7 @@ fn main() -> ! {
     let (clocks, cmu) = Clocks::new(
         dp2.cmu_s,
         ClockConfig {
-            hfxo: Some(HfxoConfig::new(39_000_000)),
+            hfxo: Some(HfxoConfig::new(39.MHz())),
             lfxo: Some(LfxoConfig::default()),
         },
     )
@@ -48,7 +48,7 @@ fn main() -> ! {
     let (clocks, cmu) = Clocks::new(
         dp.cmu_s,
         ClockConfig {
-            hfxo: Some(HfxoConfig::new(39_000_000)),
+            hfxo: Some(HfxoConfig::new(39.MHz())),
             lfxo: Some(LfxoConfig::default()),
         },
     )
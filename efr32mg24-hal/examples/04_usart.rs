@@ -79,7 +79,7 @@ fn main() -> ! {
     // Echo loop: read bytes and echo them back
     loop {
         // Check for received data (non-blocking)
-        if let Some(byte) = usart.read_byte() {
+        if let Ok(byte) = usart.read_byte() {
             // Echo the byte back
             usart.write_byte(byte);
 
@@ -33,6 +33,7 @@ use efr32mg24_hal as hal;
 use hal::clock::{ClockConfig, Clocks, HfxoConfig};
 use hal::i2c::{Config, I2c0, Speed};
 use hal::pac;
+use hal::prelude::*;
 use panic_halt as _;
 
 #[entry]
@@ -55,7 +56,7 @@ fn main() -> ! {
     let (clocks, cmu) = Clocks::new(
         dp.cmu_s,
         ClockConfig {
-            hfxo: Some(HfxoConfig::new(39_000_000)), // Adjust for your board
+            hfxo: Some(HfxoConfig::new(39.MHz())), // Adjust for your board
             lfxo: Some(Default::default()),
         },
     )
@@ -64,7 +65,8 @@ fn main() -> ! {
     let frozen_clocks = clocks.freeze(cmu);
 
     // Create I2C0 instance with 100 kHz (standard mode)
-    // For 400 kHz (fast mode), use Speed::Fast400kHz
+    // For 400 kHz (fast mode), use Speed::Fast400kHz; for 1 MHz (Fast-mode Plus),
+    // Speed::FastPlus1MHz; any other frequency with Speed::Custom(50.kHz())
     let mut i2c = I2c0::new(
         dp.i2c0_s,
         Config::new(Speed::Standard100kHz),
@@ -93,7 +95,7 @@ fn main() -> ! {
     // Example 2: Read single register
     // Read WHO_AM_I register (0x75) from device
     let mut buffer = [0u8; 1];
-    match i2c.write_read(DEVICE_ADDR, 0x75, &mut buffer) {
+    match i2c.write_read(DEVICE_ADDR, &[0x75], &mut buffer) {
         Ok(()) => {
             // buffer[0] now contains WHO_AM_I value
             let who_am_i = buffer[0];
@@ -107,7 +109,7 @@ fn main() -> ! {
     // Example 3: Read multiple bytes
     // Read 6 bytes starting from register 0x22
     let mut data = [0u8; 6];
-    match i2c.write_read(DEVICE_ADDR, 0x22, &mut data) {
+    match i2c.write_read(DEVICE_ADDR, &[0x22], &mut data) {
         Ok(()) => {
             // data[] now contains 6 bytes from device
         }
@@ -134,7 +136,7 @@ fn main() -> ! {
 
         // For demonstration, read WHO_AM_I continuously
         let mut who_am_i = [0u8; 1];
-        if i2c.write_read(DEVICE_ADDR, 0x75, &mut who_am_i).is_ok() {
+        if i2c.write_read(DEVICE_ADDR, &[0x75], &mut who_am_i).is_ok() {
             // Successfully read WHO_AM_I
             let _ = who_am_i[0];
         }
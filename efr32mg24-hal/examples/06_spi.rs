@@ -44,6 +44,7 @@ use cortex_m_rt::entry;
 use efr32mg24_hal as hal;
 use hal::clock::{ClockConfig, Clocks, HfxoConfig};
 use hal::pac;
+use hal::prelude::*;
 use hal::spi::{Config, Mode, Spi0, Spi1, Spi2};
 use panic_halt as _;
 
@@ -64,7 +65,7 @@ fn main() -> ! {
     let (clocks, cmu) = Clocks::new(
         dp.cmu_s,
         ClockConfig {
-            hfxo: Some(HfxoConfig::new(39_000_000)), // Adjust for your board
+            hfxo: Some(HfxoConfig::new(39.MHz())), // Adjust for your board
             lfxo: Some(Default::default()),
         },
     )
@@ -79,21 +80,21 @@ fn main() -> ! {
     // SPI0: USART0 in SPI mode
     let mut spi0 = Spi0::new(
         dp.usart0_s,
-        Config::new(Mode::Mode0, 1_000_000),
+        Config::new(Mode::Mode0, 1.MHz()),
         &frozen_clocks,
     );
 
     // SPI1: EUSART0 in SPI mode
     let mut spi1 = Spi1::new(
         dp.eusart0_s,
-        Config::new(Mode::Mode0, 1_000_000),
+        Config::new(Mode::Mode0, 1.MHz()),
         &frozen_clocks,
     );
 
     // SPI2: EUSART1 in SPI mode (using Mode 3 as example)
     let mut spi2 = Spi2::new(
         dp.eusart1_s,
-        Config::new(Mode::Mode3, 4_000_000),
+        Config::new(Mode::Mode3, 4.MHz()),
         &frozen_clocks,
     );
 
@@ -175,9 +176,9 @@ fn main() -> ! {
     // Example 6: Different frequencies
     //
     // Common SPI frequencies:
-    // - 1 MHz: Config::new(Mode::Mode0, 1_000_000)
-    // - 4 MHz: Config::new(Mode::Mode0, 4_000_000)
-    // - 8 MHz: Config::new(Mode::Mode0, 8_000_000)
+    // - 1 MHz: Config::new(Mode::Mode0, 1.MHz())
+    // - 4 MHz: Config::new(Mode::Mode0, 4.MHz())
+    // - 8 MHz: Config::new(Mode::Mode0, 8.MHz())
     //
     // Maximum frequency depends on:
     // - HCLK frequency
@@ -73,7 +73,7 @@ fn main() -> ! {
     let (clocks, cmu) = Clocks::new(
         dp.cmu_s,
         ClockConfig {
-            hfxo: Some(HfxoConfig::new(39_000_000)),
+            hfxo: Some(HfxoConfig::new(39.MHz())),
             lfxo: Some(Default::default()),
         },
     )
@@ -88,7 +88,7 @@ fn main() -> ! {
     // PWM frequency = 10 kHz provides smooth LED dimming without visible flicker
     let mut timer = Timer0::new(
         dp.timer0_s,
-        Config::new(10_000).with_pwm(PwmMode::EdgeAligned),
+        Config::new(10.kHz()).with_pwm(PwmMode::EdgeAligned),
         &frozen_clocks,
     );
 
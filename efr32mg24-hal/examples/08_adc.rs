@@ -73,7 +73,7 @@ fn main() -> ! {
     let (clocks, cmu) = Clocks::new(
         dp.cmu_s,
         ClockConfig {
-            hfxo: Some(HfxoConfig::new(39_000_000)),
+            hfxo: Some(HfxoConfig::new(39.MHz())),
             lfxo: Some(Default::default()),
         },
     )
@@ -129,7 +129,7 @@ fn demonstrate_vdd_reference() {
     let (clocks, cmu) = Clocks::new(
         dp.cmu_s,
         ClockConfig {
-            hfxo: Some(HfxoConfig::new(39_000_000)),
+            hfxo: Some(HfxoConfig::new(39.MHz())),
             lfxo: Some(Default::default()),
         },
     )
@@ -218,7 +218,7 @@ fn demonstrate_voltage_divider() {
     let (clocks, cmu) = Clocks::new(
         dp.cmu_s,
         ClockConfig {
-            hfxo: Some(HfxoConfig::new(39_000_000)),
+            hfxo: Some(HfxoConfig::new(39.MHz())),
             lfxo: Some(Default::default()),
         },
     )
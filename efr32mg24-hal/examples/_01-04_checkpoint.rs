@@ -66,7 +66,7 @@ fn main() -> ! {
     let (clocks, cmu) = Clocks::new(
         dp.cmu_s,
         ClockConfig {
-            hfxo: Some(HfxoConfig::new(39_000_000)),
+            hfxo: Some(HfxoConfig::new(39.MHz())),
             lfxo: Some(Default::default()),
         },
     )
@@ -115,7 +115,7 @@ fn main() -> ! {
     usart.write(b"Clock Configuration:\r\n");
     usart.write(b"  HFXO: 39 MHz\r\n");
     usart.write(b"  PCLK: ");
-    write_u32(&mut usart, frozen_clocks.pclk().into());
+    write_u32(&mut usart, frozen_clocks.pclk().to_Hz());
     usart.write(b" Hz\r\n");
     usart.write(b"\r\n");
 
@@ -165,7 +165,7 @@ fn main() -> ! {
         button_was_pressed = button_pressed;
 
         // Check for received UART data (non-blocking)
-        if let Some(byte) = usart.read_byte() {
+        if let Ok(byte) = usart.read_byte() {
             // Echo received character
             usart.write(b"[UART] Received: '");
             usart.write_byte(byte);
@@ -29,13 +29,14 @@
 //!     clock::{Clocks, ClockConfig, HfxoConfig},
 //!     adc::{Adc, Config, Channel, Reference},
 //!     pac,
+//!     prelude::*,
 //! };
 //!
 //! let dp = pac::Peripherals::take().unwrap();
 //!
 //! // Configure clocks
 //! let clocks = Clocks::new(dp.cmu_s, ClockConfig {
-//!     hfxo: Some(HfxoConfig::new(39_000_000)),
+//!     hfxo: Some(HfxoConfig::new(39.MHz())),
 //!     lfxo: Some(Default::default()),
 //! }).freeze();
 //!
@@ -72,10 +73,10 @@ impl Adc {
     /// # Example
     ///
     /// ```no_run
-    /// # use efr32mg24_hal::{adc::{Adc, Config}, clock::{Clocks, ClockConfig, HfxoConfig}, pac};
+    /// # use efr32mg24_hal::{adc::{Adc, Config}, clock::{Clocks, ClockConfig, HfxoConfig}, pac, prelude::*};
     /// # let dp = pac::Peripherals::take().unwrap();
     /// # let clocks = Clocks::new(dp.cmu_s, ClockConfig {
-    /// #     hfxo: Some(HfxoConfig::new(39_000_000)),
+    /// #     hfxo: Some(HfxoConfig::new(39.MHz())),
     /// #     lfxo: Some(Default::default()),
     /// # }).freeze();
     /// let adc = Adc::new(dp.iadc0_s, Config::default(), &clocks);
@@ -134,10 +135,10 @@ impl Adc {
     /// # Example
     ///
     /// ```no_run
-    /// # use efr32mg24_hal::{adc::{Adc, Config, Channel}, clock::{Clocks, ClockConfig, HfxoConfig}, pac};
+    /// # use efr32mg24_hal::{adc::{Adc, Config, Channel}, clock::{Clocks, ClockConfig, HfxoConfig}, pac, prelude::*};
     /// # let dp = pac::Peripherals::take().unwrap();
     /// # let clocks = Clocks::new(dp.cmu_s, ClockConfig {
-    /// #     hfxo: Some(HfxoConfig::new(39_000_000)),
+    /// #     hfxo: Some(HfxoConfig::new(39.MHz())),
     /// #     lfxo: Some(Default::default()),
     /// # }).freeze();
     /// # let mut adc = Adc::new(dp.iadc0_s, Config::default(), &clocks);
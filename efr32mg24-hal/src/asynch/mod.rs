@@ -0,0 +1,250 @@
+//! Interrupt-Driven Async Support
+//!
+//! This module contains the interrupt plumbing shared by the async drivers in
+//! [`usart`](crate::usart), [`spi`](crate::spi), [`i2c`](crate::i2c) and
+//! [`gpio`](crate::gpio). It is enabled with the `async` cargo feature.
+//!
+//! # How It Works
+//!
+//! Each async operation polls the peripheral's STATUS register. If the condition
+//! it waits for is not met, the future registers its waker, clears the matching
+//! IF flag and enables the interrupt in IEN, then checks STATUS once more.
+//!
+//! The interrupt handler does not touch the data registers. It only disables the
+//! IEN bits that fired and wakes the registered task, which then re-polls the
+//! peripheral. This keeps the handlers identical for every driver sharing a
+//! peripheral (for example `Usart0` and `Spi0` both use USART0).
+//!
+//! # Interrupt Handlers
+//!
+//! Each async driver has an `InterruptHandler` type, bound to its vectors with
+//! [`bind_interrupts!`](crate::bind_interrupts). The `new_async` constructors
+//! require the binding and enable the vectors in the NVIC:
+//!
+//! ```no_run
+//! use efr32mg24_hal::{bind_interrupts, usart};
+//!
+//! bind_interrupts!(struct Irqs {
+//!     USART0_RX => usart::InterruptHandler;
+//!     USART0_TX => usart::InterruptHandler;
+//! });
+//!
+//! let usart = usart::Usart0::new_async(dp.usart0_s, usart::Config::default(), &clocks, Irqs);
+//! ```
+//!
+//! Applications that define their own vector functions can call
+//! [`on_interrupt`] from them instead.
+//!
+//! # Supported Vectors
+//!
+//! | Vector | Drivers |
+//! |--------|---------|
+//! | USART0_RX / USART0_TX | `Usart0`, `Spi0` |
+//! | EUSART0_RX / EUSART0_TX | `Eusart0`, `Spi1` |
+//! | EUSART1_RX / EUSART1_TX | `Eusart1`, `Spi2` |
+//! | I2C0 / I2C1 | `I2c0`, `I2c1`, `I2cTarget0`, `I2cTarget1` |
+//! | GPIO_EVEN / GPIO_ODD | `ExtiInput` |
+//! | LDMA | `SpiDma` |
+
+use core::cell::RefCell;
+use core::future::poll_fn;
+use core::task::{Poll, Waker};
+
+use critical_section::Mutex;
+
+use crate::pac;
+use crate::pac::Interrupt;
+use crate::regs;
+
+/// Waker slot shared between a future and an interrupt handler
+pub(crate) struct AtomicWaker {
+    waker: Mutex<RefCell<Option<Waker>>>,
+}
+
+impl AtomicWaker {
+    /// Create an empty waker slot
+    pub(crate) const fn new() -> Self {
+        Self {
+            waker: Mutex::new(RefCell::new(None)),
+        }
+    }
+
+    /// Register the waker of the task to wake on the next interrupt
+    pub(crate) fn register(&self, waker: &Waker) {
+        critical_section::with(|cs| {
+            let mut slot = self.waker.borrow(cs).borrow_mut();
+            match slot.as_ref() {
+                Some(current) if current.will_wake(waker) => {}
+                _ => *slot = Some(waker.clone()),
+            }
+        });
+    }
+
+    /// Wake the registered task, if any
+    pub(crate) fn wake(&self) {
+        let waker = critical_section::with(|cs| self.waker.borrow(cs).borrow_mut().take());
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// USART0 receive interrupt waker
+pub(crate) static USART0_RX: AtomicWaker = AtomicWaker::new();
+/// USART0 transmit interrupt waker
+pub(crate) static USART0_TX: AtomicWaker = AtomicWaker::new();
+/// EUSART0 receive interrupt waker
+pub(crate) static EUSART0_RX: AtomicWaker = AtomicWaker::new();
+/// EUSART0 transmit interrupt waker
+pub(crate) static EUSART0_TX: AtomicWaker = AtomicWaker::new();
+/// EUSART1 receive interrupt waker
+pub(crate) static EUSART1_RX: AtomicWaker = AtomicWaker::new();
+/// EUSART1 transmit interrupt waker
+pub(crate) static EUSART1_TX: AtomicWaker = AtomicWaker::new();
+/// I2C0 interrupt waker
+pub(crate) static I2C0: AtomicWaker = AtomicWaker::new();
+/// I2C1 interrupt waker
+pub(crate) static I2C1: AtomicWaker = AtomicWaker::new();
+/// GPIO external interrupt wakers, one per EXTI line
+pub(crate) static GPIO_EXTI: [AtomicWaker; 16] = [const { AtomicWaker::new() }; 16];
+/// LDMA channel done wakers, one per channel
+pub(crate) static LDMA: [AtomicWaker; 8] = [const { AtomicWaker::new() }; 8];
+
+/// USART IF/IEN bits serviced by the RX vector (RXDATAV, RXFULL, RXOF, PERR, FERR)
+pub(crate) const USART_RX_FLAGS: u32 = (1 << 2) | (1 << 3) | (1 << 4) | (1 << 8) | (1 << 9);
+/// USART IF/IEN bits serviced by the TX vector (TXC, TXBL)
+pub(crate) const USART_TX_FLAGS: u32 = (1 << 0) | (1 << 1);
+/// EUSART IF/IEN bits serviced by the RX vector (RXFL, RXFULL, RXOF, PERR, FERR, RXTO)
+pub(crate) const EUSART_RX_FLAGS: u32 =
+    (1 << 2) | (1 << 3) | (1 << 4) | (1 << 8) | (1 << 9) | (1 << 25);
+/// EUSART IF/IEN bits serviced by the TX vector (TXC, TXFL)
+pub(crate) const EUSART_TX_FLAGS: u32 = (1 << 0) | (1 << 1);
+
+/// Disable the interrupts that fired in `$mask` and wake `$waker`
+macro_rules! service {
+    ($periph:expr, $mask:expr, $waker:expr) => {{
+        let periph = $periph;
+        let pending = periph.if_().read().bits() & periph.ien().read().bits() & $mask;
+        regs::clear_bits(periph.ien(), pending);
+        $waker.wake();
+    }};
+}
+
+/// Service an interrupt used by the async drivers
+///
+/// The drivers' `InterruptHandler` types call this. Applications that do not use
+/// [`bind_interrupts!`](crate::bind_interrupts) must call it from the handler of
+/// every vector listed in the [module documentation](self) that an async driver
+/// relies on. The handler disables the
+/// interrupt sources that fired and wakes the waiting task; the task itself reads
+/// the data and re-enables the interrupt if it needs to wait again.
+///
+/// Interrupts that are not used by any async driver are ignored.
+pub fn on_interrupt(irq: Interrupt) {
+    // SAFETY: The handlers only read IF/IEN and clear IEN bits through the CLR
+    // alias, which never races with the owning driver's register accesses.
+    unsafe {
+        match irq {
+            Interrupt::USART0_RX => service!(&*pac::Usart0S::ptr(), USART_RX_FLAGS, USART0_RX),
+            Interrupt::USART0_TX => service!(&*pac::Usart0S::ptr(), USART_TX_FLAGS, USART0_TX),
+            Interrupt::EUSART0_RX => {
+                service!(&*pac::Eusart0S::ptr(), EUSART_RX_FLAGS, EUSART0_RX)
+            }
+            Interrupt::EUSART0_TX => {
+                service!(&*pac::Eusart0S::ptr(), EUSART_TX_FLAGS, EUSART0_TX)
+            }
+            Interrupt::EUSART1_RX => {
+                service!(&*pac::Eusart1S::ptr(), EUSART_RX_FLAGS, EUSART1_RX)
+            }
+            Interrupt::EUSART1_TX => {
+                service!(&*pac::Eusart1S::ptr(), EUSART_TX_FLAGS, EUSART1_TX)
+            }
+            Interrupt::I2C0 => service!(&*pac::I2c0S::ptr(), u32::MAX, I2C0),
+            Interrupt::I2C1 => service!(&*pac::I2c1S::ptr(), u32::MAX, I2C1),
+            Interrupt::GPIO_EVEN => on_gpio_interrupt(0x5555),
+            Interrupt::GPIO_ODD => on_gpio_interrupt(0xAAAA),
+            Interrupt::LDMA => on_ldma_interrupt(),
+            _ => {}
+        }
+    }
+}
+
+/// Waker of the task waiting on the USART, EUSART or I2C vector `irq`
+///
+/// Lets drivers that are generic over a peripheral instance find their waker
+/// from the instance's interrupt. The GPIO and LDMA vectors have one waker per
+/// line or channel and are not covered.
+pub(crate) fn waker(irq: Interrupt) -> &'static AtomicWaker {
+    match irq {
+        Interrupt::USART0_RX => &USART0_RX,
+        Interrupt::USART0_TX => &USART0_TX,
+        Interrupt::EUSART0_RX => &EUSART0_RX,
+        Interrupt::EUSART0_TX => &EUSART0_TX,
+        Interrupt::EUSART1_RX => &EUSART1_RX,
+        Interrupt::EUSART1_TX => &EUSART1_TX,
+        Interrupt::I2C0 => &I2C0,
+        Interrupt::I2C1 => &I2C1,
+        _ => unreachable!("no driver waker for this interrupt"),
+    }
+}
+
+/// Disable the EXTI lines in `lines` that fired and wake their tasks
+///
+/// The IF bits are left set so that the waiting future can see that its edge
+/// occurred; the future clears them when it completes.
+fn on_gpio_interrupt(lines: u32) {
+    // SAFETY: Only IF is read and IEN bits are cleared through the CLR alias.
+    let gpio = unsafe { &*pac::GpioS::ptr() };
+    let pending = gpio.if_().read().bits() & gpio.ien().read().bits() & lines;
+    regs::clear_bits(gpio.ien(), pending);
+
+    for (line, waker) in GPIO_EXTI.iter().enumerate() {
+        if pending & (1 << line) != 0 {
+            waker.wake();
+        }
+    }
+}
+
+/// Disable the done interrupts of the LDMA channels that finished and wake
+/// their tasks
+///
+/// The IF bits are left set: they are the channels' done flags.
+fn on_ldma_interrupt() {
+    // SAFETY: Only IF is read and IEN bits are cleared through the CLR alias.
+    let ldma = unsafe { &*pac::LdmaS::ptr() };
+    let pending = ldma.if_().read().bits() & ldma.ien().read().bits() & 0xFF;
+    regs::clear_bits(ldma.ien(), pending);
+
+    for (channel, waker) in LDMA.iter().enumerate() {
+        if pending & (1 << channel) != 0 {
+            waker.wake();
+        }
+    }
+}
+
+/// Wait until `ready` returns `true`, sleeping on `waker` in between
+///
+/// `arm` is called after the waker is registered and must clear the relevant IF
+/// flags and enable the interrupt. `ready` is checked again afterwards, so a
+/// condition that became true while arming is never missed.
+pub(crate) async fn wait_for(
+    waker: &AtomicWaker,
+    mut ready: impl FnMut() -> bool,
+    mut arm: impl FnMut(),
+) {
+    poll_fn(|cx| {
+        if ready() {
+            return Poll::Ready(());
+        }
+
+        waker.register(cx.waker());
+        arm();
+
+        if ready() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    })
+    .await
+}
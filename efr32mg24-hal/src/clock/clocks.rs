@@ -3,7 +3,7 @@
 //! This module implements actual hardware configuration for the EFR32MG24 clock system.
 
 use super::frozen::FrozenClocks;
-use super::types::{ClockConfig, ClockError, Hertz};
+use super::types::{ClockConfig, ClockError, Hertz, LfClockSource};
 
 /// Configured clock frequencies with hardware control
 ///
@@ -14,6 +14,8 @@ pub struct Clocks {
     pub(super) hfclk: Hertz,
     /// Low frequency clock (LFCLK) frequency
     pub(super) lfclk: Hertz,
+    /// Low frequency clock (LFCLK) source
+    pub(super) lf_source: LfClockSource,
     /// Peripheral clock (PCLK) frequency
     pub(super) pclk: Hertz,
     /// System clock (SYSCLK) frequency
@@ -56,14 +58,14 @@ impl Clocks {
     /// # Example
     ///
     /// ```no_run
-    /// use efr32mg24_hal::{clock::{Clocks, ClockConfig, HfxoConfig}, pac};
+    /// use efr32mg24_hal::{clock::{Clocks, ClockConfig, HfxoConfig}, pac, prelude::*};
     ///
     /// let dp = pac::Peripherals::take().unwrap();
     ///
     /// let clocks = Clocks::new(
     ///     dp.cmu_s,
     ///     ClockConfig {
-    ///         hfxo: Some(HfxoConfig::new(39_000_000)),
+    ///         hfxo: Some(HfxoConfig::new(39.MHz())),
     ///         lfxo: None, // Use internal LFRCO
     ///     }
     /// )?;
@@ -92,20 +94,20 @@ impl Clocks {
         } else {
             // Use default HFRCO
             // HFRCO is typically enabled by default
-            Hertz(Self::HFRCO_FREQ)
+            Hertz::from_raw(Self::HFRCO_FREQ)
         };
 
-        let lfclk = if let Some(lfxo_config) = config.lfxo {
+        let (lfclk, lf_source) = if let Some(lfxo_config) = config.lfxo {
             // TODO: Configure LFXO
             // The EFR32MG24 uses different oscillator control registers than earlier series.
             // Need to verify correct register/field names from reference manual.
             // For now, just track the configured frequency.
             cortex_m::asm::delay(Self::OSC_TIMEOUT);
 
-            lfxo_config.frequency
+            (lfxo_config.frequency, LfClockSource::Lfxo)
         } else {
             // Use default LFRCO
-            Hertz(Self::LFRCO_FREQ)
+            (Hertz::from_raw(Self::LFRCO_FREQ), LfClockSource::Lfrco)
         };
 
         // On EFR32MG24, SYSCLK and PCLK typically equal HFCLK
@@ -115,6 +117,7 @@ impl Clocks {
         let clocks = Self {
             hfclk,
             lfclk,
+            lf_source,
             pclk,
             sysclk,
         };
@@ -149,6 +152,12 @@ impl Clocks {
         self.lfclk
     }
 
+    /// Get the low frequency clock (LFCLK) source
+    #[inline]
+    pub fn lf_source(&self) -> LfClockSource {
+        self.lf_source
+    }
+
     /// Get the peripheral clock (PCLK) frequency
     #[inline]
     pub fn pclk(&self) -> Hertz {
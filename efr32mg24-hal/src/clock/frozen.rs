@@ -3,7 +3,7 @@
 //! Once frozen, clock frequencies cannot be changed, ensuring peripheral stability.
 
 use super::clocks::Clocks;
-use super::types::Hertz;
+use super::types::{Hertz, LfClockSource};
 
 /// Frozen clock configuration
 ///
@@ -31,6 +31,15 @@ impl FrozenClocks {
         self.clocks.lfclk
     }
 
+    /// Get the low frequency clock (LFCLK) source
+    ///
+    /// LF peripherals such as SYSRTC0 select this oscillator, so they run at
+    /// [`lfclk`](Self::lfclk).
+    #[inline]
+    pub fn lf_source(&self) -> LfClockSource {
+        self.clocks.lf_source
+    }
+
     /// Get the peripheral clock (PCLK) frequency
     #[inline]
     pub fn pclk(&self) -> Hertz {
@@ -25,6 +25,7 @@
 //! use efr32mg24_hal::{
 //!     clock::{Clocks, ClockConfig, HfxoConfig, LfxoConfig},
 //!     pac,
+//!     prelude::*,
 //! };
 //!
 //! let dp = pac::Peripherals::take().unwrap();
@@ -33,7 +34,7 @@
 //! let clocks = Clocks::new(
 //!     dp.cmu_s,
 //!     ClockConfig {
-//!         hfxo: Some(HfxoConfig::new(39_000_000)),
+//!         hfxo: Some(HfxoConfig::new(39.MHz())),
 //!         lfxo: Some(LfxoConfig::default()),  // 32.768 kHz
 //!     }
 //! );
@@ -41,7 +42,7 @@
 //! let frozen_clocks = clocks.freeze();
 //!
 //! // Use frozen clocks with peripherals
-//! let sysclk_freq = frozen_clocks.sysclk().0;
+//! let sysclk_freq = frozen_clocks.sysclk().to_Hz();
 //! ```
 //!
 //! ## Using Internal RC Oscillators
@@ -70,4 +71,4 @@ mod types;
 // Re-export public types
 pub use clocks::Clocks;
 pub use frozen::FrozenClocks;
-pub use types::{ClockConfig, ClockError, Hertz, HfxoConfig, LfxoConfig};
+pub use types::{ClockConfig, ClockError, Hertz, HfxoConfig, LfClockSource, LfxoConfig};
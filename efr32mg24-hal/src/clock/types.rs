@@ -2,21 +2,7 @@
 //!
 //! This module contains basic types and configurations for the clock system.
 
-/// Clock frequencies in Hertz
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
-pub struct Hertz(pub u32);
-
-impl From<u32> for Hertz {
-    fn from(freq: u32) -> Self {
-        Hertz(freq)
-    }
-}
-
-impl From<Hertz> for u32 {
-    fn from(hz: Hertz) -> u32 {
-        hz.0
-    }
-}
+pub use crate::time::Hertz;
 
 /// High Frequency Crystal Oscillator (HFXO) configuration
 #[derive(Copy, Clone, Debug)]
@@ -27,10 +13,8 @@ pub struct HfxoConfig {
 
 impl HfxoConfig {
     /// Create a new HFXO configuration with the specified frequency
-    pub fn new(frequency: u32) -> Self {
-        Self {
-            frequency: Hertz(frequency),
-        }
+    pub const fn new(frequency: Hertz) -> Self {
+        Self { frequency }
     }
 }
 
@@ -43,19 +27,26 @@ pub struct LfxoConfig {
 
 impl LfxoConfig {
     /// Create a new LFXO configuration with the specified frequency
-    pub fn new(frequency: u32) -> Self {
-        Self {
-            frequency: Hertz(frequency),
-        }
+    pub const fn new(frequency: Hertz) -> Self {
+        Self { frequency }
     }
 }
 
 impl Default for LfxoConfig {
     fn default() -> Self {
-        Self::new(32_768)
+        Self::new(Hertz::from_raw(32_768))
     }
 }
 
+/// Low frequency clock (LFCLK) source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LfClockSource {
+    /// Internal 32.768 kHz RC oscillator (no LFXO configured)
+    Lfrco,
+    /// Low frequency crystal oscillator ([`ClockConfig::lfxo`])
+    Lfxo,
+}
+
 /// Clock configuration
 #[derive(Default, Copy, Clone, Debug)]
 pub struct ClockConfig {
@@ -1,6 +1,13 @@
-//! Delay implementation using SysTick
+//! Delay implementations
 //!
-//! This module provides blocking delay functions using the ARM Cortex-M SysTick timer.
+//! This module provides blocking delay functions implementing `DelayNs`:
+//!
+//! - [`Delay`] - ARM Cortex-M SysTick timer (takes ownership of `SYST`)
+//! - [`DwtDelay`] - DWT cycle counter, cycle-accurate for sub-microsecond delays
+//! - [`SysrtcDelay`] - SYSRTC0, sleeping in EM1 or EM2 instead of spinning
+//!
+//! Any `timer::TimerN` also implements `DelayNs`, so an application whose RTOS owns
+//! SysTick can use a TIMER, SYSRTC0 or the DWT for delays instead.
 //!
 //! # Examples
 //!
@@ -21,6 +28,12 @@
 //! delay.delay_us(100);
 //! ```
 
+mod dwt;
+mod sysrtc;
+
+pub use dwt::DwtDelay;
+pub use sysrtc::{SleepMode, SysrtcDelay};
+
 use cortex_m::peripheral::syst::SystClkSource;
 use cortex_m::peripheral::SYST;
 use embedded_hal::delay::DelayNs;
@@ -70,119 +83,47 @@ impl Delay {
     }
 }
 
-impl DelayNs for Delay {
-    fn delay_ns(&mut self, ns: u32) {
-        // Convert nanoseconds to SysTick ticks
-        // ticks = (ns * sysclk_hz) / 1_000_000_000
-
-        let sysclk_hz = self.sysclk.0;
-
-        // For very short delays, just use a NOP loop
-        if ns < 1000 {
-            // At 39 MHz, each instruction is ~25ns
-            // This is approximate but good enough for very short delays
-            let cycles = (ns * (sysclk_hz / 1_000_000)) / 1000;
-            cortex_m::asm::delay(cycles);
-            return;
-        }
+impl Delay {
+    /// Busy-wait for `ticks` SysTick (SYSCLK) cycles
+    ///
+    /// Long delays are split into reloads of at most 24 bits; the total is
+    /// computed once, so the error does not accumulate across chunks.
+    fn delay_ticks(&mut self, ticks: u64) {
+        const MAX_RVR: u64 = 0x00FF_FFFF;
 
-        // For longer delays, use SysTick
-        // SysTick is a 24-bit down counter
-        const MAX_RVR: u32 = 0x00FF_FFFF;
-
-        // Calculate ticks, being careful with overflow
-        // ticks = (ns / 1_000_000_000) * sysclk_hz
-        //       = (ns * sysclk_hz) / 1_000_000_000
-
-        // To avoid overflow, we'll process in chunks if needed
-        let ticks = if ns < 1_000_000 {
-            // For delays < 1ms, calculate directly
-            ((ns as u64) * (sysclk_hz as u64) / 1_000_000_000) as u32
-        } else {
-            // For delays >= 1ms, use microsecond precision
-            self.delay_us(ns / 1000);
-            return;
-        };
-
-        if ticks == 0 {
-            return;
-        }
+        let mut remaining = ticks;
+        while remaining > 0 {
+            let current = remaining.min(MAX_RVR);
 
-        // If the delay fits in one SysTick cycle
-        if ticks <= MAX_RVR {
-            self.syst.set_reload(ticks);
+            self.syst.set_reload(current as u32);
             self.syst.clear_current();
             self.syst.enable_counter();
 
             while !self.syst.has_wrapped() {}
 
             self.syst.disable_counter();
-        } else {
-            // Split into multiple cycles
-            let mut remaining = ticks;
 
-            while remaining > 0 {
-                let current = remaining.min(MAX_RVR);
-
-                self.syst.set_reload(current);
-                self.syst.clear_current();
-                self.syst.enable_counter();
-
-                while !self.syst.has_wrapped() {}
-
-                self.syst.disable_counter();
-
-                remaining -= current;
-            }
+            remaining -= current;
         }
     }
+}
 
-    fn delay_us(&mut self, us: u32) {
-        // Convert microseconds to ticks
-        // ticks = (us * sysclk_hz) / 1_000_000
-
-        let sysclk_hz = self.sysclk.0;
-        const MAX_RVR: u32 = 0x00FF_FFFF;
-
-        // Calculate ticks
-        let ticks = ((us as u64) * (sysclk_hz as u64) / 1_000_000) as u32;
-
-        if ticks == 0 {
-            return;
-        }
-
-        if ticks <= MAX_RVR {
-            self.syst.set_reload(ticks);
-            self.syst.clear_current();
-            self.syst.enable_counter();
-
-            while !self.syst.has_wrapped() {}
-
-            self.syst.disable_counter();
-        } else {
-            // Split into multiple cycles
-            let mut remaining = ticks;
-
-            while remaining > 0 {
-                let current = remaining.min(MAX_RVR);
-
-                self.syst.set_reload(current);
-                self.syst.clear_current();
-                self.syst.enable_counter();
-
-                while !self.syst.has_wrapped() {}
-
-                self.syst.disable_counter();
+impl DelayNs for Delay {
+    fn delay_ns(&mut self, ns: u32) {
+        // ticks = ceil(ns * sysclk_hz / 1_000_000_000)
+        let ticks = (ns as u64 * self.sysclk.to_Hz() as u64).div_ceil(1_000_000_000);
+        self.delay_ticks(ticks);
+    }
 
-                remaining -= current;
-            }
-        }
+    fn delay_us(&mut self, us: u32) {
+        // ticks = ceil(us * sysclk_hz / 1_000_000)
+        let ticks = (us as u64 * self.sysclk.to_Hz() as u64).div_ceil(1_000_000);
+        self.delay_ticks(ticks);
     }
 
     fn delay_ms(&mut self, ms: u32) {
-        // For millisecond delays, call delay_us in chunks to avoid overflow
-        for _ in 0..ms {
-            self.delay_us(1000);
-        }
+        // ticks = ceil(ms * sysclk_hz / 1_000)
+        let ticks = (ms as u64 * self.sysclk.to_Hz() as u64).div_ceil(1_000);
+        self.delay_ticks(ticks);
     }
 }
@@ -0,0 +1,214 @@
+//! Low-power delay using SYSRTC0
+//!
+//! SYSRTC0 is a 32-bit counter clocked from LFCLK that keeps running in EM2.
+//! [`SysrtcDelay`] selects the oscillator reported by
+//! [`FrozenClocks::lf_source`] (the LFXO when configured, the LFRCO otherwise)
+//! and counts at [`FrozenClocks::lfclk`]. It arms the GRP0 compare channel and
+//! sleeps with WFI until it matches, so the core is not kept busy during long
+//! delays.
+//!
+//! # Wake-Up Without a Handler
+//!
+//! The delay does not need a handler bound to the SYSRTC_APP interrupt, so the
+//! interrupt is never taken. Instead, the delay enables SYSRTC_APP in the NVIC
+//! only inside a critical section, executes WFI (which returns when an enabled
+//! interrupt becomes pending, even with PRIMASK set), then masks and unpends it
+//! again before the critical section ends. Other interrupts that wake the core
+//! are serviced between sleeps.
+//!
+//! # Resolution
+//!
+//! One SYSRTC tick is ~30.5 µs at 32.768 kHz. Delays are rounded up to whole
+//! ticks, and delays of a few ticks are busy-waited since sleeping would not
+//! save anything.
+
+use cortex_m::peripheral::NVIC;
+use embedded_hal::delay::DelayNs;
+
+use crate::clock::{FrozenClocks, LfClockSource};
+use crate::pac;
+use crate::regs;
+
+/// Delays up to this many ticks are busy-waited
+const MIN_SLEEP_TICKS: u32 = 3;
+
+/// GRP0 IF/IEN compare 0 bit
+const IF_CMP0: u32 = 1 << 1;
+
+/// Energy mode entered while a [`SysrtcDelay`] sleeps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SleepMode {
+    /// EM1 (Sleep): core clock stopped, peripherals keep running
+    Em1,
+    /// EM2 (Deep Sleep): high-frequency clocks stopped (SLEEPDEEP)
+    Em2,
+}
+
+/// Delay provider using SYSRTC0
+///
+/// Sleeps in [`SleepMode::Em1`] by default. In EM2, HF peripherals (TIMERs,
+/// USART, ...) stop while the delay sleeps.
+pub struct SysrtcDelay {
+    sysrtc: pac::Sysrtc0S,
+    sleep_mode: SleepMode,
+    /// SYSRTC0 counter frequency (LFCLK)
+    counter_hz: u64,
+}
+
+impl SysrtcDelay {
+    /// Create a new SYSRTC0 delay provider
+    ///
+    /// Selects the LFCLK oscillator (see [`FrozenClocks::lf_source`]) as SYSRTC
+    /// clock and starts the counter.
+    ///
+    /// # Arguments
+    ///
+    /// * `sysrtc` - SYSRTC0_S peripheral instance
+    /// * `clocks` - Frozen clock configuration
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// let mut delay = SysrtcDelay::new(dp.sysrtc0_s, &clocks);
+    /// delay.set_sleep_mode(SleepMode::Em2);
+    /// delay.delay_ms(500);
+    /// ```
+    pub fn new(sysrtc: pac::Sysrtc0S, clocks: &FrozenClocks) -> Self {
+        clocks.enable_peripheral_clock(|cmu| {
+            cmu.sysrtc0clkctrl().write(|w| match clocks.lf_source() {
+                LfClockSource::Lfxo => w.clksel().lfxo(),
+                LfClockSource::Lfrco => w.clksel().lfrco(),
+            });
+            cmu.clken0().modify(|_, w| w.sysrtc0().set_bit());
+        });
+
+        sysrtc.en().write(|w| w.en().set_bit());
+
+        // Writes to the LF domain complete after SYNCBUSY clears
+        sysrtc.grp0_ctrl().write(|w| w.cmp0en().set_bit());
+        while sysrtc.grp0_syncbusy().read().ctrl().bit_is_set() {}
+
+        sysrtc.cmd().write(|w| w.start().set_bit());
+        while sysrtc.syncbusy().read().start().bit_is_set() {}
+
+        Self {
+            sysrtc,
+            sleep_mode: SleepMode::Em1,
+            counter_hz: clocks.lfclk().to_Hz() as u64,
+        }
+    }
+
+    /// Select the energy mode entered while sleeping
+    pub fn set_sleep_mode(&mut self, mode: SleepMode) {
+        self.sleep_mode = mode;
+    }
+
+    /// Release the SYSRTC0 peripheral
+    ///
+    /// The counter keeps running.
+    pub fn free(self) -> pac::Sysrtc0S {
+        self.sysrtc
+    }
+
+    /// Current counter value
+    #[inline]
+    fn now(&self) -> u32 {
+        self.sysrtc.cnt().read().bits()
+    }
+
+    /// Wait for `ticks` SYSRTC ticks, in wrap-safe chunks
+    fn delay_ticks(&mut self, ticks: u64) {
+        const MAX_CHUNK: u64 = 0x8000_0000;
+
+        let mut remaining = ticks;
+        while remaining > 0 {
+            let current = remaining.min(MAX_CHUNK);
+            self.wait(current as u32);
+            remaining -= current;
+        }
+    }
+
+    /// Wait for `ticks` (at most 2^31) SYSRTC ticks
+    fn wait(&mut self, ticks: u32) {
+        let start = self.now();
+
+        if ticks <= MIN_SLEEP_TICKS {
+            while self.now().wrapping_sub(start) < ticks {}
+            return;
+        }
+
+        let target = start.wrapping_add(ticks);
+        while self.sysrtc.grp0_syncbusy().read().cmp0value().bit_is_set() {}
+        // SAFETY: CMP0VALUE is a 32-bit field; any value is valid.
+        self.sysrtc
+            .grp0_cmp0value()
+            .write(|w| unsafe { w.bits(target) });
+        while self.sysrtc.grp0_syncbusy().read().cmp0value().bit_is_set() {}
+
+        regs::clear_bits(self.sysrtc.grp0_if(), IF_CMP0);
+        regs::set_bits(self.sysrtc.grp0_ien(), IF_CMP0);
+
+        loop {
+            let done = critical_section::with(|_cs| {
+                // Once the counter is at or past the target the compare cannot
+                // match any more, so stop instead of sleeping
+                let left = target.wrapping_sub(self.now());
+                if left == 0 || left > ticks {
+                    return true;
+                }
+                if self.sysrtc.grp0_if().read().cmp0().bit_is_set() {
+                    return true;
+                }
+
+                self.sleep();
+                false
+            });
+
+            if done {
+                break;
+            }
+        }
+
+        regs::clear_bits(self.sysrtc.grp0_ien(), IF_CMP0);
+        regs::clear_bits(self.sysrtc.grp0_if(), IF_CMP0);
+    }
+
+    /// Sleep until an enabled interrupt (or the SYSRTC compare) is pending
+    ///
+    /// Must be called inside a critical section.
+    fn sleep(&self) {
+        // SAFETY: Called with PRIMASK set, so SYSRTC_APP is never taken; it is
+        // masked and unpended again before returning. SCB is only used to
+        // toggle SLEEPDEEP around the WFI.
+        unsafe {
+            let mut scb = cortex_m::Peripherals::steal().SCB;
+            NVIC::unmask(pac::Interrupt::SYSRTC_APP);
+
+            if self.sleep_mode == SleepMode::Em2 {
+                scb.set_sleepdeep();
+            }
+            cortex_m::asm::wfi();
+            scb.clear_sleepdeep();
+
+            NVIC::mask(pac::Interrupt::SYSRTC_APP);
+            NVIC::unpend(pac::Interrupt::SYSRTC_APP);
+        }
+    }
+}
+
+impl DelayNs for SysrtcDelay {
+    fn delay_ns(&mut self, ns: u32) {
+        let ticks = (ns as u64 * self.counter_hz).div_ceil(1_000_000_000);
+        self.delay_ticks(ticks);
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        let ticks = (us as u64 * self.counter_hz).div_ceil(1_000_000);
+        self.delay_ticks(ticks);
+    }
+
+    fn delay_ms(&mut self, ms: u32) {
+        let ticks = (ms as u64 * self.counter_hz).div_ceil(1_000);
+        self.delay_ticks(ticks);
+    }
+}
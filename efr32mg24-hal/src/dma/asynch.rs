@@ -0,0 +1,69 @@
+//! LDMA completion interrupt for the async drivers
+//!
+//! Async peripheral transfers sleep until their channel's done flag is set. The
+//! LDMA vector must be bound to [`InterruptHandler`] and the controller created
+//! with [`Dma::new_async`].
+
+use super::{Channel, Dma};
+use crate::asynch::{self, wait_for};
+use crate::interrupt::{
+    self,
+    typelevel::{self, Binding, Handler},
+};
+use crate::pac;
+use crate::regs;
+
+/// LDMA interrupt handler for the async drivers
+///
+/// Bind the LDMA vector to this handler with
+/// [`bind_interrupts!`](crate::bind_interrupts).
+pub struct InterruptHandler {
+    _private: (),
+}
+
+impl Handler<typelevel::LDMA> for InterruptHandler {
+    unsafe fn on_interrupt() {
+        asynch::on_interrupt(pac::Interrupt::LDMA);
+    }
+}
+
+impl Dma {
+    /// Creates a new DMA controller for async transfers
+    ///
+    /// Initializes the controller like [`new`](Self::new), then enables the
+    /// LDMA interrupt in the NVIC. `_irqs` proves that the vector is bound to
+    /// [`InterruptHandler`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// bind_interrupts!(struct Irqs {
+    ///     LDMA => dma::InterruptHandler;
+    /// });
+    ///
+    /// let mut dma = Dma::new_async(dp.ldma_s, Irqs);
+    /// ```
+    pub fn new_async(
+        ldma: pac::LdmaS,
+        _irqs: impl Binding<typelevel::LDMA, InterruptHandler>,
+    ) -> Self {
+        let dma = Self::new(ldma);
+        interrupt::enable::<typelevel::LDMA>();
+        dma
+    }
+}
+
+impl<const N: u8> Channel<N> {
+    /// Wait until the channel has completed a descriptor, sleeping on the LDMA
+    /// interrupt
+    pub(crate) async fn wait_done(&self) {
+        // SAFETY: Only our channel's IEN bit is set, through the SET alias.
+        let ldma = unsafe { &*pac::LdmaS::ptr() };
+        wait_for(
+            &asynch::LDMA[N as usize],
+            || self.is_done(),
+            || regs::set_bits(ldma.ien(), Self::MASK),
+        )
+        .await
+    }
+}
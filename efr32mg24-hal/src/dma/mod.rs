@@ -8,25 +8,27 @@
 //!
 //! - **8 Independent Channels**: CH0-CH7 for concurrent transfers
 //! - **Memory-to-Memory**: Efficient bulk data copying
+//! - **Peripheral Requests**: Channels paced by peripheral signals routed through LDMAXBAR
 //! - **Transfer Sizes**: Byte (8-bit), Halfword (16-bit), Word (32-bit)
 //! - **Blocking Transfers**: Wait for completion with timeout
 //! - **Type-Safe Channels**: Compile-time channel validation
 //! - **Critical Sections**: RTOS-safe atomic operations
 //!
-//! # Current Implementation (Phase 1)
+//! # Current Implementation
 //!
 //! - ✅ Memory-to-memory transfers
 //! - ✅ Software-triggered (SWREQ)
 //! - ✅ Blocking operation
-//! - ✅ Single channel (CH0)
+//! - ✅ All 8 channels (CH0-CH7)
 //! - ✅ All transfer sizes
+//! - ✅ Peripheral request routing (LDMAXBAR), used by the USART DMA methods
+//! - ✅ Circular transfers (a descriptor linked to itself)
 //!
-//! # Future Extensions (Phase 2)
+//! - ✅ Completion interrupt for async drivers (`async` feature, `Dma::new_async`)
 //!
-//! - ⏳ Linked descriptors (chained transfers)
-//! - ⏳ Peripheral-to-memory transfers
-//! - ⏳ Interrupt-driven transfers
-//! - ⏳ All 8 channels
+//! # Future Extensions
+//!
+//! - ⏳ Linked descriptor chains
 //!
 //! # Architecture
 //!
@@ -71,6 +73,17 @@
 //! assert_eq!(dst, [1, 2, 3, 4, 5]);
 //! ```
 //!
+//! # Peripheral Requests
+//!
+//! Peripheral transfers move one unit each time the selected peripheral signal
+//! (a [`Request`]) asserts. The signal is selected in the channel's LDMAXBAR
+//! `CHx_REQSEL` register, which the DMA driver owns along with the LDMA itself.
+//! Peripheral drivers build on this, e.g. [`Usart0::write_dma`] and
+//! [`SpiDma`].
+//!
+//! [`Usart0::write_dma`]: crate::usart::Usart0::write_dma
+//! [`SpiDma`]: crate::spi::SpiDma
+//!
 //! # Safety
 //!
 //! This module uses `unsafe` for direct hardware register access. All unsafe
@@ -79,10 +92,193 @@
 //! - What invariants are maintained
 //! - What preconditions must hold
 
+#[cfg(feature = "async")]
+mod asynch;
 mod types;
+#[cfg(feature = "async")]
+pub use asynch::InterruptHandler;
 pub use types::*;
 
 use crate::pac;
+use crate::regs;
+use core::cell::UnsafeCell;
+
+/// Largest number of units a single descriptor can move (XFERCNT is 11 bits)
+pub(crate) const MAX_TRANSFER_UNITS: usize = 2048;
+
+/// CHx_CTRL field positions and values
+const CTRL_XFERCNT_SHIFT: u32 = 4;
+const CTRL_DONEIEN: u32 = 1 << 20;
+const CTRL_SRCINC_SHIFT: u32 = 24;
+const CTRL_SIZE_SHIFT: u32 = 26;
+const CTRL_DSTINC_SHIFT: u32 = 28;
+const INC_ONE: u32 = 0;
+const INC_NONE: u32 = 3;
+
+/// CHx_LINK: load the descriptor at LINKADDR when the current one is done
+const LINK_LINK: u32 = 1 << 1;
+
+/// LDMAXBAR CHx_REQSEL field positions
+const REQSEL_SOURCESEL_SHIFT: u32 = 16;
+
+/// Run `$body` with the registers of channel `$n` bound to the given names
+///
+/// The PAC models each channel's registers as distinct types, so the body is
+/// expanded once per channel.
+macro_rules! with_channel {
+    ($ldma:expr, $n:expr, |$ctrl:ident, $src:ident, $dst:ident, $link:ident| $body:expr) => {
+        match $n {
+            0 => {
+                let ($ctrl, $src, $dst, $link) = (
+                    $ldma.ch0_ctrl(),
+                    $ldma.ch0_src(),
+                    $ldma.ch0_dst(),
+                    $ldma.ch0_link(),
+                );
+                $body
+            }
+            1 => {
+                let ($ctrl, $src, $dst, $link) = (
+                    $ldma.ch1_ctrl(),
+                    $ldma.ch1_src(),
+                    $ldma.ch1_dst(),
+                    $ldma.ch1_link(),
+                );
+                $body
+            }
+            2 => {
+                let ($ctrl, $src, $dst, $link) = (
+                    $ldma.ch2_ctrl(),
+                    $ldma.ch2_src(),
+                    $ldma.ch2_dst(),
+                    $ldma.ch2_link(),
+                );
+                $body
+            }
+            3 => {
+                let ($ctrl, $src, $dst, $link) = (
+                    $ldma.ch3_ctrl(),
+                    $ldma.ch3_src(),
+                    $ldma.ch3_dst(),
+                    $ldma.ch3_link(),
+                );
+                $body
+            }
+            4 => {
+                let ($ctrl, $src, $dst, $link) = (
+                    $ldma.ch4_ctrl(),
+                    $ldma.ch4_src(),
+                    $ldma.ch4_dst(),
+                    $ldma.ch4_link(),
+                );
+                $body
+            }
+            5 => {
+                let ($ctrl, $src, $dst, $link) = (
+                    $ldma.ch5_ctrl(),
+                    $ldma.ch5_src(),
+                    $ldma.ch5_dst(),
+                    $ldma.ch5_link(),
+                );
+                $body
+            }
+            6 => {
+                let ($ctrl, $src, $dst, $link) = (
+                    $ldma.ch6_ctrl(),
+                    $ldma.ch6_src(),
+                    $ldma.ch6_dst(),
+                    $ldma.ch6_link(),
+                );
+                $body
+            }
+            7 => {
+                let ($ctrl, $src, $dst, $link) = (
+                    $ldma.ch7_ctrl(),
+                    $ldma.ch7_src(),
+                    $ldma.ch7_dst(),
+                    $ldma.ch7_link(),
+                );
+                $body
+            }
+            _ => unreachable!(),
+        }
+    };
+}
+
+/// Write channel `$n`'s request selection in LDMAXBAR
+macro_rules! write_reqsel {
+    ($xbar:expr, $n:expr, $bits:expr) => {
+        match $n {
+            0 => $xbar.ch0_reqsel().write(|w| unsafe { w.bits($bits) }),
+            1 => $xbar.ch1_reqsel().write(|w| unsafe { w.bits($bits) }),
+            2 => $xbar.ch2_reqsel().write(|w| unsafe { w.bits($bits) }),
+            3 => $xbar.ch3_reqsel().write(|w| unsafe { w.bits($bits) }),
+            4 => $xbar.ch4_reqsel().write(|w| unsafe { w.bits($bits) }),
+            5 => $xbar.ch5_reqsel().write(|w| unsafe { w.bits($bits) }),
+            6 => $xbar.ch6_reqsel().write(|w| unsafe { w.bits($bits) }),
+            7 => $xbar.ch7_reqsel().write(|w| unsafe { w.bits($bits) }),
+            _ => unreachable!(),
+        }
+    };
+}
+
+/// LDMA transfer descriptor, in the layout the controller loads from memory
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub(crate) struct Descriptor {
+    ctrl: u32,
+    src: u32,
+    dst: u32,
+    link: u32,
+}
+
+impl Descriptor {
+    const EMPTY: Self = Self {
+        ctrl: 0,
+        src: 0,
+        dst: 0,
+        link: 0,
+    };
+
+    /// Peripheral transfer of `count` units, one unit per request
+    ///
+    /// An address that is not incremented stays on a peripheral data register.
+    /// The done flag (IF bit `N`) is set when the last unit has moved.
+    pub(crate) fn peripheral(
+        size: TransferSize,
+        count: usize,
+        src: u32,
+        src_inc: bool,
+        dst: u32,
+        dst_inc: bool,
+    ) -> Self {
+        debug_assert!(count > 0 && count <= MAX_TRANSFER_UNITS);
+        let inc = |enabled| if enabled { INC_ONE } else { INC_NONE };
+        Self {
+            ctrl: ((count as u32 - 1) << CTRL_XFERCNT_SHIFT)
+                | CTRL_DONEIEN
+                | (inc(src_inc) << CTRL_SRCINC_SHIFT)
+                | ((size as u32) << CTRL_SIZE_SHIFT)
+                | (inc(dst_inc) << CTRL_DSTINC_SHIFT),
+            src,
+            dst,
+            link: 0,
+        }
+    }
+}
+
+/// Per-channel descriptors that circular transfers link back to
+///
+/// The controller reloads the descriptor from memory each time the buffer wraps,
+/// so it must outlive the transfer.
+struct LoopDescriptors([UnsafeCell<Descriptor>; 8]);
+
+// SAFETY: Slot `N` is only written by the owner of `Channel<N>`, while that
+// channel is disabled. The LDMA reads it afterwards.
+unsafe impl Sync for LoopDescriptors {}
+
+static LOOP_DESCRIPTORS: LoopDescriptors =
+    LoopDescriptors([const { UnsafeCell::new(Descriptor::EMPTY) }; 8]);
 
 /// DMA Controller
 ///
@@ -91,7 +287,8 @@ use crate::pac;
 /// # Ownership
 ///
 /// This struct takes ownership of the `LDMA_S` peripheral, ensuring exclusive
-/// access and preventing multiple mutable references.
+/// access and preventing multiple mutable references. The LDMAXBAR request
+/// crossbar has no other users and is driven through the channels as well.
 pub struct Dma {
     _ldma: pac::LdmaS,
 }
@@ -112,12 +309,14 @@ impl Dma {
     /// let dma = Dma::new(dp.ldma_s);
     /// ```
     pub fn new(ldma: pac::LdmaS) -> Self {
-        // Enable LDMA clock
+        // Enable LDMA and LDMAXBAR clocks
         // SAFETY: We have exclusive access to CMU via critical section.
-        // Setting LDMA bit enables the DMA controller clock.
+        // Setting the LDMA and LDMAXBAR bits enables the DMA controller and
+        // request crossbar clocks.
         critical_section::with(|_cs| {
             let cmu = unsafe { &(*pac::CmuS::ptr()) };
-            cmu.clken0().modify(|_, w| w.ldma().set_bit());
+            cmu.clken0()
+                .modify(|_, w| w.ldma().set_bit().ldmaxbar().set_bit());
         });
 
         // Enable LDMA peripheral
@@ -139,9 +338,42 @@ impl Dma {
     /// let mut ch0 = dma.channel0();
     /// ```
     pub fn channel0(&mut self) -> Channel<0> {
-        Channel {
-            _marker: core::marker::PhantomData,
-        }
+        Channel::new()
+    }
+
+    /// Returns Channel 1
+    pub fn channel1(&mut self) -> Channel<1> {
+        Channel::new()
+    }
+
+    /// Returns Channel 2
+    pub fn channel2(&mut self) -> Channel<2> {
+        Channel::new()
+    }
+
+    /// Returns Channel 3
+    pub fn channel3(&mut self) -> Channel<3> {
+        Channel::new()
+    }
+
+    /// Returns Channel 4
+    pub fn channel4(&mut self) -> Channel<4> {
+        Channel::new()
+    }
+
+    /// Returns Channel 5
+    pub fn channel5(&mut self) -> Channel<5> {
+        Channel::new()
+    }
+
+    /// Returns Channel 6
+    pub fn channel6(&mut self) -> Channel<6> {
+        Channel::new()
+    }
+
+    /// Returns Channel 7
+    pub fn channel7(&mut self) -> Channel<7> {
+        Channel::new()
     }
 }
 
@@ -157,6 +389,18 @@ pub struct Channel<const N: u8> {
 }
 
 impl<const N: u8> Channel<N> {
+    /// Channel bit in the CHEN/CHDIS/CHBUSY/IF registers
+    const MASK: u32 = {
+        assert!(N < 8, "LDMA has channels 0-7");
+        1 << N
+    };
+
+    const fn new() -> Self {
+        Self {
+            _marker: core::marker::PhantomData,
+        }
+    }
+
     /// Performs a blocking memory-to-memory transfer
     ///
     /// Copies data from `src` to `dst` using DMA. Blocks until transfer completes
@@ -201,53 +445,56 @@ impl<const N: u8> Channel<N> {
             let ldma = unsafe { &(*pac::LdmaS::ptr()) };
 
             // Ensure channel is idle before configuration
-            if N == 0 && ldma.chbusy().read().bits() & (1 << N) != 0 {
+            if ldma.chbusy().read().bits() & Self::MASK != 0 {
                 return Err(DmaError::Busy);
             }
 
+            // Software-triggered: no peripheral request
+            Self::select_request(None);
+
             // Configure transfer control
-            // SAFETY: Writing to CH0_CTRL register configures the transfer parameters.
+            // SAFETY: Writing to CHx_CTRL register configures the transfer parameters.
             // - STRUCTTYPE = Transfer (0)
             // - XFERCNT = number of units to transfer
             // - SIZE = transfer unit size
             // - SRCINC/DSTINC = One (increment by one unit)
             // - BLOCKSIZE = All (transfer all at once)
+            with_channel!(ldma, N, |ctrl, src_reg, dst_reg, link| {
+                ctrl.write(|w| unsafe {
+                    w.structtype()
+                        .transfer()
+                        .xfercnt()
+                        .bits(xfer_count - 1) // Hardware uses count-1
+                        .size()
+                        .bits(size as u8)
+                        .srcinc()
+                        .one()
+                        .dstinc()
+                        .one()
+                        .blocksize()
+                        .all()
+                        .doneien()
+                        .set_bit() // Enable done interrupt flag
+                });
+
+                // Configure addresses
+                // SAFETY: Writing absolute addresses for memory-to-memory transfer.
+                src_reg.write(|w| unsafe { w.bits(src.as_ptr() as u32) });
+                dst_reg.write(|w| unsafe { w.bits(dst.as_mut_ptr() as u32) });
+                link.write(|w| unsafe { w.bits(0) });
+            });
+
+            // Clear done flag
+            regs::clear_bits(ldma.if_(), Self::MASK);
+
+            // SAFETY: CHEN and SWREQ are write-one command registers for our
+            // channel bit only.
             unsafe {
-                match N {
-                    0 => {
-                        ldma.ch0_ctrl().write(|w| {
-                            w.structtype()
-                                .transfer()
-                                .xfercnt()
-                                .bits(xfer_count - 1) // Hardware uses count-1
-                                .size()
-                                .bits(size as u8)
-                                .srcinc()
-                                .one()
-                                .dstinc()
-                                .one()
-                                .blocksize()
-                                .all()
-                                .doneien()
-                                .set_bit() // Enable done interrupt flag
-                        });
-
-                        // Configure addresses
-                        // SAFETY: Writing absolute addresses for memory-to-memory transfer.
-                        ldma.ch0_src().write(|w| w.bits(src.as_ptr() as u32));
-                        ldma.ch0_dst().write(|w| w.bits(dst.as_mut_ptr() as u32));
-
-                        // Clear done flag
-                        ldma.if_().write(|w| w.bits(1 << N));
-
-                        // Enable channel
-                        ldma.chen().write(|w| w.bits(1 << N));
-
-                        // Trigger software request
-                        ldma.swreq().write(|w| w.bits(1 << N));
-                    }
-                    _ => return Err(DmaError::Unsupported),
-                }
+                // Enable channel
+                ldma.chen().write(|w| w.bits(Self::MASK));
+
+                // Trigger software request
+                ldma.swreq().write(|w| w.bits(Self::MASK));
             }
 
             Ok(())
@@ -257,26 +504,104 @@ impl<const N: u8> Channel<N> {
         let timeout_cycles = 1_000_000; // ~13ms at 78 MHz
         let mut cycles = 0;
 
-        // SAFETY: Reading status registers to poll for completion.
-        let ldma = unsafe { &(*pac::LdmaS::ptr()) };
-
-        loop {
-            // Check if done flag is set
-            if ldma.if_().read().bits() & (1 << N) != 0 {
-                // Clear done flag
-                // SAFETY: Writing to IF register clears the interrupt flag.
-                unsafe {
-                    ldma.if_().write(|w| w.bits(1 << N));
-                }
-                break;
-            }
-
+        while !self.is_done() {
             cycles += 1;
             if cycles > timeout_cycles {
                 return Err(DmaError::Timeout);
             }
         }
+        self.clear_done();
 
         Ok(())
     }
+
+    /// Start a peripheral transfer described by `descriptor`, paced by `request`
+    ///
+    /// With `circular`, the channel reloads the same descriptor each time it
+    /// completes, so the transfer runs until [`stop`](Self::stop).
+    ///
+    /// # Safety
+    ///
+    /// The memory the descriptor points at must stay valid until the channel is
+    /// stopped or, for a one-shot transfer, reports done.
+    pub(crate) unsafe fn start(
+        &mut self,
+        request: Request,
+        descriptor: Descriptor,
+        circular: bool,
+    ) {
+        // SAFETY: We own channel N. Only its own registers and bits are written.
+        let ldma = unsafe { &(*pac::LdmaS::ptr()) };
+        self.stop();
+
+        let mut first = descriptor;
+        if circular {
+            let slot = LOOP_DESCRIPTORS.0[N as usize].get();
+            // SAFETY: The channel is disabled, so the LDMA is not reading the slot,
+            // and only the owner of `Channel<N>` writes it.
+            unsafe {
+                slot.write(Descriptor {
+                    link: (slot as u32) | LINK_LINK,
+                    ..descriptor
+                })
+            };
+            first.link = (slot as u32) | LINK_LINK;
+        }
+
+        critical_section::with(|_cs| {
+            Self::select_request(Some(request));
+            with_channel!(ldma, N, |ctrl, src, dst, link| {
+                // SAFETY: Raw descriptor words in the CHx register layout.
+                ctrl.write(|w| unsafe { w.bits(first.ctrl) });
+                src.write(|w| unsafe { w.bits(first.src) });
+                dst.write(|w| unsafe { w.bits(first.dst) });
+                link.write(|w| unsafe { w.bits(first.link) });
+            });
+            regs::clear_bits(ldma.if_(), Self::MASK);
+            // SAFETY: CHEN is a write-one command register for our channel bit.
+            ldma.chen().write(|w| unsafe { w.bits(Self::MASK) });
+        });
+    }
+
+    /// Disable the channel and wait until it is idle
+    pub(crate) fn stop(&mut self) {
+        // SAFETY: We own channel N. CHDIS is a write-one command register.
+        let ldma = unsafe { &(*pac::LdmaS::ptr()) };
+        ldma.chdis().write(|w| unsafe { w.bits(Self::MASK) });
+        while ldma.chbusy().read().bits() & Self::MASK != 0 {
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Check whether the channel has completed a descriptor (IF bit `N`)
+    pub(crate) fn is_done(&self) -> bool {
+        // SAFETY: Read-only access to the LDMA interrupt flags.
+        let ldma = unsafe { &(*pac::LdmaS::ptr()) };
+        ldma.if_().read().bits() & Self::MASK != 0
+    }
+
+    /// Clear the channel's done flag
+    pub(crate) fn clear_done(&self) {
+        // SAFETY: Clears only our channel's flag through the CLR alias.
+        let ldma = unsafe { &(*pac::LdmaS::ptr()) };
+        regs::clear_bits(ldma.if_(), Self::MASK);
+    }
+
+    /// Address the channel will write next (CHx_DST)
+    pub(crate) fn destination(&self) -> u32 {
+        // SAFETY: Read-only access to our channel's DST register.
+        let ldma = unsafe { &(*pac::LdmaS::ptr()) };
+        with_channel!(ldma, N, |_ctrl, _src, dst, _link| dst.read().bits())
+    }
+
+    /// Route `request` to this channel, or none for software-triggered transfers
+    fn select_request(request: Option<Request>) {
+        let bits = request.map_or(0, |request| {
+            ((request.source() as u32) << REQSEL_SOURCESEL_SHIFT) | request.signal() as u32
+        });
+        // SAFETY: The DMA driver is the only user of LDMAXBAR, and channel N's
+        // REQSEL register belongs to the owner of `Channel<N>`.
+        let xbar = unsafe { &(*pac::LdmaxbarS::ptr()) };
+        write_reqsel!(xbar, N, bits);
+    }
 }
@@ -27,10 +27,51 @@ pub enum DmaError {
     Busy,
     /// Transfer did not complete within timeout period
     Timeout,
-    /// Operation not supported (e.g., channel number >0 in Phase 1)
+    /// Operation not supported
     Unsupported,
 }
 
+/// Peripheral DMA request
+///
+/// The peripheral signal that paces a channel, selected through the LDMAXBAR
+/// crossbar (`CHx_REQSEL`: SOURCESEL and SIGSEL).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Request {
+    /// USART0 receive data valid (RXDATAV)
+    Usart0RxDataValid,
+    /// USART0 transmit buffer level (TXBL)
+    Usart0TxBufferLevel,
+    /// EUSART0 receive FIFO level (RXFL)
+    Eusart0RxFifoLevel,
+    /// EUSART0 transmit FIFO level (TXFL)
+    Eusart0TxFifoLevel,
+    /// EUSART1 receive FIFO level (RXFL)
+    Eusart1RxFifoLevel,
+    /// EUSART1 transmit FIFO level (TXFL)
+    Eusart1TxFifoLevel,
+}
+
+impl Request {
+    /// LDMAXBAR SOURCESEL value
+    pub(crate) const fn source(self) -> u8 {
+        match self {
+            Request::Usart0RxDataValid | Request::Usart0TxBufferLevel => 0x04,
+            Request::Eusart0RxFifoLevel | Request::Eusart0TxFifoLevel => 0x0F,
+            Request::Eusart1RxFifoLevel | Request::Eusart1TxFifoLevel => 0x10,
+        }
+    }
+
+    /// LDMAXBAR SIGSEL value within the source
+    pub(crate) const fn signal(self) -> u8 {
+        match self {
+            Request::Usart0RxDataValid => 0x00,
+            Request::Usart0TxBufferLevel => 0x02,
+            Request::Eusart0RxFifoLevel | Request::Eusart1RxFifoLevel => 0x00,
+            Request::Eusart0TxFifoLevel | Request::Eusart1TxFifoLevel => 0x01,
+        }
+    }
+}
+
 /// Trait for types that can be transferred via DMA
 ///
 /// This trait defines types that have a valid DMA transfer size.
@@ -0,0 +1,203 @@
+//! embedded-hal-async digital Wait implementation for input pins
+//!
+//! An input pin is turned into an [`ExtiInput`] with
+//! [`Pin::into_exti_input`]. Each pin uses the external interrupt (EXTI) line
+//! with the same number as the pin, so PA3 and PB3 cannot be awaited at the same
+//! time. Even lines raise the GPIO_EVEN interrupt and odd lines GPIO_ODD; both
+//! must be bound to [`InterruptHandler`].
+
+use embedded_hal::digital::{ErrorType, InputPin};
+use embedded_hal_async::digital::Wait;
+
+use super::pin::Pin;
+use super::types::{GpioError, Input};
+use crate::asynch::{self, wait_for};
+use crate::interrupt::{
+    self,
+    typelevel::{self, Binding, Handler},
+};
+use crate::pac;
+use crate::regs;
+
+/// GPIO interrupt handler for [`ExtiInput`]
+///
+/// Bind GPIO_EVEN and GPIO_ODD to this handler with
+/// [`bind_interrupts!`](crate::bind_interrupts).
+pub struct InterruptHandler {
+    _private: (),
+}
+
+impl Handler<typelevel::GPIO_EVEN> for InterruptHandler {
+    unsafe fn on_interrupt() {
+        asynch::on_interrupt(pac::Interrupt::GPIO_EVEN);
+    }
+}
+
+impl Handler<typelevel::GPIO_ODD> for InterruptHandler {
+    unsafe fn on_interrupt() {
+        asynch::on_interrupt(pac::Interrupt::GPIO_ODD);
+    }
+}
+
+/// Input pin that can be awaited through its EXTI line
+pub struct ExtiInput<const PORT: char, const PIN: u8, PULL> {
+    pin: Pin<PORT, PIN, Input<PULL>>,
+}
+
+impl<const PORT: char, const PIN: u8, PULL> Pin<PORT, PIN, Input<PULL>> {
+    /// Convert the pin into an [`ExtiInput`] that implements
+    /// `embedded_hal_async::digital::Wait`
+    ///
+    /// Enables the GPIO_EVEN and GPIO_ODD interrupts in the NVIC. `_irqs` proves
+    /// that both vectors are bound to [`InterruptHandler`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// bind_interrupts!(struct Irqs {
+    ///     GPIO_EVEN => gpio::InterruptHandler;
+    ///     GPIO_ODD => gpio::InterruptHandler;
+    /// });
+    ///
+    /// let mut button = gpio.portb.pb1.into_pull_up_input().into_exti_input(Irqs);
+    /// button.wait_for_falling_edge().await.unwrap();
+    /// ```
+    pub fn into_exti_input(
+        self,
+        _irqs: impl Binding<typelevel::GPIO_EVEN, InterruptHandler>
+            + Binding<typelevel::GPIO_ODD, InterruptHandler>,
+    ) -> ExtiInput<PORT, PIN, PULL> {
+        interrupt::enable::<typelevel::GPIO_EVEN>();
+        interrupt::enable::<typelevel::GPIO_ODD>();
+        ExtiInput { pin: self }
+    }
+}
+
+/// Edge(s) that complete a wait
+#[derive(Clone, Copy)]
+enum Edge {
+    Rising,
+    Falling,
+    Any,
+}
+
+impl<const PORT: char, const PIN: u8, PULL> ExtiInput<PORT, PIN, PULL> {
+    /// Release the underlying input pin
+    pub fn into_inner(self) -> Pin<PORT, PIN, Input<PULL>> {
+        self.pin
+    }
+
+    /// EXTI line bit used by this pin
+    const LINE: u32 = 1 << PIN;
+
+    /// Route this pin to its EXTI line and enable edge detection
+    ///
+    /// Clears any stale interrupt flag, so an edge seen afterwards is new.
+    fn listen(&mut self, edge: Edge) {
+        self.pin.select_exti_line();
+
+        match edge {
+            Edge::Rising => {
+                regs::set_bits(self.gpio().extirise(), Self::LINE);
+                regs::clear_bits(self.gpio().extifall(), Self::LINE);
+            }
+            Edge::Falling => {
+                regs::clear_bits(self.gpio().extirise(), Self::LINE);
+                regs::set_bits(self.gpio().extifall(), Self::LINE);
+            }
+            Edge::Any => {
+                regs::set_bits(self.gpio().extirise(), Self::LINE);
+                regs::set_bits(self.gpio().extifall(), Self::LINE);
+            }
+        }
+
+        regs::clear_bits(self.gpio().if_(), Self::LINE);
+    }
+
+    /// Disable edge detection and the interrupt for this pin's EXTI line
+    fn unlisten(&mut self) {
+        let gpio = self.gpio();
+        regs::clear_bits(gpio.ien(), Self::LINE);
+        regs::clear_bits(gpio.extirise(), Self::LINE);
+        regs::clear_bits(gpio.extifall(), Self::LINE);
+        regs::clear_bits(gpio.if_(), Self::LINE);
+    }
+
+    /// Sleep until the edge configured by [`listen`](Self::listen) is detected
+    async fn wait_for_line(&mut self) {
+        let gpio = self.gpio();
+        wait_for(
+            &asynch::GPIO_EXTI[PIN as usize],
+            || gpio.if_().read().bits() & Self::LINE != 0,
+            || regs::set_bits(gpio.ien(), Self::LINE),
+        )
+        .await;
+        self.unlisten();
+    }
+
+    /// Wait for an edge, returning early if `level` is already reached
+    async fn wait_for_level(&mut self, high: bool) -> Result<(), GpioError> {
+        self.listen(if high { Edge::Rising } else { Edge::Falling });
+
+        // Edge detection is armed before the level is sampled, so a transition
+        // between the two is still caught
+        match self.pin.is_high() {
+            Ok(level) if level != high => self.wait_for_line().await,
+            result => {
+                self.unlisten();
+                result?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Access the GPIO register block
+    fn gpio(&self) -> &'static pac::gpio_s::RegisterBlock {
+        // SAFETY: Only this pin's bit is modified in the EXTI, IF and IEN
+        // registers, through the SET/CLR aliases.
+        unsafe { &*pac::GpioS::ptr() }
+    }
+}
+
+impl<const PORT: char, const PIN: u8, PULL> ErrorType for ExtiInput<PORT, PIN, PULL> {
+    type Error = GpioError;
+}
+
+impl<const PORT: char, const PIN: u8, PULL> InputPin for ExtiInput<PORT, PIN, PULL> {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        self.pin.is_high()
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        self.pin.is_low()
+    }
+}
+
+impl<const PORT: char, const PIN: u8, PULL> Wait for ExtiInput<PORT, PIN, PULL> {
+    async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+        self.wait_for_level(true).await
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+        self.wait_for_level(false).await
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+        self.listen(Edge::Rising);
+        self.wait_for_line().await;
+        Ok(())
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+        self.listen(Edge::Falling);
+        self.wait_for_line().await;
+        Ok(())
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+        self.listen(Edge::Any);
+        self.wait_for_line().await;
+        Ok(())
+    }
+}
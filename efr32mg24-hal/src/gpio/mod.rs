@@ -7,7 +7,8 @@
 //! The EFR32MG24 provides multiple GPIO ports (A, B, C, D) with various capabilities:
 //! - Digital input/output with configurable drive strength
 //! - Internal pull-up/pull-down resistors
-//! - Interrupt capability on pin state changes
+//! - Interrupt capability on pin state changes (`ExtiInput` implementing
+//!   `embedded_hal_async::digital::Wait`, `async` feature)
 //! - Alternative function routing for peripherals
 //!
 //! # Type-Safe Pin Management
@@ -68,11 +69,15 @@
 //! let mut led = gpio.portb.pb2.into_push_pull_output_with_drive(DriveStrength::Strong);
 //! ```
 
+#[cfg(feature = "async")]
+mod asynch;
 mod pin;
 mod traits;
 mod types;
 
 // Re-export public types
+#[cfg(feature = "async")]
+pub use asynch::{ExtiInput, InterruptHandler};
 pub use pin::Pin;
 pub use types::{
     Alternate, Analog, DriveStrength, Floating, GpioError, Input, Output, Parts, PinMode, PortA,
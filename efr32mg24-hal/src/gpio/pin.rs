@@ -22,6 +22,41 @@ impl<const PORT: char, const PIN: u8, MODE> Pin<PORT, PIN, MODE> {
         Self { _mode: PhantomData }
     }
 
+    /// Port and pin numbers, as selected in the GPIO peripheral route registers
+    pub(crate) const fn route_location(&self) -> (u8, u8) {
+        (PORT as u8 - b'A', PIN)
+    }
+
+    /// Select this pin for the external interrupt (EXTI) line with its number
+    pub(crate) fn select_exti_line(&self) {
+        let (port, _) = self.route_location();
+        let port = port as u32;
+
+        critical_section::with(|_cs| {
+            let gpio = unsafe { &(*crate::pac::GpioS::ptr()) };
+
+            // EXTIPSEL selects the port (4-bit fields), EXTIPINSEL the pin
+            // within the group of four pins sharing the line's range
+            let shift = (PIN % 8) * 4;
+            let pinsel = (PIN % 4) as u32;
+            if PIN < 8 {
+                gpio.extipsell().modify(|r, w| unsafe {
+                    w.bits((r.bits() & !(0xF << shift)) | (port << shift))
+                });
+                gpio.extipinsell().modify(|r, w| unsafe {
+                    w.bits((r.bits() & !(0xF << shift)) | (pinsel << shift))
+                });
+            } else {
+                gpio.extipselh().modify(|r, w| unsafe {
+                    w.bits((r.bits() & !(0xF << shift)) | (port << shift))
+                });
+                gpio.extipinselh().modify(|r, w| unsafe {
+                    w.bits((r.bits() & !(0xF << shift)) | (pinsel << shift))
+                });
+            }
+        });
+    }
+
     /// Convert pin to push-pull output mode with standard drive strength
     pub fn into_push_pull_output(self) -> Pin<PORT, PIN, Output<0>> {
         // Configure pin as push-pull output
@@ -0,0 +1,227 @@
+//! embedded-hal-async I2C trait implementations
+//!
+//! A transaction is a single START ... STOP sequence: adjacent operations of the
+//! same direction share one address phase, and a repeated START is generated
+//! only when the direction changes. The driver sleeps on the I2C0/I2C1
+//! interrupt between bus events; the vector must be bound to
+//! [`InterruptHandler`] and the driver created with `new_async`.
+//!
+//! [`Config::timeout`] only bounds the blocking waits. An async transfer is
+//! aborted by the hardware bus idle timeout and clock low timeout
+//! ([`Config::with_clock_low_timeout`]), which `new_async` enables at its
+//! longest setting, [`ClockLowTimeout::Cycles1024`], when the configuration
+//! leaves it disabled: without it, a target holding SCL low would leave the
+//! transfer waiting forever.
+
+use super::{
+    check_reads, flags_error, ClockLowTimeout, Config, Error, I2c, Instance, IF_ACK, IF_ARBLOST,
+    IF_BITO, IF_BUSERR, IF_CLTO, IF_MSTOP, IF_NACK,
+};
+use crate::asynch::{self, wait_for};
+use crate::clock::FrozenClocks;
+use crate::interrupt::{
+    self,
+    typelevel::{self, Binding, Handler, Interrupt},
+};
+use crate::pac;
+use crate::regs;
+use embedded_hal_async::i2c::Operation;
+
+/// I2C IF/IEN RXDATAV bit position
+const IF_RXDATAV: u32 = 1 << 5;
+
+/// Flags that abort a transfer
+const IF_ERRORS: u32 = IF_ARBLOST | IF_BUSERR | IF_BITO | IF_CLTO;
+
+/// I2C interrupt handler for the async drivers
+///
+/// Bind I2C0 and/or I2C1 to this handler with
+/// [`bind_interrupts!`](crate::bind_interrupts).
+pub struct InterruptHandler {
+    _private: (),
+}
+
+impl Handler<typelevel::I2C0> for InterruptHandler {
+    unsafe fn on_interrupt() {
+        asynch::on_interrupt(pac::Interrupt::I2C0);
+    }
+}
+
+impl Handler<typelevel::I2C1> for InterruptHandler {
+    unsafe fn on_interrupt() {
+        asynch::on_interrupt(pac::Interrupt::I2C1);
+    }
+}
+
+impl<I: Instance> I2c<I> {
+    /// Create a new I2C master instance for async use
+    ///
+    /// Configures the peripheral like [`new`](Self::new), then enables its interrupt in
+    /// the NVIC. `_irq` proves that the vector is bound to
+    /// [`InterruptHandler`].
+    ///
+    /// A disabled clock low timeout is replaced by
+    /// [`ClockLowTimeout::Cycles1024`] (1.28 ms at 100 kHz), so a target holding
+    /// SCL low ends the transfer with [`Error::Timeout`]. The timeout scales
+    /// with the SCL period; run a target that stretches the clock for longer
+    /// at a lower [`Speed::Custom`](super::Speed::Custom) frequency, or use the
+    /// blocking driver.
+    pub fn new_async(
+        i2c: I,
+        config: Config,
+        clocks: &FrozenClocks,
+        _irq: impl Binding<I::Interrupt, InterruptHandler>,
+    ) -> Self
+    where
+        InterruptHandler: Handler<I::Interrupt>,
+    {
+        let config = match config.clock_low_timeout {
+            ClockLowTimeout::Disabled => config.with_clock_low_timeout(ClockLowTimeout::Cycles1024),
+            _ => config,
+        };
+        let i2c = Self::new(i2c, config, clocks);
+        interrupt::enable::<I::Interrupt>();
+        i2c
+    }
+
+    /// Wait until one of the IF flags in `mask` (or a bus error) is set
+    ///
+    /// Returns the IF flags that were set.
+    async fn wait_flags(&mut self, mask: u32) -> u32 {
+        let i2c = I::regs();
+        let mask = mask | IF_ERRORS;
+        wait_for(
+            asynch::waker(I::Interrupt::IRQ),
+            || i2c.if_().read().bits() & mask != 0,
+            || regs::set_bits(i2c.ien(), mask),
+        )
+        .await;
+        I::regs().if_().read().bits() & mask
+    }
+
+    /// Transmit one byte (address or data) and wait for the ACK/NACK
+    async fn send_byte(&mut self, byte: u8) -> Result<(), Error> {
+        regs::clear_bits(I::regs().if_(), IF_ACK | IF_NACK);
+        I::regs().txdata().write(|w| unsafe { w.bits(byte as u32) });
+
+        let flags = self.wait_flags(IF_ACK | IF_NACK).await;
+        if let Some(error) = flags_error(flags) {
+            Err(error)
+        } else if flags & IF_NACK != 0 {
+            Err(Error::Nack)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Receive one byte, then ACK it or (for the last byte) NACK it
+    async fn receive_byte(&mut self, last: bool) -> Result<u8, Error> {
+        let i2c = I::regs();
+        wait_for(
+            asynch::waker(I::Interrupt::IRQ),
+            || {
+                i2c.status().read().rxdatav().bit_is_set()
+                    || i2c.if_().read().bits() & IF_ERRORS != 0
+            },
+            || regs::set_bits(i2c.ien(), IF_RXDATAV | IF_ERRORS),
+        )
+        .await;
+
+        if let Some(error) = flags_error(I::regs().if_().read().bits()) {
+            return Err(error);
+        }
+
+        let byte = I::regs().rxdata().read().bits() as u8;
+        if last {
+            I::regs().cmd().write(|w| w.nack().set_bit());
+        } else {
+            I::regs().cmd().write(|w| w.ack().set_bit());
+        }
+        Ok(byte)
+    }
+
+    /// Run all operations between START and STOP
+    async fn run_operations(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Error> {
+        let mut previous_read = None;
+
+        for index in 0..operations.len() {
+            // A read run ends with a NACK when the next operation is not a
+            // read; reads are never empty (check_reads), so a following read
+            // receives at least one more byte
+            let next_is_read = matches!(operations.get(index + 1), Some(Operation::Read(_)));
+
+            match &mut operations[index] {
+                Operation::Write(bytes) => {
+                    if previous_read != Some(false) {
+                        I::regs().cmd().write(|w| w.start().set_bit());
+                        self.send_byte(address << 1).await?;
+                    }
+                    for &byte in bytes.iter() {
+                        self.send_byte(byte).await?;
+                    }
+                    previous_read = Some(false);
+                }
+                Operation::Read(buffer) => {
+                    if previous_read != Some(true) {
+                        I::regs().cmd().write(|w| w.start().set_bit());
+                        self.send_byte((address << 1) | 0x01).await?;
+                    }
+                    let last_idx = buffer.len().wrapping_sub(1);
+                    for (i, byte) in buffer.iter_mut().enumerate() {
+                        *byte = self.receive_byte(i == last_idx && !next_is_read).await?;
+                    }
+                    previous_read = Some(true);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Release the bus after a transaction
+    async fn finish(&mut self, result: Result<(), Error>) -> Result<(), Error> {
+        match result {
+            Ok(()) | Err(Error::Nack) => {
+                // With AUTOSN the STOP may already have been sent after a NACK,
+                // in which case MSTOP is already set.
+                I::regs().cmd().write(|w| w.stop().set_bit());
+                let flags = self.wait_flags(IF_MSTOP).await;
+                if let Some(error) = flags_error(flags & !IF_MSTOP) {
+                    I::regs().cmd().write(|w| w.abort().set_bit());
+                    regs::clear_bits(I::regs().ien(), u32::MAX);
+                    return result.and(Err(error));
+                }
+            }
+            Err(_) => {
+                // Arbitration was lost, the bus misbehaved or timed out: the
+                // master no longer owns the bus, so abort instead of sending
+                // STOP.
+                I::regs().cmd().write(|w| w.abort().set_bit());
+            }
+        }
+
+        regs::clear_bits(I::regs().ien(), u32::MAX);
+        result
+    }
+}
+
+impl<I: Instance> embedded_hal_async::i2c::I2c for I2c<I> {
+    async fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        if operations.is_empty() {
+            return Ok(());
+        }
+        check_reads(operations)?;
+
+        regs::clear_bits(I::regs().if_(), u32::MAX);
+        let result = self.run_operations(address, operations).await;
+        self.finish(result).await
+    }
+}
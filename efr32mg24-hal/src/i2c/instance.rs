@@ -0,0 +1,93 @@
+//! I2C peripheral instances
+//!
+//! [`Instance`] is implemented for I2C0 and I2C1. It ties each of them to its
+//! register block, CMU clock enable bit, interrupt vector and GPIO route
+//! registers, so the driver is written once for both.
+
+use crate::interrupt::typelevel;
+use crate::pac;
+
+mod sealed {
+    use crate::pac;
+
+    pub trait Instance {
+        /// Register block
+        fn regs() -> &'static pac::i2c0_s::RegisterBlock;
+
+        /// Enable the peripheral clock in the CMU
+        fn enable_clock(cmu: &pac::CmuS);
+
+        /// SCL and SDA `(port, pin)`, as selected in SCLROUTE/SDAROUTE
+        fn pins(gpio: &pac::gpio_s::RegisterBlock) -> ((u8, u8), (u8, u8));
+
+        /// Connect SCL and SDA to the peripheral, or hand them back to DOUT
+        fn route_pins(gpio: &pac::gpio_s::RegisterBlock, enable: bool);
+    }
+}
+
+/// An I2C peripheral: I2C0 or I2C1
+pub trait Instance: sealed::Instance + 'static {
+    /// Interrupt vector
+    type Interrupt: typelevel::Interrupt;
+}
+
+/// GPIO I2Cn_ROUTEEN: SCL and SDA output enable
+const ROUTEEN_SCLPEN_SDAPEN: u32 = (1 << 0) | (1 << 1);
+
+macro_rules! impl_instance {
+    (
+        $Periph:ident, $clk:ident, $IRQ:ident,
+        $routeen:ident, $sclroute:ident, $sdaroute:ident
+    ) => {
+        impl sealed::Instance for pac::$Periph {
+            fn regs() -> &'static pac::i2c0_s::RegisterBlock {
+                // SAFETY: I2C1 has the same register layout as I2C0, and the
+                // register block is only accessed by the driver that owns the
+                // peripheral.
+                unsafe { &*(pac::$Periph::ptr() as *const pac::i2c0_s::RegisterBlock) }
+            }
+
+            fn enable_clock(cmu: &pac::CmuS) {
+                cmu.clken0().modify(|_, w| w.$clk().set_bit());
+            }
+
+            fn pins(gpio: &pac::gpio_s::RegisterBlock) -> ((u8, u8), (u8, u8)) {
+                let scl = gpio.$sclroute().read();
+                let sda = gpio.$sdaroute().read();
+                (
+                    (scl.port().bits(), scl.pin().bits()),
+                    (sda.port().bits(), sda.pin().bits()),
+                )
+            }
+
+            fn route_pins(gpio: &pac::gpio_s::RegisterBlock, enable: bool) {
+                if enable {
+                    crate::regs::set_bits(gpio.$routeen(), ROUTEEN_SCLPEN_SDAPEN);
+                } else {
+                    crate::regs::clear_bits(gpio.$routeen(), ROUTEEN_SCLPEN_SDAPEN);
+                }
+            }
+        }
+
+        impl Instance for pac::$Periph {
+            type Interrupt = typelevel::$IRQ;
+        }
+    };
+}
+
+impl_instance!(
+    I2c0S,
+    i2c0,
+    I2C0,
+    i2c0_routeen,
+    i2c0_sclroute,
+    i2c0_sdaroute
+);
+impl_instance!(
+    I2c1S,
+    i2c1,
+    I2C1,
+    i2c1_routeen,
+    i2c1_sclroute,
+    i2c1_sdaroute
+);
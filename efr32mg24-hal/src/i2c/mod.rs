@@ -1,12 +1,13 @@
-//! I2C Master Mode Driver
+//! I2C Master and Target Mode Drivers
 //!
 //! This module provides a hardware abstraction layer for the EFR32MG24 I2C peripherals
-//! in master mode with support for 7-bit addressing.
+//! in master mode and in target (slave) mode, with support for 7-bit addressing.
 //!
 //! # Hardware Registers
 //!
 //! - **EN**: Enable register
 //! - **CTRL**: Control register (master/slave mode, clock settings)
+//! - **SADDR/SADDRMASK**: Target address and address mask
 //! - **CMD**: Command register (START, STOP, ACK, NACK, etc.)
 //! - **STATUS**: Status register (TXBL, RXDATAV, TXC, etc.)
 //! - **CLKDIV**: Clock divider for SCL frequency
@@ -16,9 +17,23 @@
 //! # Features
 //!
 //! - I2C master mode with 7-bit addressing
-//! - Configurable SCL frequency (standard 100 kHz, fast 400 kHz)
+//! - Configurable SCL frequency (standard 100 kHz, fast 400 kHz, Fast-mode Plus
+//!   1 MHz or any custom frequency), with the SCL low/high ratio selected
+//!   automatically and the achieved frequency reported by [`I2c::scl_frequency`]
 //! - Blocking write and read operations
-//! - embedded-hal v1.0 I2C traits
+//! - embedded-hal v1.0 `I2c::transaction` with a repeated START between
+//!   operations and a STOP only at the end; [`I2c::write_read`] takes a
+//!   register address of any width
+//! - Timeouts on every blocking wait ([`Config::with_timeout`]), hardware clock
+//!   low and bus idle timeouts, arbitration loss and bus error detection
+//! - Bus recovery with nine SCL clocks and a STOP through GPIO
+//!   ([`I2c::recover_bus`])
+//! - embedded-hal-async `I2c` with repeated START (`async` feature)
+//! - Target mode ([`I2cTarget`]): address with mask, general call, read and
+//!   write requests with clock stretching while the application prepares
+//!   data
+//! - One generic [`I2c`] driver over the sealed [`Instance`] trait; `I2c0` and
+//!   `I2c1` are aliases
 //!
 //! # Example
 //!
@@ -27,13 +42,14 @@
 //!     clock::{Clocks, ClockConfig, HfxoConfig},
 //!     i2c::{I2c0, Config, Speed},
 //!     pac,
+//!     prelude::*,
 //! };
 //!
 //! let dp = pac::Peripherals::take().unwrap();
 //!
 //! // Configure clocks
 //! let clocks = Clocks::new(dp.cmu_s, ClockConfig {
-//!     hfxo: Some(HfxoConfig::new(39_000_000)),
+//!     hfxo: Some(HfxoConfig::new(39.MHz())),
 //!     lfxo: Some(Default::default()),
 //! }).freeze();
 //!
@@ -50,32 +66,163 @@
 //! // Read from device
 //! let mut buffer = [0u8; 1];
 //! i2c.read(0x6B, &mut buffer).unwrap();
+//!
+//! // Read register 0x75 without releasing the bus (repeated START)
+//! i2c.write_read(0x6B, &[0x75], &mut buffer).unwrap();
 //! ```
 
+#[cfg(feature = "async")]
+mod asynch;
+mod instance;
+mod target;
 mod traits;
 mod types;
 
-pub use types::{Config, Error, Speed};
+#[cfg(feature = "async")]
+pub use asynch::InterruptHandler;
+pub use instance::Instance;
+pub use target::{I2cTarget, I2cTarget0, I2cTarget1};
+pub use types::{ClockLowTimeout, Config, Error, Request, Speed, TargetConfig, TargetEvent};
 
 use crate::clock::FrozenClocks;
 use crate::pac;
+use crate::pac::i2c0_s::ctrl::Clhr;
+use crate::regs;
+use crate::time::{Hertz, MicroSeconds};
+use embedded_hal::i2c::Operation;
+
+/// I2C IF bit positions
+const IF_ACK: u32 = 1 << 6;
+const IF_NACK: u32 = 1 << 7;
+const IF_MSTOP: u32 = 1 << 8;
+const IF_ARBLOST: u32 = 1 << 9;
+const IF_BUSERR: u32 = 1 << 10;
+const IF_BITO: u32 = 1 << 14;
+const IF_CLTO: u32 = 1 << 15;
+
+/// Approximate core cycles per iteration of a polling loop
+const POLL_CYCLES: u64 = 10;
+
+/// SCL frequency used by [`I2c::recover_bus`]
+const RECOVERY_SCL_HZ: u32 = 100_000;
+
+/// Fastest SCL frequency (Fast-mode Plus)
+const MAX_SCL_HZ: u32 = 1_000_000;
+
+/// Peripheral clock cycles added to every SCL period by clock synchronization
+/// and the SCL rise time (emlib's I2C_CR_MAX)
+const SCL_OVERHEAD_CYCLES: u32 = 4;
+
+/// Largest CLKDIV value (9-bit field)
+const MAX_CLKDIV: u32 = 0x1FF;
+
+/// SCL clock setup for a bus speed
+struct SclTiming {
+    /// SCL low/high ratio (CTRL.CLHR)
+    clhr: Clhr,
+    /// Clock divider (CLKDIV)
+    clkdiv: u32,
+    /// Achieved SCL frequency
+    scl_hz: u32,
+}
 
-/// I2C0 peripheral instance
-pub struct I2c0 {
-    i2c: pac::I2c0S,
+impl SclTiming {
+    /// Clock setup for `speed` with a peripheral clock of `hclk_hz`
+    ///
+    /// fSCL = fHCLK / ((Nlow + Nhigh) * (CLKDIV + 1) + 4), the 4 cycles being
+    /// the synchronization and rise time overhead. CLKDIV is rounded up, so SCL
+    /// does not run faster than requested, and CLHR gives the low period the
+    /// share the I2C specification requires (tLOW of at least 4.7 us, 1.3 us and
+    /// 0.5 us in standard, fast and Fast-mode Plus).
+    ///
+    /// Below fHCLK / (8 * 512 + 4), about 9.5 kHz at 39 MHz, CLKDIV saturates
+    /// and SCL runs at that minimum instead, faster than requested.
+    fn new(hclk_hz: u32, speed: Speed) -> Self {
+        let target_hz = speed.frequency().to_Hz().clamp(1, MAX_SCL_HZ);
+
+        let (clhr, cycles) = if target_hz <= 100_000 {
+            (Clhr::Standard, 4 + 4)
+        } else if target_hz <= 400_000 {
+            (Clhr::Asymmetric, 6 + 3)
+        } else {
+            (Clhr::Fast, 11 + 6)
+        };
+
+        let clkdiv = hclk_hz
+            .saturating_sub(SCL_OVERHEAD_CYCLES * target_hz)
+            .div_ceil(cycles * target_hz)
+            .saturating_sub(1)
+            .min(MAX_CLKDIV);
+        let scl_hz = hclk_hz / (cycles * (clkdiv + 1) + SCL_OVERHEAD_CYCLES);
+
+        Self {
+            clhr,
+            clkdiv,
+            scl_hz,
+        }
+    }
 }
 
-/// I2C1 peripheral instance
-pub struct I2c1 {
-    i2c: pac::I2c1S,
+/// Polling loop iterations that take roughly `timeout` at `clock_hz`
+fn timeout_polls(clock_hz: u32, timeout: MicroSeconds) -> u32 {
+    (u64::from(clock_hz) * u64::from(timeout.to_micros()) / 1_000_000 / POLL_CYCLES)
+        .clamp(1, u64::from(u32::MAX)) as u32
 }
 
-impl I2c0 {
-    /// Creates a new I2C0 instance
+/// Reject transactions with an empty read
+///
+/// The last byte of a read run is NACKed, which needs a byte to receive: an
+/// empty read would either move the NACK away from the byte before it or leave
+/// the target driving SDA after the address, unanswered.
+fn check_reads(operations: &[Operation<'_>]) -> Result<(), Error> {
+    let empty_read = operations
+        .iter()
+        .any(|operation| matches!(operation, Operation::Read(buffer) if buffer.is_empty()));
+    if empty_read {
+        Err(Error::InvalidData)
+    } else {
+        Ok(())
+    }
+}
+
+/// Error reported by the abort flags in `flags`, if any
+fn flags_error(flags: u32) -> Option<Error> {
+    if flags & IF_ARBLOST != 0 {
+        Some(Error::ArbitrationLoss)
+    } else if flags & (IF_CLTO | IF_BITO) != 0 {
+        Some(Error::Timeout)
+    } else if flags & IF_BUSERR != 0 {
+        Some(Error::Bus)
+    } else {
+        None
+    }
+}
+
+/// I2C master driver on I2C0 or I2C1
+///
+/// Use the [`I2c0`] and [`I2c1`] aliases to name a concrete driver.
+pub struct I2c<I: Instance> {
+    _i2c: I,
+    /// Peripheral clock frequency
+    clock_hz: u32,
+    /// Achieved SCL frequency
+    scl_hz: u32,
+    /// Polling loop iterations before a blocking wait gives up
+    timeout_polls: u32,
+}
+
+/// I2C0 peripheral instance
+pub type I2c0 = I2c<pac::I2c0S>;
+
+/// I2C1 peripheral instance
+pub type I2c1 = I2c<pac::I2c1S>;
+
+impl<I: Instance> I2c<I> {
+    /// Creates a new I2C master instance
     ///
     /// # Arguments
     ///
-    /// * `i2c` - I2C0_S peripheral instance
+    /// * `i2c` - I2C0_S or I2C1_S peripheral instance
     /// * `config` - I2C configuration
     /// * `clocks` - Frozen clock configuration
     ///
@@ -84,44 +231,135 @@ impl I2c0 {
     /// ```no_run
     /// let i2c = I2c0::new(dp.i2c0_s, Config::new(Speed::Standard100kHz), &clocks);
     /// ```
-    pub fn new(i2c: pac::I2c0S, config: Config, clocks: &FrozenClocks) -> Self {
-        // Enable I2C0 clock in CMU using safe accessor
-        clocks.enable_peripheral_clock(|cmu| {
-            cmu.clken0().modify(|_, w| w.i2c0().set_bit());
-        });
+    pub fn new(i2c: I, config: Config, clocks: &FrozenClocks) -> Self {
+        // Enable I2C clock in CMU using safe accessor
+        clocks.enable_peripheral_clock(|cmu| I::enable_clock(cmu));
 
         // Enable I2C peripheral
-        i2c.en().write(|w| w.en().set_bit());
+        let regs = I::regs();
+        regs.en().write(|w| w.en().set_bit());
+
+        // SCL low/high ratio and clock divider for the configured speed
+        let clock_hz = clocks.hfclk().to_Hz();
+        let timing = SclTiming::new(clock_hz, config.speed);
 
         // Configure as master mode
-        i2c.ctrl().write(|w| {
+        regs.ctrl().write(|w| {
             w.slave()
                 .clear_bit() // Master mode
                 .autoack()
                 .clear_bit() // Manual ACK/NACK
                 .autosn()
                 .set_bit() // Automatic STOP/START
+                .clhr()
+                .variant(timing.clhr);
+
+            // A bus left busy without a STOP (e.g. by a reset mid-transfer)
+            // is considered idle again after the bus idle timeout
+            w.bito().i2c160pcc().gibito().set_bit();
+
+            match config.clock_low_timeout {
+                ClockLowTimeout::Disabled => w.clto().off(),
+                ClockLowTimeout::Cycles40 => w.clto().i2c40pcc(),
+                ClockLowTimeout::Cycles80 => w.clto().i2c80pcc(),
+                ClockLowTimeout::Cycles160 => w.clto().i2c160pcc(),
+                ClockLowTimeout::Cycles320 => w.clto().i2c320pcc(),
+                ClockLowTimeout::Cycles1024 => w.clto().i2c1024pcc(),
+            }
         });
 
-        // Calculate and set clock divider
-        let clkdiv = Self::calculate_clkdiv(clocks.hfclk().into(), config.speed);
-        i2c.clkdiv().write(|w| unsafe { w.bits(clkdiv) });
+        regs.clkdiv().write(|w| unsafe { w.bits(timing.clkdiv) });
 
-        Self { i2c }
+        // The peripheral considers the bus busy after being enabled, until it
+        // sees a STOP; the bus is known to be idle at this point
+        regs.cmd().write(|w| w.abort().set_bit());
+
+        Self {
+            _i2c: i2c,
+            clock_hz,
+            scl_hz: timing.scl_hz,
+            timeout_polls: timeout_polls(clock_hz, config.timeout),
+        }
     }
 
-    /// Calculate clock divider value for desired I2C speed
+    /// Free the bus after a slave was reset or interrupted mid-transfer
     ///
-    /// CLKDIV = (fHCLK / (8 * fSCL)) - 1
-    fn calculate_clkdiv(hclk_hz: u32, speed: Speed) -> u32 {
-        let scl_hz = match speed {
-            Speed::Standard100kHz => 100_000,
-            Speed::Fast400kHz => 400_000,
-        };
+    /// A slave that was sending a 0 bit when the master stopped keeps holding
+    /// SDA low, and every following transfer fails. This disconnects SCL and
+    /// SDA from the peripheral, clocks SCL through GPIO until the slave
+    /// releases SDA (at most nine times), generates a STOP and reconnects the
+    /// pins. The pins keep their GPIO mode, which must be open-drain
+    /// (wired-AND) as for normal I2C operation.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Bus`] if SDA is still held low afterwards.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// if let Err(Error::Timeout | Error::Bus) = i2c.write_read(0x6A, &[0x0F], &mut id) {
+    ///     i2c.recover_bus()?;
+    /// }
+    /// ```
+    pub fn recover_bus(&mut self) -> Result<(), Error> {
+        // SAFETY: Only the DOUT bits of this peripheral's SCL and SDA pins and
+        // its own route enable bits are written, through the SET/CLR aliases.
+        let gpio = unsafe { &*pac::GpioS::ptr() };
+        let (scl, sda) = I::pins(gpio);
+        let half_period = self.clock_hz / (2 * RECOVERY_SCL_HZ);
+
+        // Release both lines before handing them to DOUT
+        gpio_write(gpio, sda, true);
+        gpio_write(gpio, scl, true);
+        I::regs().en().write(|w| w.en().clear_bit());
+        I::route_pins(gpio, false);
+        cortex_m::asm::delay(half_period);
+
+        for _ in 0..9 {
+            if gpio_read(gpio, sda) {
+                break;
+            }
+            gpio_write(gpio, scl, false);
+            cortex_m::asm::delay(half_period);
+            gpio_write(gpio, scl, true);
+            cortex_m::asm::delay(half_period);
+        }
 
-        // CLKDIV = (fHCLK / (8 * fSCL)) - 1
-        let clkdiv = (hclk_hz / (8 * scl_hz)).saturating_sub(1);
-        clkdiv.min(0x1FF) // 9-bit field
+        // STOP: SDA rises while SCL is high
+        gpio_write(gpio, scl, false);
+        cortex_m::asm::delay(half_period);
+        gpio_write(gpio, sda, false);
+        cortex_m::asm::delay(half_period);
+        gpio_write(gpio, scl, true);
+        cortex_m::asm::delay(half_period);
+        gpio_write(gpio, sda, true);
+        cortex_m::asm::delay(half_period);
+        let released = gpio_read(gpio, sda);
+
+        I::route_pins(gpio, true);
+        I::regs().en().write(|w| w.en().set_bit());
+        I::regs()
+            .cmd()
+            .write(|w| w.abort().set_bit().cleartx().set_bit().clearpc().set_bit());
+        regs::clear_bits(I::regs().if_(), u32::MAX);
+
+        if released {
+            Ok(())
+        } else {
+            Err(Error::Bus)
+        }
+    }
+
+    /// SCL frequency achieved for the configured [`Speed`]
+    ///
+    /// At most the requested frequency; lower when the peripheral clock is not
+    /// a multiple of it. Requests below the minimum, fHCLK / 4100 (about
+    /// 9.5 kHz at 39 MHz), run at the minimum. The frequency includes the
+    /// nominal synchronization and rise time overhead; clock stretching by a
+    /// slave and slow SCL edges on a heavily loaded bus slow it down further.
+    pub fn scl_frequency(&self) -> Hertz {
+        Hertz::from_raw(self.scl_hz)
     }
 
     /// Write data to I2C device
@@ -139,32 +377,13 @@ impl I2c0 {
             return Err(Error::InvalidData);
         }
 
-        // Send START condition
-        self.i2c.cmd().write(|w| w.start().set_bit());
-
-        // Wait for START to complete
-        while self.i2c.status().read().pstart().bit_is_set() {}
-
-        // Send address with write bit (R/W = 0)
-        let addr_byte = (addr << 1) & 0xFE;
-        self.write_byte(addr_byte)?;
-
-        // Write data bytes
-        for &byte in bytes {
-            self.write_byte(byte)?;
-        }
-
-        // Send STOP condition
-        self.i2c.cmd().write(|w| w.stop().set_bit());
-
-        // Wait for STOP to complete
-        while self.i2c.status().read().pstop().bit_is_set() {}
-
-        Ok(())
+        self.transfer(addr, &mut [Operation::Write(bytes)])
     }
 
     /// Read data from I2C device
     ///
+    /// The last byte is NACKed before the STOP.
+    ///
     /// # Arguments
     ///
     /// * `addr` - 7-bit device address
@@ -178,226 +397,193 @@ impl I2c0 {
             return Err(Error::InvalidData);
         }
 
-        // Send START condition
-        self.i2c.cmd().write(|w| w.start().set_bit());
-
-        // Wait for START to complete
-        while self.i2c.status().read().pstart().bit_is_set() {}
-
-        // Send address with read bit (R/W = 1)
-        let addr_byte = (addr << 1) | 0x01;
-        self.write_byte(addr_byte)?;
-
-        // Read data bytes
-        let last_idx = buffer.len() - 1;
-        for (i, byte) in buffer.iter_mut().enumerate() {
-            // Wait for RX data valid
-            while self.i2c.status().read().rxdatav().bit_is_clear() {}
-
-            // Read byte
-            *byte = self.i2c.rxdata().read().bits() as u8;
-
-            // Send ACK for all bytes except last, NACK for last byte
-            if i == last_idx {
-                self.i2c.cmd().write(|w| w.nack().set_bit());
-            } else {
-                self.i2c.cmd().write(|w| w.ack().set_bit());
-            }
-        }
-
-        // Send STOP condition
-        self.i2c.cmd().write(|w| w.stop().set_bit());
-
-        // Wait for STOP to complete
-        while self.i2c.status().read().pstop().bit_is_set() {}
-
-        Ok(())
+        self.transfer(addr, &mut [Operation::Read(buffer)])
     }
 
-    /// Write register then read from I2C device
+    /// Write bytes, then read from I2C device after a repeated START
+    ///
+    /// `bytes` is typically a register address of any width (one byte for most
+    /// sensors, two for EEPROMs). No STOP is sent between the write and the
+    /// read.
     ///
     /// # Arguments
     ///
     /// * `addr` - 7-bit device address
-    /// * `register` - Register address to read from
+    /// * `bytes` - Register address (or other data) to write first
     /// * `buffer` - Buffer to store read data
     ///
     /// # Returns
     ///
     /// `Ok(())` on success, `Err(Error)` on failure
-    pub fn write_read(&mut self, addr: u8, register: u8, buffer: &mut [u8]) -> Result<(), Error> {
-        if buffer.is_empty() {
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// // Read WHO_AM_I (0x0F) from the LSM6DS3
+    /// let mut id = [0u8; 1];
+    /// i2c.write_read(0x6A, &[0x0F], &mut id)?;
+    /// ```
+    pub fn write_read(&mut self, addr: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), Error> {
+        if bytes.is_empty() || buffer.is_empty() {
             return Err(Error::InvalidData);
         }
 
-        // Write register address
-        self.write(addr, &[register])?;
-
-        // Read data
-        self.read(addr, buffer)?;
-
-        Ok(())
+        self.transfer(
+            addr,
+            &mut [Operation::Write(bytes), Operation::Read(buffer)],
+        )
     }
 
-    /// Write a single byte to TX buffer and wait for ACK/NACK
-    fn write_byte(&mut self, byte: u8) -> Result<(), Error> {
-        // Wait for TX buffer ready
-        while self.i2c.status().read().txbl().bit_is_clear() {}
-
-        // Write byte to TX buffer
-        self.i2c.txdata().write(|w| unsafe { w.bits(byte as u32) });
+    /// Run `operations` as one transaction
+    ///
+    /// START, a repeated START whenever the direction changes, STOP at the end.
+    /// Returns [`Error::InvalidData`] for an empty read, before the bus is
+    /// touched.
+    fn transfer(&mut self, address: u8, operations: &mut [Operation<'_>]) -> Result<(), Error> {
+        if operations.is_empty() {
+            return Ok(());
+        }
+        check_reads(operations)?;
 
-        // Wait for transmission complete
-        while self.i2c.status().read().txc().bit_is_clear() {}
+        regs::clear_bits(I::regs().if_(), u32::MAX);
+        let result = self.run_transfer(address, operations);
+        self.release_bus(result)
+    }
 
-        // Check for NACK (address or data not acknowledged)
-        if self.i2c.if_().read().nack().bit_is_set() {
-            // Clear NACK flag
-            self.i2c.if_().write(|w| w.nack().set_bit());
-            return Err(Error::Nack);
+    /// Run all operations between START and STOP
+    fn run_transfer(&mut self, address: u8, operations: &mut [Operation<'_>]) -> Result<(), Error> {
+        let mut previous_read = None;
+
+        for index in 0..operations.len() {
+            // A read run ends with a NACK when the next operation is not a
+            // read; reads are never empty (check_reads), so a following read
+            // receives at least one more byte
+            let next_is_read = matches!(operations.get(index + 1), Some(Operation::Read(_)));
+
+            match &mut operations[index] {
+                Operation::Write(bytes) => {
+                    // Adjacent writes share one address phase
+                    if previous_read != Some(false) {
+                        I::regs().cmd().write(|w| w.start().set_bit());
+                        self.write_byte(address << 1)?;
+                    }
+                    for &byte in bytes.iter() {
+                        self.write_byte(byte)?;
+                    }
+                    previous_read = Some(false);
+                }
+                Operation::Read(buffer) => {
+                    if previous_read != Some(true) {
+                        I::regs().cmd().write(|w| w.start().set_bit());
+                        self.write_byte((address << 1) | 0x01)?;
+                    }
+                    let last_idx = buffer.len().wrapping_sub(1);
+                    for (i, byte) in buffer.iter_mut().enumerate() {
+                        *byte = self.read_byte(i == last_idx && !next_is_read)?;
+                    }
+                    previous_read = Some(true);
+                }
+            }
         }
 
         Ok(())
     }
-}
 
-impl I2c1 {
-    /// Creates a new I2C1 instance
-    pub fn new(i2c: pac::I2c1S, config: Config, clocks: &FrozenClocks) -> Self {
-        // Enable I2C1 clock in CMU using safe accessor
-        clocks.enable_peripheral_clock(|cmu| {
-            cmu.clken0().modify(|_, w| w.i2c1().set_bit());
-        });
-
-        // Enable I2C peripheral
-        i2c.en().write(|w| w.en().set_bit());
-
-        // Configure as master mode
-        i2c.ctrl().write(|w| {
-            w.slave()
-                .clear_bit()
-                .autoack()
-                .clear_bit()
-                .autosn()
-                .set_bit()
-        });
-
-        // Calculate and set clock divider
-        let clkdiv = Self::calculate_clkdiv(clocks.hfclk().into(), config.speed);
-        i2c.clkdiv().write(|w| unsafe { w.bits(clkdiv) });
-
-        Self { i2c }
-    }
+    /// Release the bus after a transaction
+    fn release_bus(&mut self, result: Result<(), Error>) -> Result<(), Error> {
+        match result {
+            Ok(()) | Err(Error::Nack) => {
+                // With AUTOSN the STOP may already have been sent after a NACK,
+                // in which case MSTOP is already set.
+                I::regs().cmd().write(|w| w.stop().set_bit());
+                if let Err(error) =
+                    self.wait_until(|| I::regs().if_().read().bits() & IF_MSTOP != 0)
+                {
+                    I::regs().cmd().write(|w| w.abort().set_bit());
+                    return result.and(Err(error));
+                }
+            }
+            Err(_) => {
+                // The master no longer owns the bus (or cannot drive it), so
+                // abort instead of sending STOP
+                I::regs().cmd().write(|w| w.abort().set_bit());
+            }
+        }
 
-    fn calculate_clkdiv(hclk_hz: u32, speed: Speed) -> u32 {
-        let scl_hz = match speed {
-            Speed::Standard100kHz => 100_000,
-            Speed::Fast400kHz => 400_000,
-        };
-        let clkdiv = (hclk_hz / (8 * scl_hz)).saturating_sub(1);
-        clkdiv.min(0x1FF)
+        result
     }
 
-    /// Write data to I2C device
-    ///
-    /// # Arguments
-    ///
-    /// * `addr` - 7-bit device address
-    /// * `bytes` - Data to write
-    ///
-    /// # Returns
+    /// Busy-wait until `ready` returns true
     ///
-    /// `Ok(())` on success, `Err(Error)` on failure
-    pub fn write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), Error> {
-        if bytes.is_empty() {
-            return Err(Error::InvalidData);
-        }
-
-        self.i2c.cmd().write(|w| w.start().set_bit());
-        while self.i2c.status().read().pstart().bit_is_set() {}
-
-        let addr_byte = (addr << 1) & 0xFE;
-        self.write_byte(addr_byte)?;
-
-        for &byte in bytes {
-            self.write_byte(byte)?;
+    /// Gives up on an abort flag (arbitration lost, bus error, clock low or bus
+    /// idle timeout) or after the configured timeout.
+    fn wait_until(&self, ready: impl Fn() -> bool) -> Result<(), Error> {
+        for _ in 0..self.timeout_polls {
+            if ready() {
+                return Ok(());
+            }
+            if let Some(error) = flags_error(I::regs().if_().read().bits()) {
+                return Err(error);
+            }
         }
-
-        self.i2c.cmd().write(|w| w.stop().set_bit());
-        while self.i2c.status().read().pstop().bit_is_set() {}
-
-        Ok(())
+        Err(Error::Timeout)
     }
 
-    /// Read data from I2C device
-    ///
-    /// # Arguments
-    ///
-    /// * `addr` - 7-bit device address
-    /// * `buffer` - Buffer to store read data
-    ///
-    /// # Returns
-    ///
-    /// `Ok(())` on success, `Err(Error)` on failure
-    pub fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Error> {
-        if buffer.is_empty() {
-            return Err(Error::InvalidData);
+    /// Transmit one byte (address or data) and wait for the ACK/NACK
+    fn write_byte(&mut self, byte: u8) -> Result<(), Error> {
+        regs::clear_bits(I::regs().if_(), IF_ACK | IF_NACK);
+        I::regs().txdata().write(|w| unsafe { w.bits(byte as u32) });
+
+        self.wait_until(|| I::regs().if_().read().bits() & (IF_ACK | IF_NACK) != 0)?;
+        if I::regs().if_().read().nack().bit_is_set() {
+            Err(Error::Nack)
+        } else {
+            Ok(())
         }
+    }
 
-        self.i2c.cmd().write(|w| w.start().set_bit());
-        while self.i2c.status().read().pstart().bit_is_set() {}
-
-        let addr_byte = (addr << 1) | 0x01;
-        self.write_byte(addr_byte)?;
+    /// Receive one byte, then ACK it or (for the last byte) NACK it
+    fn read_byte(&mut self, last: bool) -> Result<u8, Error> {
+        self.wait_until(|| I::regs().status().read().rxdatav().bit_is_set())?;
 
-        let last_idx = buffer.len() - 1;
-        for (i, byte) in buffer.iter_mut().enumerate() {
-            while self.i2c.status().read().rxdatav().bit_is_clear() {}
-            *byte = self.i2c.rxdata().read().bits() as u8;
+        let byte = I::regs().rxdata().read().bits() as u8;
+        if last {
+            I::regs().cmd().write(|w| w.nack().set_bit());
+        } else {
+            I::regs().cmd().write(|w| w.ack().set_bit());
+        }
+        Ok(byte)
+    }
+}
 
-            if i == last_idx {
-                self.i2c.cmd().write(|w| w.nack().set_bit());
+/// Drive `(port, pin)` low, or release it, through DOUT
+fn gpio_write(gpio: &pac::gpio_s::RegisterBlock, (port, pin): (u8, u8), high: bool) {
+    let mask = 1 << pin;
+    macro_rules! write_dout {
+        ($dout:expr) => {
+            if high {
+                regs::set_bits($dout, mask)
             } else {
-                self.i2c.cmd().write(|w| w.ack().set_bit());
+                regs::clear_bits($dout, mask)
             }
-        }
-
-        self.i2c.cmd().write(|w| w.stop().set_bit());
-        while self.i2c.status().read().pstop().bit_is_set() {}
-
-        Ok(())
+        };
     }
 
-    /// Write register then read from I2C device
-    ///
-    /// # Arguments
-    ///
-    /// * `addr` - 7-bit device address
-    /// * `register` - Register address to read from
-    /// * `buffer` - Buffer to store read data
-    ///
-    /// # Returns
-    ///
-    /// `Ok(())` on success, `Err(Error)` on failure
-    pub fn write_read(&mut self, addr: u8, register: u8, buffer: &mut [u8]) -> Result<(), Error> {
-        if buffer.is_empty() {
-            return Err(Error::InvalidData);
-        }
-        self.write(addr, &[register])?;
-        self.read(addr, buffer)?;
-        Ok(())
+    match port {
+        0 => write_dout!(gpio.porta_dout()),
+        1 => write_dout!(gpio.portb_dout()),
+        2 => write_dout!(gpio.portc_dout()),
+        _ => write_dout!(gpio.portd_dout()),
     }
+}
 
-    fn write_byte(&mut self, byte: u8) -> Result<(), Error> {
-        while self.i2c.status().read().txbl().bit_is_clear() {}
-        self.i2c.txdata().write(|w| unsafe { w.bits(byte as u32) });
-        while self.i2c.status().read().txc().bit_is_clear() {}
-
-        if self.i2c.if_().read().nack().bit_is_set() {
-            self.i2c.if_().write(|w| w.nack().set_bit());
-            return Err(Error::Nack);
-        }
-
-        Ok(())
-    }
+/// Level of `(port, pin)` from DIN
+fn gpio_read(gpio: &pac::gpio_s::RegisterBlock, (port, pin): (u8, u8)) -> bool {
+    let din = match port {
+        0 => gpio.porta_din().read().bits(),
+        1 => gpio.portb_din().read().bits(),
+        2 => gpio.portc_din().read().bits(),
+        _ => gpio.portd_din().read().bits(),
+    };
+    din & (1 << pin) != 0
 }
@@ -0,0 +1,380 @@
+//! I2C target (slave) mode driver
+//!
+//! [`I2cTarget`], with the aliases [`I2cTarget0`] and [`I2cTarget1`], answers
+//! an external controller (e.g. a Linux host) at a 7-bit address. SADDR holds
+//! the address and SADDRMASK selects which of its bits must match, so one
+//! target can answer a range of addresses. The general call address (0) is
+//! matched when enabled in [`TargetConfig`].
+//!
+//! # Requests
+//!
+//! When the controller addresses the target, [`request`](I2cTarget::request)
+//! (or [`wait_request`](I2cTarget::wait_request) with the `async` feature)
+//! returns a [`Request`]. The peripheral stretches the clock from then on, so
+//! the application can take its time preparing data before it answers:
+//!
+//! - [`Request::Write`]: the controller sends data; receive it with
+//!   [`read`](I2cTarget::read).
+//! - [`Request::Read`]: the controller reads data; send it with
+//!   [`write`](I2cTarget::write).
+//! - Refuse either with [`nack`](I2cTarget::nack).
+//!
+//! `read` and `write` return at the controller's STOP or repeated START. A
+//! register read (write the register address, repeated START, read) is
+//! therefore a `Write` request followed by a `Read` request.
+//!
+//! # Errors
+//!
+//! A data phase ends with [`Error::Bus`] on a misplaced START or STOP,
+//! [`Error::ArbitrationLoss`] when another device drove SDA while the target
+//! was sending, and [`Error::Timeout`] on a bus idle timeout (the controller
+//! stopped clocking with SCL high) or when the controller does not send or
+//! acknowledge the next byte within [`TargetConfig::timeout`]. The target has no
+//! clock low timeout: it holds SCL low itself while the application prepares
+//! its answer.
+
+use super::{
+    flags_error, timeout_polls, Error, Instance, Request, SclTiming, TargetConfig, TargetEvent,
+};
+use crate::clock::FrozenClocks;
+use crate::pac;
+use crate::regs;
+
+#[cfg(feature = "async")]
+use super::InterruptHandler;
+#[cfg(feature = "async")]
+use crate::asynch::{self, wait_for};
+#[cfg(feature = "async")]
+use crate::interrupt::{
+    self,
+    typelevel::{Binding, Handler, Interrupt},
+};
+
+/// I2C IF/IEN bit positions
+const IF_RSTART: u32 = 1 << 1;
+const IF_ADDR: u32 = 1 << 2;
+const IF_ACK: u32 = 1 << 6;
+const IF_NACK: u32 = 1 << 7;
+const IF_SSTOP: u32 = 1 << 16;
+
+/// Flags that end a data phase
+const IF_END: u32 = IF_SSTOP | IF_RSTART;
+
+/// Byte sent when the controller reads past the end of the data
+const FILL_BYTE: u8 = 0xFF;
+
+/// I2C target driver on I2C0 or I2C1
+///
+/// Use the [`I2cTarget0`] and [`I2cTarget1`] aliases to name a concrete
+/// driver.
+pub struct I2cTarget<I: Instance> {
+    i2c: I,
+    /// Polling loop iterations before a data phase wait gives up
+    timeout_polls: u32,
+}
+
+/// I2C target using I2C0
+pub type I2cTarget0 = I2cTarget<pac::I2c0S>;
+
+/// I2C target using I2C1
+pub type I2cTarget1 = I2cTarget<pac::I2c1S>;
+
+impl<I: Instance> I2cTarget<I> {
+    /// Creates a new I2C target answering at `config.address`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the address or mask does not fit in 7 bits.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let target = I2cTarget0::new(dp.i2c0_s, TargetConfig::new(0x42), &clocks);
+    /// ```
+    pub fn new(i2c: I, config: TargetConfig, clocks: &FrozenClocks) -> Self {
+        assert!(
+            config.address <= 0x7F && config.address_mask <= 0x7F,
+            "I2C target addresses are 7 bits"
+        );
+
+        clocks.enable_peripheral_clock(|cmu| I::enable_clock(cmu));
+
+        let regs = I::regs();
+        regs.en().write(|w| w.en().set_bit());
+        regs.ctrl().write(|w| {
+            w.slave()
+                .set_bit() // Addressable as target
+                .autoack()
+                .clear_bit() // Stretch the clock until the driver ACKs or NACKs
+                .gcamen()
+                .bit(config.general_call);
+
+            // A controller that stops clocking with SCL high ends the transfer
+            w.bito().i2c160pcc().gibito().set_bit()
+        });
+        // Target timing (data setup, bus idle timeout) runs on the prescaled
+        // clock, which is set up for the controller's bus speed
+        let clock_hz = clocks.hfclk().to_Hz();
+        let timing = SclTiming::new(clock_hz, config.speed);
+        regs.clkdiv().write(|w| unsafe { w.bits(timing.clkdiv) });
+
+        regs.saddr()
+            .write(|w| unsafe { w.addr().bits(config.address) });
+        regs.saddrmask()
+            .write(|w| unsafe { w.saddrmask().bits(config.address_mask) });
+
+        // The peripheral considers the bus busy after being enabled, until it
+        // sees a STOP; start from the idle state instead
+        regs.cmd().write(|w| w.abort().set_bit());
+        regs::clear_bits(regs.if_(), u32::MAX);
+
+        Self {
+            i2c,
+            timeout_polls: timeout_polls(clock_hz, config.timeout),
+        }
+    }
+
+    /// Take a pending request from the controller (non-blocking)
+    ///
+    /// Returns `WouldBlock` until the controller addresses the target. The
+    /// clock is stretched from then on until the request is answered with
+    /// [`read`](Self::read), [`write`](Self::write) or [`nack`](Self::nack).
+    ///
+    /// # Errors
+    ///
+    /// Returns (and clears) a bus error, arbitration loss or timeout latched
+    /// since the last request.
+    pub fn request(&mut self) -> nb::Result<Request, Error> {
+        let i2c = I::regs();
+        let flags = i2c.if_().read().bits();
+
+        if let Some(error) = flags_error(flags) {
+            regs::clear_bits(i2c.if_(), flags & !IF_ADDR);
+            return Err(nb::Error::Other(error));
+        }
+        if flags & IF_ADDR == 0 {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        // The end flags of the previous transfer are cleared here, so a data
+        // phase only sees its own STOP or repeated START
+        regs::clear_bits(i2c.if_(), IF_ADDR | IF_END | IF_ACK | IF_NACK);
+
+        // The matched address byte, with the R/W bit, is in the RX buffer
+        let byte = i2c.rxdata().read().bits() as u8;
+        let address = byte >> 1;
+        Ok(if byte & 0x01 == 0 {
+            Request::Write { address }
+        } else {
+            Request::Read { address }
+        })
+    }
+
+    /// Refuse the pending request
+    ///
+    /// NACKs the address; the controller then ends the transfer.
+    pub fn nack(&mut self) {
+        I::regs().cmd().write(|w| w.nack().set_bit());
+    }
+
+    /// Receive the data of a [`Request::Write`]
+    ///
+    /// ACKs the address and every byte that fits in `buffer`, then NACKs, so a
+    /// controller sending more than `buffer.len()` bytes is stopped. Returns
+    /// the number of bytes received once the controller sends a STOP or a
+    /// repeated START.
+    pub fn read(&mut self, buffer: &mut [u8]) -> Result<usize, Error> {
+        let i2c = I::regs();
+        i2c.cmd().write(|w| w.ack().set_bit());
+
+        let mut received = 0;
+        loop {
+            self.wait_until(|| {
+                i2c.status().read().rxdatav().bit_is_set() || i2c.if_().read().bits() & IF_END != 0
+            })?;
+            if i2c.status().read().rxdatav().bit_is_clear() {
+                return Ok(received);
+            }
+
+            // The clock is stretched until the byte is ACKed or NACKed; only
+            // a byte that was stored is ACKed
+            let byte = i2c.rxdata().read().bits() as u8;
+            if let Some(slot) = buffer.get_mut(received) {
+                *slot = byte;
+                received += 1;
+                i2c.cmd().write(|w| w.ack().set_bit());
+            } else {
+                i2c.cmd().write(|w| w.nack().set_bit());
+            }
+        }
+    }
+
+    /// Send the data of a [`Request::Read`]
+    ///
+    /// ACKs the address, then sends `bytes` until the controller NACKs. If the
+    /// controller reads past the end of `bytes`, 0xFF is sent. Returns the
+    /// number of bytes of `bytes` that were sent once the controller sends a
+    /// STOP or a repeated START.
+    pub fn write(&mut self, bytes: &[u8]) -> Result<usize, Error> {
+        let i2c = I::regs();
+        i2c.cmd().write(|w| w.ack().set_bit());
+
+        let mut sent = 0;
+        loop {
+            regs::clear_bits(i2c.if_(), IF_ACK | IF_NACK);
+            let byte = bytes.get(sent).copied().unwrap_or(FILL_BYTE);
+            i2c.txdata().write(|w| unsafe { w.bits(byte as u32) });
+
+            let flags = self.wait_flags(IF_ACK | IF_NACK | IF_END)?;
+            if sent < bytes.len() && flags & (IF_ACK | IF_NACK) != 0 {
+                sent += 1;
+            }
+            if flags & IF_END != 0 {
+                break;
+            }
+            if flags & IF_NACK != 0 {
+                // The controller NACKs the last byte it wants, then ends the
+                // transfer
+                self.wait_flags(IF_END)?;
+                break;
+            }
+        }
+
+        // A byte loaded for a read the controller ended without NACKing is
+        // still in the TX buffer; drop it so the next Read request does not
+        // start with it
+        i2c.cmd().write(|w| w.cleartx().set_bit());
+        Ok(sent)
+    }
+
+    /// Busy-wait until one of the IF flags in `mask` is set
+    ///
+    /// Returns the IF flags in `mask` that were set.
+    fn wait_flags(&mut self, mask: u32) -> Result<u32, Error> {
+        let i2c = I::regs();
+        self.wait_until(|| i2c.if_().read().bits() & mask != 0)?;
+        Ok(i2c.if_().read().bits() & mask)
+    }
+
+    /// Busy-wait until `ready` returns true
+    ///
+    /// Gives up on an abort flag (arbitration lost, bus error, bus idle
+    /// timeout) or after the configured timeout, releasing the bus.
+    fn wait_until(&mut self, ready: impl Fn() -> bool) -> Result<(), Error> {
+        for _ in 0..self.timeout_polls {
+            if ready() {
+                return Ok(());
+            }
+            if let Some(error) = flags_error(I::regs().if_().read().bits()) {
+                return Err(self.abort(error));
+            }
+        }
+        Err(self.abort(Error::Timeout))
+    }
+
+    /// Release the bus after `error` and return to the idle state
+    fn abort(&mut self, error: Error) -> Error {
+        let i2c = I::regs();
+        i2c.cmd().write(|w| w.abort().set_bit().cleartx().set_bit());
+        regs::clear_bits(i2c.if_(), u32::MAX);
+        error
+    }
+
+    /// Enable the interrupt for `event`
+    ///
+    /// The peripheral's I2C0 or I2C1 interrupt is raised; flags stay set until
+    /// cleared with [`clear_interrupt`](Self::clear_interrupt) or taken by
+    /// [`request`](Self::request).
+    pub fn listen(&mut self, event: TargetEvent) {
+        regs::set_bits(I::regs().ien(), Self::event_bit(event));
+    }
+
+    /// Disable the interrupt for `event`
+    pub fn unlisten(&mut self, event: TargetEvent) {
+        regs::clear_bits(I::regs().ien(), Self::event_bit(event));
+    }
+
+    /// Clear the interrupt flag of `event`
+    pub fn clear_interrupt(&mut self, event: TargetEvent) {
+        regs::clear_bits(I::regs().if_(), Self::event_bit(event));
+    }
+
+    /// IF/IEN bit of `event`
+    fn event_bit(event: TargetEvent) -> u32 {
+        match event {
+            TargetEvent::Request => IF_ADDR,
+            TargetEvent::Stop => IF_SSTOP,
+        }
+    }
+
+    /// Release the peripheral
+    ///
+    /// Disables the peripheral, so the target no longer answers.
+    pub fn free(self) -> I {
+        let i2c = I::regs();
+        i2c.ien().reset();
+        i2c.en().write(|w| w.en().clear_bit());
+        self.i2c
+    }
+}
+
+#[cfg(feature = "async")]
+impl<I: Instance> I2cTarget<I> {
+    /// Create a new I2C target whose requests can be awaited
+    ///
+    /// Configures the peripheral like [`new`](Self::new), then enables its
+    /// interrupt in the NVIC. `_irq` proves that the vector is bound to
+    /// [`InterruptHandler`].
+    pub fn new_async(
+        i2c: I,
+        config: TargetConfig,
+        clocks: &FrozenClocks,
+        _irq: impl Binding<I::Interrupt, InterruptHandler>,
+    ) -> Self
+    where
+        InterruptHandler: Handler<I::Interrupt>,
+    {
+        let target = Self::new(i2c, config, clocks);
+        interrupt::enable::<I::Interrupt>();
+        target
+    }
+
+    /// Wait for a request from the controller
+    ///
+    /// Sleeps until the controller addresses the target, then returns like
+    /// [`request`](Self::request). The target must be created with
+    /// `new_async`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// loop {
+    ///     match target.wait_request().await? {
+    ///         Request::Write { .. } => {
+    ///             let len = target.read(&mut command)?;
+    ///             handle(&command[..len]);
+    ///         }
+    ///         Request::Read { .. } => {
+    ///             target.write(&status)?;
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub async fn wait_request(&mut self) -> Result<Request, Error> {
+        loop {
+            match self.request() {
+                Ok(request) => return Ok(request),
+                Err(nb::Error::Other(error)) => return Err(error),
+                Err(nb::Error::WouldBlock) => {}
+            }
+
+            let i2c = I::regs();
+            wait_for(
+                asynch::waker(I::Interrupt::IRQ),
+                || i2c.if_().read().addr().bit_is_set(),
+                || regs::set_bits(i2c.ien(), IF_ADDR),
+            )
+            .await;
+        }
+    }
+}
@@ -1,56 +1,24 @@
 //! embedded-hal v1.0 I2C trait implementations
 
-use super::{Error, I2c0, I2c1};
-use embedded_hal::i2c::{ErrorType, I2c, Operation};
+use super::{Error, I2c, Instance};
+use embedded_hal::i2c::{ErrorType, Operation};
 
-// Implement embedded-hal ErrorType for I2c0
-impl ErrorType for I2c0 {
+// Implement embedded-hal ErrorType
+impl<I: Instance> ErrorType for I2c<I> {
     type Error = Error;
 }
 
-// Implement embedded-hal I2C trait for I2c0
-impl I2c for I2c0 {
+// Implement embedded-hal I2C trait
+//
+// The operations run as one transaction: a repeated START between operations
+// of different direction, STOP only after the last one. An empty read is
+// rejected with `Error::InvalidData`.
+impl<I: Instance> embedded_hal::i2c::I2c for I2c<I> {
     fn transaction(
         &mut self,
         address: u8,
         operations: &mut [Operation<'_>],
     ) -> Result<(), Self::Error> {
-        for operation in operations {
-            match operation {
-                Operation::Write(bytes) => {
-                    self.write(address, bytes)?;
-                }
-                Operation::Read(buffer) => {
-                    self.read(address, buffer)?;
-                }
-            }
-        }
-        Ok(())
-    }
-}
-
-// Implement embedded-hal ErrorType for I2c1
-impl ErrorType for I2c1 {
-    type Error = Error;
-}
-
-// Implement embedded-hal I2C trait for I2c1
-impl I2c for I2c1 {
-    fn transaction(
-        &mut self,
-        address: u8,
-        operations: &mut [Operation<'_>],
-    ) -> Result<(), Self::Error> {
-        for operation in operations {
-            match operation {
-                Operation::Write(bytes) => {
-                    self.write(address, bytes)?;
-                }
-                Operation::Read(buffer) => {
-                    self.read(address, buffer)?;
-                }
-            }
-        }
-        Ok(())
+        self.transfer(address, operations)
     }
 }
@@ -1,34 +1,205 @@
 //! I2C Types and Configuration
 
+use crate::time::{Hertz, MicroSeconds};
+
 /// I2C configuration
 #[derive(Debug, Clone, Copy)]
 pub struct Config {
     /// I2C bus speed
     pub speed: Speed,
+    /// Longest a blocking transfer waits for a single bus event (default 25 ms)
+    pub timeout: MicroSeconds,
+    /// Hardware clock low timeout (CTRL.CLTO, default disabled; `Cycles1024`
+    /// for async drivers)
+    pub clock_low_timeout: ClockLowTimeout,
 }
 
 impl Config {
     /// Create new I2C configuration with specified speed
     pub const fn new(speed: Speed) -> Self {
-        Self { speed }
+        Self {
+            speed,
+            timeout: MicroSeconds::from_ticks(25_000),
+            clock_low_timeout: ClockLowTimeout::Disabled,
+        }
+    }
+
+    /// Set the blocking wait timeout
+    ///
+    /// Every wait for a bus event (ACK/NACK, received byte, STOP) gives up with
+    /// [`Error::Timeout`] after roughly this long. The default of 25 ms matches
+    /// the SMBus clock low limit and leaves room for sensors that stretch the
+    /// clock during a conversion. The timeout is counted in polling loops, so
+    /// it is approximate.
+    pub const fn with_timeout(mut self, timeout: MicroSeconds) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the hardware clock low timeout
+    ///
+    /// The peripheral aborts a transfer with [`Error::Timeout`] once SCL has
+    /// been held low for this long. Unlike [`with_timeout`](Self::with_timeout),
+    /// it also covers async transfers; drivers created with `new_async` use
+    /// [`ClockLowTimeout::Cycles1024`] when it is left disabled.
+    pub const fn with_clock_low_timeout(mut self, timeout: ClockLowTimeout) -> Self {
+        self.clock_low_timeout = timeout;
+        self
     }
 }
 
 impl Default for Config {
     fn default() -> Self {
+        Self::new(Speed::Standard100kHz)
+    }
+}
+
+/// I2C target configuration
+#[derive(Debug, Clone, Copy)]
+pub struct TargetConfig {
+    /// 7-bit address the target answers at
+    pub address: u8,
+    /// Address bits that must match (SADDRMASK, default 0x7F: all bits)
+    pub address_mask: u8,
+    /// Answer the general call address 0 (default disabled)
+    pub general_call: bool,
+    /// Bus speed of the controller (default standard mode)
+    pub speed: Speed,
+    /// Longest a data phase waits for a single bus event (default 25 ms)
+    pub timeout: MicroSeconds,
+}
+
+impl TargetConfig {
+    /// Create a target configuration answering only at `address`
+    pub const fn new(address: u8) -> Self {
         Self {
+            address,
+            address_mask: 0x7F,
+            general_call: false,
             speed: Speed::Standard100kHz,
+            timeout: MicroSeconds::from_ticks(25_000),
         }
     }
+
+    /// Set the address mask
+    ///
+    /// Only the address bits set in `mask` are compared, e.g. address 0x40 with
+    /// mask 0x7C answers at 0x40 to 0x43. The matched address is reported in
+    /// the [`Request`].
+    pub const fn with_address_mask(mut self, mask: u8) -> Self {
+        self.address_mask = mask;
+        self
+    }
+
+    /// Answer the general call address 0
+    pub const fn with_general_call(mut self, enabled: bool) -> Self {
+        self.general_call = enabled;
+        self
+    }
+
+    /// Set the controller's bus speed
+    ///
+    /// Sets up the prescaled clock the target's timing runs on.
+    pub const fn with_speed(mut self, speed: Speed) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Set the data phase timeout
+    ///
+    /// [`read`](super::I2cTarget::read) and [`write`](super::I2cTarget::write)
+    /// give up with [`Error::Timeout`] when the controller does not send or
+    /// acknowledge the next byte, or end the transfer, within roughly this
+    /// long. Like [`Config::with_timeout`], it is counted in polling loops.
+    pub const fn with_timeout(mut self, timeout: MicroSeconds) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+/// Request from the controller to an I2C target
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Request {
+    /// The controller writes data to the target
+    Write {
+        /// Matched 7-bit address, 0 for a general call
+        address: u8,
+    },
+    /// The controller reads data from the target
+    Read {
+        /// Matched 7-bit address
+        address: u8,
+    },
+}
+
+/// I2C target interrupt events
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetEvent {
+    /// The controller addressed the target (IF.ADDR)
+    Request,
+    /// STOP condition received while addressed (IF.SSTOP)
+    Stop,
+}
+
+/// Hardware clock low timeout (CTRL.CLTO)
+///
+/// Counted in prescaled clock cycles: 8 per SCL period at 100 kHz, 9 at 400 kHz
+/// and 17 at 1 MHz (the CTRL.CLHR ratio of each [`Speed`]), so `Cycles1024` is
+/// 1.28 ms at 100 kHz.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockLowTimeout {
+    /// No clock low timeout
+    Disabled,
+    /// 40 prescaled clock cycles (50 us at 100 kHz)
+    Cycles40,
+    /// 80 prescaled clock cycles (100 us at 100 kHz)
+    Cycles80,
+    /// 160 prescaled clock cycles (200 us at 100 kHz)
+    Cycles160,
+    /// 320 prescaled clock cycles (400 us at 100 kHz)
+    Cycles320,
+    /// 1024 prescaled clock cycles (1.28 ms at 100 kHz)
+    Cycles1024,
 }
 
 /// I2C bus speed
+///
+/// The SCL low/high ratio (CTRL.CLHR) follows from the frequency, so the
+/// minimum low and high times of the I2C specification are met:
+///
+/// | Frequency | CLHR | Low:high |
+/// |-----------|------|----------|
+/// | up to 100 kHz | Standard | 4:4 |
+/// | up to 400 kHz | Asymmetric | 6:3 |
+/// | up to 1 MHz | Fast | 11:6 |
+///
+/// The SCL frequency does not exceed the requested one, except below the
+/// minimum of fHCLK / 4100 (about 9.5 kHz at 39 MHz), where the clock divider
+/// saturates. The achieved frequency is reported by
+/// [`I2c::scl_frequency`](super::I2c::scl_frequency).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Speed {
     /// Standard mode: 100 kHz
     Standard100kHz,
     /// Fast mode: 400 kHz
     Fast400kHz,
+    /// Fast-mode Plus: 1 MHz
+    FastPlus1MHz,
+    /// Any frequency up to 1 MHz, e.g. `Speed::Custom(50.kHz())` for long
+    /// cables; higher frequencies are limited to 1 MHz
+    Custom(Hertz),
+}
+
+impl Speed {
+    /// Requested SCL frequency
+    pub const fn frequency(self) -> Hertz {
+        match self {
+            Speed::Standard100kHz => Hertz::from_raw(100_000),
+            Speed::Fast400kHz => Hertz::from_raw(400_000),
+            Speed::FastPlus1MHz => Hertz::from_raw(1_000_000),
+            Speed::Custom(frequency) => frequency,
+        }
+    }
 }
 
 /// I2C errors
@@ -36,8 +207,13 @@ pub enum Speed {
 pub enum Error {
     /// Address or data not acknowledged (NACK received)
     Nack,
-    /// Bus error (arbitration lost, bus timeout, etc.)
+    /// Bus error (misplaced START or STOP condition)
     Bus,
+    /// Another master won arbitration
+    ArbitrationLoss,
+    /// The bus did not respond in time (software timeout, clock low or bus
+    /// idle timeout)
+    Timeout,
     /// Invalid data (empty buffer)
     InvalidData,
 }
@@ -47,6 +223,8 @@ impl core::fmt::Display for Error {
         match self {
             Error::Nack => write!(f, "I2C NACK received"),
             Error::Bus => write!(f, "I2C bus error"),
+            Error::ArbitrationLoss => write!(f, "I2C arbitration lost"),
+            Error::Timeout => write!(f, "I2C timeout"),
             Error::InvalidData => write!(f, "I2C invalid data"),
         }
     }
@@ -60,7 +238,8 @@ impl embedded_hal::i2c::Error for Error {
                 embedded_hal::i2c::NoAcknowledgeSource::Unknown,
             ),
             Error::Bus => embedded_hal::i2c::ErrorKind::Bus,
-            Error::InvalidData => embedded_hal::i2c::ErrorKind::Other,
+            Error::ArbitrationLoss => embedded_hal::i2c::ErrorKind::ArbitrationLoss,
+            Error::Timeout | Error::InvalidData => embedded_hal::i2c::ErrorKind::Other,
         }
     }
 }
@@ -0,0 +1,362 @@
+//! Typed Interrupt Binding
+//!
+//! This module lets drivers that rely on an interrupt require, at compile time,
+//! that the application has routed the right vector to the right handler.
+//!
+//! # Overview
+//!
+//! - Every NVIC vector has a marker type in [`typelevel`] (for example
+//!   [`typelevel::I2C0`]) implementing [`typelevel::Interrupt`].
+//! - A driver that needs an interrupt provides a handler type implementing
+//!   [`typelevel::Handler`] for the vectors it uses (for example
+//!   `i2c::InterruptHandler`).
+//! - The [`bind_interrupts!`](crate::bind_interrupts) macro defines the vector
+//!   functions and a zero-sized struct implementing [`typelevel::Binding`] for
+//!   each vector/handler pair.
+//! - Interrupt-driven constructors take that struct as proof, then set the
+//!   priority, unpend and enable the vector in the NVIC themselves.
+//!
+//! Binding the wrong handler, or forgetting a vector, is a compile error instead
+//! of a driver that silently never wakes up.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use efr32mg24_hal::{bind_interrupts, i2c, pac};
+//!
+//! bind_interrupts!(struct Irqs {
+//!     I2C0 => i2c::InterruptHandler;
+//! });
+//!
+//! let dp = pac::Peripherals::take().unwrap();
+//! // ...
+//! let i2c = i2c::I2c0::new_async(dp.i2c0_s, i2c::Config::default(), &clocks, Irqs);
+//! ```
+//!
+//! # Priority
+//!
+//! A vector still at its reset priority (`P0`) is lowered to
+//! [`DEFAULT_PRIORITY`] by the constructor, leaving `P0`-`P3` to the
+//! application's own latency-critical handlers. A priority set with
+//! [`typelevel::Interrupt::set_priority`] before constructing the driver is kept:
+//!
+//! ```no_run
+//! use efr32mg24_hal::interrupt::{typelevel::{self, Interrupt}, Priority};
+//!
+//! typelevel::I2C0::set_priority(Priority::P3);
+//! ```
+//!
+//! To run a driver's interrupt at `P0`, call `set_priority` after constructing
+//! the driver.
+
+use cortex_m::peripheral::NVIC;
+
+/// Number of priority bits implemented by the EFR32MG24 NVIC
+pub const NVIC_PRIO_BITS: u8 = 4;
+
+/// Priority given to driver interrupts left at their reset priority
+///
+/// See the [module documentation](self#priority).
+pub const DEFAULT_PRIORITY: Priority = Priority::P4;
+
+/// Interrupt priority level
+///
+/// Lower values are more urgent: `P0` preempts every other level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum Priority {
+    /// Priority level 0 (highest)
+    P0 = 0,
+    /// Priority level 1
+    P1 = 1,
+    /// Priority level 2
+    P2 = 2,
+    /// Priority level 3
+    P3 = 3,
+    /// Priority level 4
+    P4 = 4,
+    /// Priority level 5
+    P5 = 5,
+    /// Priority level 6
+    P6 = 6,
+    /// Priority level 7
+    P7 = 7,
+    /// Priority level 8
+    P8 = 8,
+    /// Priority level 9
+    P9 = 9,
+    /// Priority level 10
+    P10 = 10,
+    /// Priority level 11
+    P11 = 11,
+    /// Priority level 12
+    P12 = 12,
+    /// Priority level 13
+    P13 = 13,
+    /// Priority level 14
+    P14 = 14,
+    /// Priority level 15 (lowest)
+    P15 = 15,
+}
+
+impl Priority {
+    /// Value written to the NVIC IPR register (level in the upper bits)
+    pub const fn to_nvic(self) -> u8 {
+        (self as u8) << (8 - NVIC_PRIO_BITS)
+    }
+
+    /// Convert an NVIC IPR value back to a priority level
+    pub const fn from_nvic(value: u8) -> Self {
+        match value >> (8 - NVIC_PRIO_BITS) {
+            0 => Priority::P0,
+            1 => Priority::P1,
+            2 => Priority::P2,
+            3 => Priority::P3,
+            4 => Priority::P4,
+            5 => Priority::P5,
+            6 => Priority::P6,
+            7 => Priority::P7,
+            8 => Priority::P8,
+            9 => Priority::P9,
+            10 => Priority::P10,
+            11 => Priority::P11,
+            12 => Priority::P12,
+            13 => Priority::P13,
+            14 => Priority::P14,
+            _ => Priority::P15,
+        }
+    }
+}
+
+/// Type-level interrupt vectors, handlers and bindings
+pub mod typelevel {
+    use super::{Priority, NVIC};
+    use crate::pac;
+
+    mod sealed {
+        pub trait Interrupt {}
+    }
+
+    /// An NVIC interrupt vector, known at compile time
+    pub trait Interrupt: sealed::Interrupt + 'static {
+        /// Interrupt number in the PAC
+        const IRQ: pac::Interrupt;
+
+        /// Enable the interrupt in the NVIC
+        ///
+        /// # Safety
+        ///
+        /// Unmasking an interrupt can break mask-based critical sections. The
+        /// vector must also be bound to a handler (see
+        /// [`bind_interrupts!`](crate::bind_interrupts)).
+        #[inline]
+        unsafe fn enable() {
+            // SAFETY: Forwarded to the caller
+            unsafe { NVIC::unmask(Self::IRQ) }
+        }
+
+        /// Disable the interrupt in the NVIC
+        #[inline]
+        fn disable() {
+            NVIC::mask(Self::IRQ);
+        }
+
+        /// Check whether the interrupt is enabled in the NVIC
+        #[inline]
+        fn is_enabled() -> bool {
+            NVIC::is_enabled(Self::IRQ)
+        }
+
+        /// Check whether the interrupt is pending
+        #[inline]
+        fn is_pending() -> bool {
+            NVIC::is_pending(Self::IRQ)
+        }
+
+        /// Set the interrupt pending
+        #[inline]
+        fn pend() {
+            NVIC::pend(Self::IRQ);
+        }
+
+        /// Clear a pending interrupt
+        #[inline]
+        fn unpend() {
+            NVIC::unpend(Self::IRQ);
+        }
+
+        /// Get the interrupt priority
+        #[inline]
+        fn get_priority() -> Priority {
+            Priority::from_nvic(NVIC::get_priority(Self::IRQ))
+        }
+
+        /// Set the interrupt priority
+        #[inline]
+        fn set_priority(priority: Priority) {
+            critical_section::with(|_cs| {
+                // SAFETY: The IPR byte of this vector is only written here, inside
+                // a critical section. Changing a priority cannot break
+                // PRIMASK-based critical sections.
+                unsafe {
+                    let mut nvic = cortex_m::Peripherals::steal().NVIC;
+                    nvic.set_priority(Self::IRQ, priority.to_nvic());
+                }
+            });
+        }
+    }
+
+    /// Interrupt handler provided by a driver
+    pub trait Handler<I: Interrupt> {
+        /// Service the interrupt
+        ///
+        /// # Safety
+        ///
+        /// Must only be called from the interrupt vector `I`.
+        unsafe fn on_interrupt();
+    }
+
+    /// Proof that vector `I` calls handler `H`
+    ///
+    /// Implemented by the struct defined with
+    /// [`bind_interrupts!`](crate::bind_interrupts).
+    ///
+    /// # Safety
+    ///
+    /// Implementors must guarantee that `H::on_interrupt()` is called from the
+    /// vector `I`. Use the macro rather than implementing this by hand.
+    pub unsafe trait Binding<I: Interrupt, H: Handler<I>> {}
+
+    macro_rules! interrupts {
+        ($($irq:ident),* $(,)?) => {
+            $(
+                #[doc = concat!("The ", stringify!($irq), " interrupt vector")]
+                #[allow(non_camel_case_types)]
+                #[derive(Debug, Clone, Copy)]
+                pub enum $irq {}
+
+                impl sealed::Interrupt for $irq {}
+
+                impl Interrupt for $irq {
+                    const IRQ: pac::Interrupt = pac::Interrupt::$irq;
+                }
+            )*
+        };
+    }
+
+    interrupts!(
+        SMU_SECURE,
+        SMU_S_PRIVILEGED,
+        EMU,
+        TIMER0,
+        TIMER1,
+        TIMER2,
+        TIMER3,
+        TIMER4,
+        USART0_RX,
+        USART0_TX,
+        EUSART0_RX,
+        EUSART0_TX,
+        EUSART1_RX,
+        EUSART1_TX,
+        ICACHE0,
+        BURTC,
+        LETIMER0,
+        SYSCFG,
+        LDMA,
+        LFXO,
+        ULFRCO,
+        GPIO_ODD,
+        GPIO_EVEN,
+        I2C0,
+        I2C1,
+        EMUDG,
+        ACMP0,
+        ACMP1,
+        WDOG0,
+        WDOG1,
+        HFXO0,
+        HFRCO0,
+        HFRCOEM23,
+        CMU,
+        AES,
+        IADC,
+        MSC,
+        DPLL0,
+        PCNT0,
+        SW0,
+        SW1,
+        SW2,
+        SW3,
+        SEMBRX,
+        SEMBTX,
+        SYSRTC_APP,
+        KEYSCAN,
+    );
+}
+
+/// Set the default priority, unpend and enable a bound interrupt vector
+///
+/// Called by interrupt-driven constructors once they have received a
+/// [`typelevel::Binding`] for the vector. A vector at the reset priority (`P0`)
+/// is lowered to [`DEFAULT_PRIORITY`]; any other priority is kept.
+pub(crate) fn enable<I: typelevel::Interrupt>() {
+    if I::get_priority() == Priority::P0 {
+        I::set_priority(DEFAULT_PRIORITY);
+    }
+    I::unpend();
+    // SAFETY: The caller holds a Binding for I, so the vector has a handler.
+    // The HAL uses PRIMASK-based critical sections, which unmasking does not
+    // affect.
+    unsafe { I::enable() };
+}
+
+/// Bind interrupt vectors to driver handlers
+///
+/// Defines a zero-sized struct that implements
+/// [`Binding`](crate::interrupt::typelevel::Binding) for each listed
+/// vector/handler pair, and the vector functions that call the handlers. Pass
+/// the struct to interrupt-driven constructors as proof of the binding.
+///
+/// A vector can call several handlers, separated by commas. Each vector may only
+/// be bound once per program; binding it twice fails at link time.
+///
+/// Requires the `rt` feature so the vector table refers to the functions.
+///
+/// # Example
+///
+/// ```no_run
+/// use efr32mg24_hal::{bind_interrupts, i2c, usart};
+///
+/// bind_interrupts!(struct Irqs {
+///     USART0_RX => usart::InterruptHandler;
+///     USART0_TX => usart::InterruptHandler;
+///     I2C0 => i2c::InterruptHandler;
+/// });
+/// ```
+#[macro_export]
+macro_rules! bind_interrupts {
+    ($vis:vis struct $name:ident { $($irq:ident => $($handler:ty),+;)* }) => {
+        #[derive(Copy, Clone)]
+        $vis struct $name;
+
+        $(
+            #[allow(non_snake_case)]
+            #[no_mangle]
+            unsafe extern "C" fn $irq() {
+                $(
+                    <$handler as $crate::interrupt::typelevel::Handler<
+                        $crate::interrupt::typelevel::$irq,
+                    >>::on_interrupt();
+                )+
+            }
+
+            $(
+                unsafe impl $crate::interrupt::typelevel::Binding<
+                    $crate::interrupt::typelevel::$irq,
+                    $handler,
+                > for $name {}
+            )+
+        )*
+    };
+}
@@ -26,7 +26,7 @@
 //!
 //! ## Phase B - Communication Peripherals (Complete)
 //!
-//! - **USART** - Serial communication (USART0) with embedded-hal-nb traits ✅
+//! - **USART** - Serial communication (USART0, EUSART0/1 UARTs) with embedded-hal-nb traits ✅
 //! - **I2C** - I2C master mode (I2C0, I2C1) with embedded-hal traits ✅
 //! - **SPI** - SPI master mode (USART in SPI mode) with embedded-hal traits ✅
 //! - **Timer** - Timer and PWM (TIMER0-4) with 3 channels each ✅
@@ -34,7 +34,7 @@
 //! ## Phase C - Advanced Peripherals (In Progress)
 //!
 //! - **ADC (IADC)** - Analog-to-digital converter ✅
-//! - **DMA (LDMA)** - Direct Memory Access controller ⏳
+//! - **DMA (LDMA)** - Direct Memory Access controller, USART0 DMA transfers ✅
 //! - Power management (EMU)
 //! - RTC (RTCC)
 //! - Watchdog (WDOG)
@@ -57,7 +57,7 @@
 //! let clocks = Clocks::new(
 //!     dp.cmu_s,
 //!     ClockConfig {
-//!         hfxo: Some(HfxoConfig::new(39_000_000)),
+//!         hfxo: Some(HfxoConfig::new(39.MHz())),
 //!         lfxo: Some(Default::default()),
 //!     }
 //! ).freeze();
@@ -74,34 +74,59 @@
 //! # Module Organization
 //!
 //! - [`adc`] - Analog-to-Digital Converter (IADC)
+//! - `asynch` - Interrupt handlers for the async drivers (`async` feature)
 //! - [`clock`] - Clock Management Unit (CMU) configuration
-//! - [`delay`] - Blocking delays using SysTick
+//! - [`delay`] - Blocking delays (SysTick, DWT cycle counter, SYSRTC0)
 //! - [`dma`] - Direct Memory Access (LDMA) controller
 //! - [`gpio`] - General Purpose I/O
 //! - [`i2c`] - I2C master mode
-//! - [`spi`] - SPI master mode (USART in SPI mode)
+//! - [`interrupt`] - Typed interrupt binding ([`bind_interrupts!`]) and NVIC priorities
+//! - `logging` - `log` and `defmt` loggers over a HAL UART (`log` / `defmt` features)
+//! - [`spi`] - SPI master and slave modes (USART/EUSART in SPI mode)
+//! - [`time`] - Typed frequency, baud rate and duration units (`fugit`)
 //! - [`timer`] - Timer and PWM functionality (TIMER0-4)
 //! - [`usart`] - USART/UART serial communication
+//! - `time_driver` - embassy-time driver on SYSRTC0 or TIMER0 (`time-driver-*` features)
 //! - [`prelude`] - Common imports for convenience
 //!
 //! # Feature Flags
 //!
 //! - `rt` - Include runtime support (startup code, vector table)
+//! - `time-driver-sysrtc0` - Implement the `embassy-time` driver on SYSRTC0, with EM2
+//!   wake-up (implies `rt`)
+//! - `time-driver-timer0` - Implement the `embassy-time` driver on TIMER0 (implies `rt`)
+//! - `async` - embedded-hal-async / embedded-io-async implementations for USART, SPI, I2C
+//!   and GPIO
+//! - `log` - Global `log` logger writing to a HAL UART (`logging` module)
+//! - `defmt` - `defmt` global logger writing to a HAL UART (`logging` module)
 
 // Re-export the PAC
 pub use efr32mg24_pac as pac;
 
 // HAL modules
 pub mod adc;
+#[cfg(feature = "async")]
+pub mod asynch;
 pub mod clock;
 pub mod delay;
 pub mod dma;
 pub mod gpio;
 pub mod i2c;
+pub mod interrupt;
+#[cfg(any(feature = "log", feature = "defmt"))]
+pub mod logging;
 pub mod spi;
+pub mod time;
+#[cfg(feature = "_time-driver")]
+pub mod time_driver;
 pub mod timer;
 pub mod usart;
 
+// Crate-internal helpers
+#[allow(dead_code)]
+mod regs;
+mod ring_buffer;
+
 /// Prelude module for convenient imports
 ///
 /// This module re-exports the most commonly used types and traits
@@ -122,4 +147,9 @@ pub mod prelude {
 
     /// Re-export GPIO extension trait
     pub use crate::gpio::GpioExt as _efr32mg24_hal_gpio_GpioExt;
+
+    /// Re-export time and rate extension traits (`.MHz()`, `.millis()`, `.bps()`)
+    pub use crate::time::BpsExt as _efr32mg24_hal_time_BpsExt;
+    pub use crate::time::ExtU32 as _fugit_ExtU32;
+    pub use crate::time::RateExtU32 as _fugit_RateExtU32;
 }
@@ -0,0 +1,100 @@
+//! defmt global logger
+//!
+//! defmt frames are encoded with rzCOBS and written to the global transport, to
+//! be decoded on the host, e.g. with `defmt-print`. The application must not link
+//! another defmt global logger such as `defmt-rtt`.
+//!
+//! A frame holds the transport lock from `acquire` to `release`, with interrupts
+//! enabled. A frame started by an interrupt while another frame is being
+//! written is dropped.
+
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use super::{locked_transport, try_lock, unlock};
+
+/// The defmt global logger
+#[defmt::global_logger]
+struct DefmtLogger;
+
+/// Frames being dropped because they preempted the frame holding the lock
+static DROPPING: AtomicU8 = AtomicU8::new(0);
+
+/// Frame encoder, used only while the transport lock is held
+struct EncoderCell(UnsafeCell<defmt::Encoder>);
+
+// SAFETY: The encoder is only accessed by the frame holding the transport lock.
+unsafe impl Sync for EncoderCell {}
+
+static ENCODER: EncoderCell = EncoderCell(UnsafeCell::new(defmt::Encoder::new()));
+
+#[cfg(feature = "_time-driver")]
+defmt::timestamp!("{=u64:us}", super::timestamp_us().unwrap_or(0));
+
+/// Write encoded bytes to the transport
+///
+/// Only called while the transport lock is held.
+fn write_encoded(bytes: &[u8]) {
+    // SAFETY: Called from the encoder, which is only used with the lock held.
+    if let Some(transport) = unsafe { locked_transport() } {
+        transport.write(bytes);
+    }
+}
+
+/// Check whether the current frame is being dropped
+///
+/// Frames preempting the lock holder run to completion before it resumes, so a
+/// nonzero count always refers to the innermost, current frame.
+fn dropping() -> bool {
+    DROPPING.load(Ordering::Relaxed) != 0
+}
+
+// SAFETY: A frame is only encoded while it holds the transport lock, from
+// `acquire` to `release`; frames that cannot take it are dropped entirely, so
+// frames are never interleaved.
+unsafe impl defmt::Logger for DefmtLogger {
+    fn acquire() {
+        if !try_lock() {
+            DROPPING.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+
+        // SAFETY: The lock is held.
+        unsafe { (*ENCODER.0.get()).start_frame(write_encoded) };
+    }
+
+    unsafe fn flush() {
+        if dropping() {
+            return;
+        }
+        // SAFETY: defmt calls `flush` between `acquire` and `release`, so the
+        // lock is held.
+        if let Some(transport) = unsafe { locked_transport() } {
+            transport.flush();
+        }
+    }
+
+    unsafe fn release() {
+        if dropping() {
+            DROPPING.fetch_sub(1, Ordering::Relaxed);
+            return;
+        }
+
+        // SAFETY: The lock is held since `acquire`.
+        unsafe {
+            (*ENCODER.0.get()).end_frame(write_encoded);
+            if let Some(transport) = locked_transport() {
+                transport.flush();
+            }
+        }
+        unlock();
+    }
+
+    unsafe fn write(bytes: &[u8]) {
+        if dropping() {
+            return;
+        }
+        // SAFETY: The lock is held since `acquire`.
+        unsafe { (*ENCODER.0.get()).write(bytes, write_encoded) };
+    }
+}
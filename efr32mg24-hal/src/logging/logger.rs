@@ -0,0 +1,77 @@
+//! Global `log` logger
+//!
+//! Each record is written as one line:
+//!
+//! ```text
+//! [   12.345678] INFO  app::radio: joined network 0x1a2b
+//! ```
+//!
+//! The timestamp, in seconds, is left out without a time source.
+
+use core::fmt::Write as _;
+
+use super::{init, timestamp_us, with_transport, Transport};
+
+/// The registered `log` logger
+struct UartLogger;
+
+static LOGGER: UartLogger = UartLogger;
+
+/// `core::fmt::Write` adapter over a transport
+struct TransportWriter<'a>(&'a mut dyn Transport);
+
+impl core::fmt::Write for TransportWriter<'_> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.0.write(s.as_bytes());
+        Ok(())
+    }
+}
+
+impl log::Log for UartLogger {
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        with_transport(|transport| {
+            let mut writer = TransportWriter(transport);
+            if let Some(us) = timestamp_us() {
+                let _ = write!(writer, "[{:5}.{:06}] ", us / 1_000_000, us % 1_000_000);
+            }
+            let _ = write!(
+                writer,
+                "{:<5} {}: {}\r\n",
+                record.level(),
+                record.target(),
+                record.args()
+            );
+            transport.flush();
+        });
+    }
+
+    fn flush(&self) {
+        with_transport(|transport| transport.flush());
+    }
+}
+
+/// Install `transport` and register it as the global `log` logger
+///
+/// Records above `level` are discarded before they are formatted.
+///
+/// # Errors
+///
+/// Returns an error if another `log` logger has already been registered; the
+/// transport is installed anyway.
+pub fn init_log(
+    transport: &'static mut dyn Transport,
+    level: log::LevelFilter,
+) -> Result<(), log::SetLoggerError> {
+    init(transport);
+    log::set_logger(&LOGGER)?;
+    log::set_max_level(level);
+    Ok(())
+}
@@ -0,0 +1,194 @@
+//! Logging over a HAL UART
+//!
+//! This module provides a global [`log`](https://docs.rs/log) logger (`log`
+//! feature) and a [`defmt`](https://defmt.ferrous-systems.com) global logger
+//! (`defmt` feature) that write to a HAL UART. The UART is installed once as the
+//! global [`Transport`] and then shared by every log call.
+//!
+//! # Transports
+//!
+//! [`Transport`] is implemented for the blocking UART drivers
+//! ([`Usart0`], its transmit half [`Tx`], [`Eusart0`], [`Eusart1`]) and for
+//! [`DmaWriter`], which hands each record to the LDMA and returns without
+//! waiting for the transmission.
+//!
+//! Records are written with interrupts enabled. The transport is protected by a
+//! lock rather than a critical section: a record logged from an interrupt that
+//! preempted another record being written is dropped, so records are never
+//! interleaved and a blocking write never delays interrupts.
+//!
+//! # Timestamps
+//!
+//! With a `time-driver-*` feature, records are timestamped with the
+//! embassy-time driver's clock, in microseconds since it was started. Without a
+//! time source, records carry no timestamp.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use efr32mg24_hal::{logging, usart::{Config, Usart0}};
+//!
+//! let usart = Usart0::new(dp.usart0_s, Config::default(), &clocks);
+//! let usart = cortex_m::singleton!(: Usart0 = usart).unwrap();
+//! logging::init_log(usart, log::LevelFilter::Info).unwrap();
+//!
+//! log::info!("booted, reset cause {:?}", cause);
+//! ```
+//!
+//! [`Usart0`]: crate::usart::Usart0
+//! [`Tx`]: crate::usart::Tx
+//! [`Eusart0`]: crate::usart::Eusart0
+//! [`Eusart1`]: crate::usart::Eusart1
+//! [`DmaWriter`]: crate::usart::DmaWriter
+
+#[cfg(feature = "defmt")]
+mod defmt_logger;
+#[cfg(feature = "log")]
+mod logger;
+
+#[cfg(feature = "log")]
+pub use logger::init_log;
+
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::usart::{DmaWriter, Eusart0, Eusart1, Tx, Usart0};
+
+/// Byte sink that log records are written to
+///
+/// Called from any context that logs, with interrupts enabled, and never
+/// reentered: only one record is written at a time.
+pub trait Transport: Send {
+    /// Write `bytes`
+    fn write(&mut self, bytes: &[u8]);
+
+    /// Start sending a finished record
+    ///
+    /// Called at the end of each record. Need not wait for the transmission to
+    /// complete; the blocking UARTs have already queued every byte.
+    fn flush(&mut self) {}
+}
+
+impl Transport for Usart0 {
+    fn write(&mut self, bytes: &[u8]) {
+        Usart0::write(self, bytes);
+    }
+}
+
+impl Transport for Tx {
+    fn write(&mut self, bytes: &[u8]) {
+        Tx::write(self, bytes);
+    }
+}
+
+impl Transport for Eusart0 {
+    fn write(&mut self, bytes: &[u8]) {
+        Eusart0::write(self, bytes);
+    }
+}
+
+impl Transport for Eusart1 {
+    fn write(&mut self, bytes: &[u8]) {
+        Eusart1::write(self, bytes);
+    }
+}
+
+impl<const N: u8> Transport for DmaWriter<N> {
+    fn write(&mut self, bytes: &[u8]) {
+        DmaWriter::write(self, bytes);
+    }
+
+    fn flush(&mut self) {
+        self.send();
+    }
+}
+
+/// The global transport and the lock serializing access to it
+struct TransportLock {
+    locked: AtomicBool,
+    transport: UnsafeCell<Option<&'static mut dyn Transport>>,
+}
+
+// SAFETY: `transport` is only accessed while `locked` is held, and transports
+// are `Send`.
+unsafe impl Sync for TransportLock {}
+
+/// The global transport, installed by [`init`]
+static TRANSPORT: TransportLock = TransportLock {
+    locked: AtomicBool::new(false),
+    transport: UnsafeCell::new(None),
+};
+
+/// Take the transport lock
+///
+/// Returns `false` if it is held, i.e. a record is being written by code this
+/// call has preempted.
+fn try_lock() -> bool {
+    TRANSPORT
+        .locked
+        .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+        .is_ok()
+}
+
+/// Release the transport lock
+fn unlock() {
+    TRANSPORT.locked.store(false, Ordering::Release);
+}
+
+/// The installed transport
+///
+/// # Safety
+///
+/// The caller must hold the transport lock, and must not keep the reference
+/// after releasing it.
+unsafe fn locked_transport() -> Option<&'static mut dyn Transport> {
+    // SAFETY: The lock gives the caller exclusive access.
+    unsafe { (*TRANSPORT.transport.get()).as_deref_mut() }
+}
+
+/// Install `transport` as the global log transport
+///
+/// Replaces a previously installed transport, waiting for a record being
+/// written to finish. Records logged before this are dropped. Must not be
+/// called from an interrupt handler. With the `log` feature, use `init_log`
+/// instead, which also registers the logger.
+pub fn init(transport: &'static mut dyn Transport) {
+    while !try_lock() {
+        core::hint::spin_loop();
+    }
+    // SAFETY: The lock is held.
+    unsafe { *TRANSPORT.transport.get() = Some(transport) };
+    unlock();
+}
+
+/// Run `f` on the global transport, with interrupts enabled
+///
+/// Does nothing if no transport is installed, or if the transport is in use by
+/// the code this call has preempted (the record is dropped).
+#[cfg(feature = "log")]
+fn with_transport(f: impl FnOnce(&mut dyn Transport)) {
+    if !try_lock() {
+        return;
+    }
+    // SAFETY: The lock is held until after `f` returns.
+    if let Some(transport) = unsafe { locked_transport() } {
+        f(transport);
+    }
+    unlock();
+}
+
+/// Microseconds since the system time source started, if there is one
+#[cfg(any(feature = "log", feature = "_time-driver"))]
+fn timestamp_us() -> Option<u64> {
+    #[cfg(feature = "_time-driver")]
+    {
+        use embassy_time_driver::TICK_HZ;
+
+        let ticks = embassy_time_driver::now();
+        Some(ticks / TICK_HZ * 1_000_000 + ticks % TICK_HZ * 1_000_000 / TICK_HZ)
+    }
+    #[cfg(not(feature = "_time-driver"))]
+    {
+        None
+    }
+}
@@ -0,0 +1,32 @@
+//! Register access helpers shared by the peripheral drivers
+//!
+//! Every Series 2 peripheral register is mirrored at two fixed offsets: a SET
+//! alias (+0x1000) and a CLR alias (+0x2000). Writing ones to an alias sets or
+//! clears exactly those bits in a single bus write, without the read-modify-write
+//! race of `modify()`. Interrupt flags in particular must be cleared through the
+//! CLR alias, since the IF register itself is read/write and writing ones to it
+//! sets the flags rather than clearing them.
+
+use crate::pac::generic::{Reg, RegisterSpec};
+
+/// Offset of the SET alias from the register address
+const SET_OFFSET: usize = 0x1000;
+
+/// Offset of the CLR alias from the register address
+const CLR_OFFSET: usize = 0x2000;
+
+/// Set the bits in `mask` through the register's SET alias
+#[inline]
+pub(crate) fn set_bits<REG: RegisterSpec<Ux = u32>>(reg: &Reg<REG>, mask: u32) {
+    // SAFETY: All Series 2 peripheral registers have a SET alias 0x1000 bytes above
+    // the register. Writing ones to the alias sets only those bits.
+    unsafe { core::ptr::write_volatile(reg.as_ptr().byte_add(SET_OFFSET), mask) }
+}
+
+/// Clear the bits in `mask` through the register's CLR alias
+#[inline]
+pub(crate) fn clear_bits<REG: RegisterSpec<Ux = u32>>(reg: &Reg<REG>, mask: u32) {
+    // SAFETY: All Series 2 peripheral registers have a CLR alias 0x2000 bytes above
+    // the register. Writing ones to the alias clears only those bits.
+    unsafe { core::ptr::write_volatile(reg.as_ptr().byte_add(CLR_OFFSET), mask) }
+}
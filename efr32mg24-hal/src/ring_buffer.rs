@@ -0,0 +1,153 @@
+//! Lock-free single-producer, single-consumer byte ring buffer
+//!
+//! Shared between an interrupt handler and the application by the buffered
+//! drivers. The storage is a caller-provided `&'static mut [u8]` attached at run
+//! time, so the ring itself can live in a `static`.
+//!
+//! Exactly one side pushes and exactly one side pops. The producer only writes
+//! `end` and the consumer only writes `start`. Both indices run over
+//! `0..2 * len`, which tells a full buffer apart from an empty one without
+//! wasting a slot.
+
+use core::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+
+/// Byte ring buffer with caller-provided storage
+pub(crate) struct RingBuffer {
+    buf: AtomicPtr<u8>,
+    len: AtomicUsize,
+    start: AtomicUsize,
+    end: AtomicUsize,
+}
+
+impl RingBuffer {
+    /// Create a ring buffer without storage
+    ///
+    /// Pushes fail and pops return `None` until [`init`](Self::init) is called.
+    pub(crate) const fn new() -> Self {
+        Self {
+            buf: AtomicPtr::new(core::ptr::null_mut()),
+            len: AtomicUsize::new(0),
+            start: AtomicUsize::new(0),
+            end: AtomicUsize::new(0),
+        }
+    }
+
+    /// Attach `len` bytes of storage at `buf` and empty the buffer
+    ///
+    /// # Safety
+    ///
+    /// `buf` must be valid for reads and writes of `len` bytes until
+    /// [`deinit`](Self::deinit), and nothing may push or pop concurrently.
+    pub(crate) unsafe fn init(&self, buf: *mut u8, len: usize) {
+        self.len.store(0, Ordering::Relaxed);
+        self.start.store(0, Ordering::Relaxed);
+        self.end.store(0, Ordering::Relaxed);
+        self.buf.store(buf, Ordering::Relaxed);
+        self.len.store(len, Ordering::Release);
+    }
+
+    /// Detach the storage and empty the buffer
+    pub(crate) fn deinit(&self) {
+        self.len.store(0, Ordering::Release);
+        self.buf.store(core::ptr::null_mut(), Ordering::Relaxed);
+        self.start.store(0, Ordering::Relaxed);
+        self.end.store(0, Ordering::Relaxed);
+    }
+
+    /// Storage size in bytes
+    pub(crate) fn capacity(&self) -> usize {
+        self.len.load(Ordering::Acquire)
+    }
+
+    /// Number of bytes currently stored
+    pub(crate) fn len(&self) -> usize {
+        let len = self.capacity();
+        let start = self.start.load(Ordering::Acquire);
+        let end = self.end.load(Ordering::Acquire);
+        Self::occupied(start, end, len)
+    }
+
+    /// Check whether the buffer holds no bytes
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Check whether the buffer has no free space
+    pub(crate) fn is_full(&self) -> bool {
+        self.len() == self.capacity()
+    }
+
+    /// Append a byte (producer side)
+    ///
+    /// Returns `false`, dropping the byte, if the buffer is full.
+    pub(crate) fn push(&self, byte: u8) -> bool {
+        let len = self.capacity();
+        let start = self.start.load(Ordering::Acquire);
+        let end = self.end.load(Ordering::Relaxed);
+        if len == 0 || Self::occupied(start, end, len) == len {
+            return false;
+        }
+
+        // SAFETY: `len` is non-zero and the buffer is not full, so storage is attached and the slot
+        // index is below `len`. The consumer does not read this slot until
+        // `end` is published below.
+        unsafe {
+            self.buf
+                .load(Ordering::Relaxed)
+                .add(Self::slot(end, len))
+                .write(byte)
+        };
+        self.end.store(Self::wrap(end + 1, len), Ordering::Release);
+        true
+    }
+
+    /// Remove the oldest byte (consumer side)
+    pub(crate) fn pop(&self) -> Option<u8> {
+        let len = self.capacity();
+        let start = self.start.load(Ordering::Relaxed);
+        let end = self.end.load(Ordering::Acquire);
+        if len == 0 || Self::occupied(start, end, len) == 0 {
+            return None;
+        }
+
+        // SAFETY: The buffer is not empty, so storage is attached and the slot
+        // index is below `len`. The producer does not overwrite this slot until
+        // `start` is published below.
+        let byte = unsafe {
+            self.buf
+                .load(Ordering::Relaxed)
+                .add(Self::slot(start, len))
+                .read()
+        };
+        self.start
+            .store(Self::wrap(start + 1, len), Ordering::Release);
+        Some(byte)
+    }
+
+    /// Bytes between `start` and `end`
+    fn occupied(start: usize, end: usize, len: usize) -> usize {
+        if end >= start {
+            end - start
+        } else {
+            end + 2 * len - start
+        }
+    }
+
+    /// Storage index of ring index `index`
+    fn slot(index: usize, len: usize) -> usize {
+        if index >= len {
+            index - len
+        } else {
+            index
+        }
+    }
+
+    /// Fold ring index `index` back into `0..2 * len`
+    fn wrap(index: usize, len: usize) -> usize {
+        if index >= 2 * len {
+            index - 2 * len
+        } else {
+            index
+        }
+    }
+}
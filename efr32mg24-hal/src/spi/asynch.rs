@@ -0,0 +1,215 @@
+//! embedded-hal-async SPI trait implementations
+//!
+//! Each frame is exchanged by waiting for room in the TX buffer, writing it, and
+//! waiting for the received frame. The driver sleeps on the peripheral's TX and RX
+//! interrupts in between. Both vectors must be bound to [`InterruptHandler`]
+//! and the driver created with `new_async`:
+//!
+//! - `Spi0`: USART0_TX and USART0_RX
+//! - `Spi1`: EUSART0_TX and EUSART0_RX
+//! - `Spi2`: EUSART1_TX and EUSART1_RX
+
+use super::{Config, Instance, Spi};
+use crate::asynch::{self, wait_for};
+use crate::clock::FrozenClocks;
+use crate::interrupt::{
+    self,
+    typelevel::{self, Binding, Handler, Interrupt},
+};
+use crate::pac;
+use embedded_hal_async::spi::SpiBus;
+
+/// USART/EUSART IF/IEN bit positions (identical on both peripherals)
+const IF_TXC: u32 = 1 << 0;
+const IF_TX_READY: u32 = 1 << 1;
+const IF_RX_READY: u32 = 1 << 2;
+
+/// SPI interrupt handler for the async drivers
+///
+/// Bind the TX and RX vectors of the underlying USART/EUSART to this handler
+/// with [`bind_interrupts!`](crate::bind_interrupts).
+pub struct InterruptHandler {
+    _private: (),
+}
+
+macro_rules! impl_handler {
+    ($($irq:ident),*) => {
+        $(
+            impl Handler<typelevel::$irq> for InterruptHandler {
+                unsafe fn on_interrupt() {
+                    asynch::on_interrupt(pac::Interrupt::$irq);
+                }
+            }
+        )*
+    };
+}
+
+impl_handler!(USART0_RX, USART0_TX, EUSART0_RX, EUSART0_TX, EUSART1_RX, EUSART1_TX);
+
+impl<I: Instance> Spi<I> {
+    /// Create a new SPI master instance for async use
+    ///
+    /// Configures the peripheral like [`new`](Self::new), then enables its TX
+    /// and RX interrupts in the NVIC. `_irqs` proves that both vectors are bound
+    /// to [`InterruptHandler`].
+    pub fn new_async(
+        periph: I,
+        config: Config,
+        clocks: &FrozenClocks,
+        _irqs: impl Binding<I::TxInterrupt, InterruptHandler>
+            + Binding<I::RxInterrupt, InterruptHandler>,
+    ) -> Self
+    where
+        InterruptHandler: Handler<I::TxInterrupt> + Handler<I::RxInterrupt>,
+    {
+        let spi = Self::new(periph, config, clocks);
+        interrupt::enable::<I::RxInterrupt>();
+        interrupt::enable::<I::TxInterrupt>();
+        spi
+    }
+
+    /// Wait until the TX buffer has room
+    async fn wait_tx_ready(&mut self) {
+        let regs = I::regs();
+        wait_for(
+            asynch::waker(I::TxInterrupt::IRQ),
+            || regs.tx_ready(),
+            || {
+                regs.clear_flags(IF_TX_READY);
+                regs.enable_interrupts(IF_TX_READY);
+            },
+        )
+        .await
+    }
+
+    /// Wait until a received frame is available
+    async fn wait_rx_ready(&mut self) {
+        let regs = I::regs();
+        wait_for(
+            asynch::waker(I::RxInterrupt::IRQ),
+            || regs.rx_ready(),
+            || {
+                regs.clear_flags(IF_RX_READY);
+                regs.enable_interrupts(IF_RX_READY);
+            },
+        )
+        .await
+    }
+
+    /// Wait until the last frame has left the shift register
+    async fn wait_txc(&mut self) {
+        let regs = I::regs();
+        wait_for(
+            asynch::waker(I::TxInterrupt::IRQ),
+            || regs.tx_complete(),
+            || {
+                regs.clear_flags(IF_TXC);
+                regs.enable_interrupts(IF_TXC);
+            },
+        )
+        .await
+    }
+
+    /// Transfer a single frame, sleeping while the bus is busy
+    async fn transfer_word_async(&mut self, word: u16) -> u16 {
+        self.wait_tx_ready().await;
+        self.write_frame(word);
+
+        self.wait_rx_ready().await;
+        self.read_frame()
+    }
+}
+
+impl<I: Instance> SpiBus<u8> for Spi<I> {
+    async fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        self.check_byte_frames()?;
+        for word in words.iter_mut() {
+            *word = self.transfer_word_async(0x00).await as u8;
+        }
+        Ok(())
+    }
+
+    async fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        self.check_byte_frames()?;
+        for &word in words {
+            self.transfer_word_async(word as u16).await;
+        }
+        Ok(())
+    }
+
+    /// Full-duplex transfer
+    ///
+    /// Clocks `max(read.len(), write.len())` bytes: missing write bytes are
+    /// sent as `0x00` and extra received bytes are discarded.
+    async fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        self.check_byte_frames()?;
+        let len = read.len().max(write.len());
+        for i in 0..len {
+            let received = self
+                .transfer_word_async(write.get(i).copied().unwrap_or(0x00) as u16)
+                .await;
+            if let Some(rx) = read.get_mut(i) {
+                *rx = received as u8;
+            }
+        }
+        Ok(())
+    }
+
+    async fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        self.check_byte_frames()?;
+        for word in words.iter_mut() {
+            *word = self.transfer_word_async(*word as u16).await as u8;
+        }
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        self.wait_txc().await;
+        Ok(())
+    }
+}
+
+impl<I: Instance> SpiBus<u16> for Spi<I> {
+    async fn read(&mut self, words: &mut [u16]) -> Result<(), Self::Error> {
+        for word in words.iter_mut() {
+            *word = self.transfer_word_async(0x0000).await;
+        }
+        Ok(())
+    }
+
+    async fn write(&mut self, words: &[u16]) -> Result<(), Self::Error> {
+        for &word in words {
+            self.transfer_word_async(word).await;
+        }
+        Ok(())
+    }
+
+    /// Full-duplex transfer
+    ///
+    /// Clocks `max(read.len(), write.len())` frames: missing write frames
+    /// are sent as `0x0000` and extra received frames are discarded.
+    async fn transfer(&mut self, read: &mut [u16], write: &[u16]) -> Result<(), Self::Error> {
+        let len = read.len().max(write.len());
+        for i in 0..len {
+            let received = self
+                .transfer_word_async(write.get(i).copied().unwrap_or(0x0000))
+                .await;
+            if let Some(rx) = read.get_mut(i) {
+                *rx = received;
+            }
+        }
+        Ok(())
+    }
+
+    async fn transfer_in_place(&mut self, words: &mut [u16]) -> Result<(), Self::Error> {
+        for word in words.iter_mut() {
+            *word = self.transfer_word_async(*word).await;
+        }
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        self.wait_txc().await;
+        Ok(())
+    }
+}
@@ -0,0 +1,529 @@
+//! `SpiDevice` implementations: chip select management and bus sharing
+//!
+//! An [`SpiBus`] only moves data. An [`SpiDevice`] also asserts the device's
+//! chip select (CS) for the duration of a transaction, so drivers for external
+//! flash, displays or radios can own their device while sharing the bus.
+//!
+//! - [`ExclusiveCsDevice`] owns the bus, for a single device with a GPIO CS.
+//! - [`CriticalSectionCsDevice`] shares a bus in a
+//!   `critical_section::Mutex<RefCell<_>>`; each transaction runs in a critical
+//!   section, so devices can be used from interrupts too.
+//! - [`RefCellCsDevice`] shares a bus in a `RefCell`, for devices used from a
+//!   single execution context. Interrupts stay enabled during transactions.
+//!
+//! They work like the `embedded-hal-bus` devices of similar names, and differ
+//! in taking CS setup and hold times and in accepting [`NoDelay`].
+//!
+//! The GPIO CS is active low. Optional setup and hold delays, in nanoseconds,
+//! are inserted after asserting CS and before deasserting it, using the
+//! device's [`DelayNs`] provider, which also executes [`Operation::DelayNs`].
+//! Devices that need neither can use [`NoDelay`]: they cannot set CS timing,
+//! and reject transactions containing a non-zero delay with
+//! [`DeviceError::Delay`] before asserting CS.
+//!
+//! With [`Config::with_hardware_cs`](super::Config::with_hardware_cs), the
+//! SPI driver itself implements [`SpiDevice`] and the peripheral drives CS
+//! (AUTOCS). The bytes of all operations are sent as one continuous stream, which
+//! keeps CS asserted; [`Operation::DelayNs`] cannot be part of such a transaction.
+//!
+//! All devices accept `u8` words, and `u16` words for frames wider than 8 bits
+//! (see [`Config::with_data_bits`](super::Config::with_data_bits)).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use core::cell::RefCell;
+//! use critical_section::Mutex;
+//! use efr32mg24_hal::spi::{CriticalSectionCsDevice, NoDelay, Spi0};
+//!
+//! let bus = Mutex::new(RefCell::new(Spi0::new(dp.usart0_s, config, &clocks)));
+//! let mut flash = CriticalSectionCsDevice::new(&bus, gpio.porta.pa4.into_push_pull_output(), NoDelay)?;
+//! let mut display = CriticalSectionCsDevice::new(&bus, gpio.portc.pc1.into_push_pull_output(), delay)?
+//!     .cs_setup_ns(50)
+//!     .cs_hold_ns(50);
+//!
+//! let mut id = [0u8; 3];
+//! flash.transaction(&mut [Operation::Write(&[0x9F]), Operation::Read(&mut id)])?;
+//! display.write(&[0x2C, 0x00, 0xF8])?;
+//! ```
+
+use core::cell::RefCell;
+
+use critical_section::Mutex;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+use embedded_hal::spi::{ErrorKind, ErrorType, Operation, SpiBus, SpiDevice};
+
+use super::{Error, Instance, Spi};
+
+/// Error of a device transaction: from the bus or from the CS pin
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceError<BUS, CS> {
+    /// The SPI bus failed
+    Spi(BUS),
+    /// Setting the CS pin failed
+    Cs(CS),
+    /// The transaction contains a delay, but the device uses [`NoDelay`]
+    Delay,
+}
+
+impl<BUS, CS> embedded_hal::spi::Error for DeviceError<BUS, CS>
+where
+    BUS: embedded_hal::spi::Error,
+    CS: core::fmt::Debug,
+{
+    fn kind(&self) -> ErrorKind {
+        match self {
+            DeviceError::Spi(error) => error.kind(),
+            DeviceError::Cs(_) => ErrorKind::ChipSelectFault,
+            DeviceError::Delay => ErrorKind::Other,
+        }
+    }
+}
+
+/// Delay provider for devices without CS timing or delay operations
+///
+/// Transactions containing a non-zero [`Operation::DelayNs`] fail with
+/// [`DeviceError::Delay`] instead of running with a shortened delay.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoDelay;
+
+mod sealed {
+    pub trait DeviceDelay {}
+}
+
+/// Delay provider of a device: any [`DelayNs`] implementation, or [`NoDelay`]
+pub trait DeviceDelay: sealed::DeviceDelay {
+    /// `false` if the provider cannot delay
+    #[doc(hidden)]
+    const CAN_DELAY: bool;
+
+    /// Wait for `ns` nanoseconds
+    #[doc(hidden)]
+    fn wait_ns(&mut self, ns: u32);
+}
+
+impl<D: DelayNs> sealed::DeviceDelay for D {}
+
+impl<D: DelayNs> DeviceDelay for D {
+    const CAN_DELAY: bool = true;
+
+    fn wait_ns(&mut self, ns: u32) {
+        self.delay_ns(ns);
+    }
+}
+
+impl sealed::DeviceDelay for NoDelay {}
+
+impl DeviceDelay for NoDelay {
+    const CAN_DELAY: bool = false;
+
+    fn wait_ns(&mut self, _ns: u32) {}
+}
+
+/// Run `operations` with `cs` asserted
+fn run_transaction<Word, BUS, CS, D>(
+    bus: &mut BUS,
+    cs: &mut CS,
+    delay: &mut D,
+    setup_ns: u32,
+    hold_ns: u32,
+    operations: &mut [Operation<'_, Word>],
+) -> Result<(), DeviceError<BUS::Error, CS::Error>>
+where
+    Word: Copy + 'static,
+    BUS: SpiBus<Word>,
+    CS: OutputPin,
+    D: DeviceDelay,
+{
+    if !D::CAN_DELAY
+        && operations
+            .iter()
+            .any(|operation| matches!(operation, Operation::DelayNs(ns) if *ns != 0))
+    {
+        return Err(DeviceError::Delay);
+    }
+
+    cs.set_low().map_err(DeviceError::Cs)?;
+    if setup_ns != 0 {
+        delay.wait_ns(setup_ns);
+    }
+
+    let result = operations
+        .iter_mut()
+        .try_for_each(|operation| match operation {
+            Operation::Read(words) => bus.read(words),
+            Operation::Write(words) => bus.write(words),
+            Operation::Transfer(read, write) => bus.transfer(read, write),
+            Operation::TransferInPlace(words) => bus.transfer_in_place(words),
+            Operation::DelayNs(ns) => {
+                bus.flush()?;
+                delay.wait_ns(*ns);
+                Ok(())
+            }
+        });
+
+    // Deassert CS even if the transfer failed, once the bus is idle
+    let flushed = bus.flush();
+    if hold_ns != 0 {
+        delay.wait_ns(hold_ns);
+    }
+    let deasserted = cs.set_high();
+
+    result.map_err(DeviceError::Spi)?;
+    flushed.map_err(DeviceError::Spi)?;
+    deasserted.map_err(DeviceError::Cs)
+}
+
+/// Device with a GPIO chip select that owns its SPI bus
+pub struct ExclusiveCsDevice<BUS, CS, D> {
+    bus: BUS,
+    cs: CS,
+    delay: D,
+    setup_ns: u32,
+    hold_ns: u32,
+}
+
+impl<BUS, CS: OutputPin, D> ExclusiveCsDevice<BUS, CS, D> {
+    /// Create a device on `bus` selected by `cs`
+    ///
+    /// Deasserts (drives high) `cs`.
+    pub fn new(bus: BUS, mut cs: CS, delay: D) -> Result<Self, CS::Error> {
+        cs.set_high()?;
+        Ok(Self {
+            bus,
+            cs,
+            delay,
+            setup_ns: 0,
+            hold_ns: 0,
+        })
+    }
+
+    /// Borrow the underlying bus
+    pub fn bus(&self) -> &BUS {
+        &self.bus
+    }
+
+    /// Mutably borrow the underlying bus
+    ///
+    /// Bus operations issued through it run without CS asserted.
+    pub fn bus_mut(&mut self) -> &mut BUS {
+        &mut self.bus
+    }
+
+    /// Release the bus, the CS pin and the delay provider
+    pub fn free(self) -> (BUS, CS, D) {
+        (self.bus, self.cs, self.delay)
+    }
+}
+
+impl<BUS, CS, D: DelayNs> ExclusiveCsDevice<BUS, CS, D> {
+    /// Set the delay between asserting CS and the first clock edge
+    pub fn cs_setup_ns(mut self, ns: u32) -> Self {
+        self.setup_ns = ns;
+        self
+    }
+
+    /// Set the delay between the last clock edge and deasserting CS
+    pub fn cs_hold_ns(mut self, ns: u32) -> Self {
+        self.hold_ns = ns;
+        self
+    }
+}
+
+impl<BUS: ErrorType, CS: OutputPin, D> ErrorType for ExclusiveCsDevice<BUS, CS, D> {
+    type Error = DeviceError<BUS::Error, CS::Error>;
+}
+
+impl<Word, BUS, CS, D> SpiDevice<Word> for ExclusiveCsDevice<BUS, CS, D>
+where
+    Word: Copy + 'static,
+    BUS: SpiBus<Word>,
+    CS: OutputPin,
+    D: DeviceDelay,
+{
+    fn transaction(&mut self, operations: &mut [Operation<'_, Word>]) -> Result<(), Self::Error> {
+        run_transaction(
+            &mut self.bus,
+            &mut self.cs,
+            &mut self.delay,
+            self.setup_ns,
+            self.hold_ns,
+            operations,
+        )
+    }
+}
+
+/// Device with a GPIO chip select on a bus shared through a critical-section
+/// mutex
+///
+/// Each transaction runs in a critical section, so devices on the same bus can
+/// be used from different interrupt priorities.
+pub struct CriticalSectionCsDevice<'a, BUS, CS, D> {
+    bus: &'a Mutex<RefCell<BUS>>,
+    cs: CS,
+    delay: D,
+    setup_ns: u32,
+    hold_ns: u32,
+}
+
+impl<'a, BUS, CS: OutputPin, D> CriticalSectionCsDevice<'a, BUS, CS, D> {
+    /// Create a device on the shared `bus` selected by `cs`
+    ///
+    /// Deasserts (drives high) `cs`.
+    pub fn new(bus: &'a Mutex<RefCell<BUS>>, mut cs: CS, delay: D) -> Result<Self, CS::Error> {
+        cs.set_high()?;
+        Ok(Self {
+            bus,
+            cs,
+            delay,
+            setup_ns: 0,
+            hold_ns: 0,
+        })
+    }
+
+    /// Release the CS pin and the delay provider
+    pub fn free(self) -> (CS, D) {
+        (self.cs, self.delay)
+    }
+}
+
+impl<BUS, CS, D: DelayNs> CriticalSectionCsDevice<'_, BUS, CS, D> {
+    /// Set the delay between asserting CS and the first clock edge
+    pub fn cs_setup_ns(mut self, ns: u32) -> Self {
+        self.setup_ns = ns;
+        self
+    }
+
+    /// Set the delay between the last clock edge and deasserting CS
+    pub fn cs_hold_ns(mut self, ns: u32) -> Self {
+        self.hold_ns = ns;
+        self
+    }
+}
+
+impl<BUS: ErrorType, CS: OutputPin, D> ErrorType for CriticalSectionCsDevice<'_, BUS, CS, D> {
+    type Error = DeviceError<BUS::Error, CS::Error>;
+}
+
+impl<Word, BUS, CS, D> SpiDevice<Word> for CriticalSectionCsDevice<'_, BUS, CS, D>
+where
+    Word: Copy + 'static,
+    BUS: SpiBus<Word>,
+    CS: OutputPin,
+    D: DeviceDelay,
+{
+    fn transaction(&mut self, operations: &mut [Operation<'_, Word>]) -> Result<(), Self::Error> {
+        critical_section::with(|token| {
+            let bus = &mut *self.bus.borrow_ref_mut(token);
+            run_transaction(
+                bus,
+                &mut self.cs,
+                &mut self.delay,
+                self.setup_ns,
+                self.hold_ns,
+                operations,
+            )
+        })
+    }
+}
+
+/// Device with a GPIO chip select on a bus shared through a `RefCell`
+///
+/// For devices used from one execution context only: a transaction started
+/// while another one holds the bus panics.
+pub struct RefCellCsDevice<'a, BUS, CS, D> {
+    bus: &'a RefCell<BUS>,
+    cs: CS,
+    delay: D,
+    setup_ns: u32,
+    hold_ns: u32,
+}
+
+impl<'a, BUS, CS: OutputPin, D> RefCellCsDevice<'a, BUS, CS, D> {
+    /// Create a device on the shared `bus` selected by `cs`
+    ///
+    /// Deasserts (drives high) `cs`.
+    pub fn new(bus: &'a RefCell<BUS>, mut cs: CS, delay: D) -> Result<Self, CS::Error> {
+        cs.set_high()?;
+        Ok(Self {
+            bus,
+            cs,
+            delay,
+            setup_ns: 0,
+            hold_ns: 0,
+        })
+    }
+
+    /// Release the CS pin and the delay provider
+    pub fn free(self) -> (CS, D) {
+        (self.cs, self.delay)
+    }
+}
+
+impl<BUS, CS, D: DelayNs> RefCellCsDevice<'_, BUS, CS, D> {
+    /// Set the delay between asserting CS and the first clock edge
+    pub fn cs_setup_ns(mut self, ns: u32) -> Self {
+        self.setup_ns = ns;
+        self
+    }
+
+    /// Set the delay between the last clock edge and deasserting CS
+    pub fn cs_hold_ns(mut self, ns: u32) -> Self {
+        self.hold_ns = ns;
+        self
+    }
+}
+
+impl<BUS: ErrorType, CS: OutputPin, D> ErrorType for RefCellCsDevice<'_, BUS, CS, D> {
+    type Error = DeviceError<BUS::Error, CS::Error>;
+}
+
+impl<Word, BUS, CS, D> SpiDevice<Word> for RefCellCsDevice<'_, BUS, CS, D>
+where
+    Word: Copy + 'static,
+    BUS: SpiBus<Word>,
+    CS: OutputPin,
+    D: DeviceDelay,
+{
+    fn transaction(&mut self, operations: &mut [Operation<'_, Word>]) -> Result<(), Self::Error> {
+        let bus = &mut *self.bus.borrow_mut();
+        run_transaction(
+            bus,
+            &mut self.cs,
+            &mut self.delay,
+            self.setup_ns,
+            self.hold_ns,
+            operations,
+        )
+    }
+}
+
+/// Frames written ahead of the received ones in a hardware CS transaction
+///
+/// Two keeps the transmitter busy without overflowing the USART0 receive
+/// buffer.
+const FRAMES_IN_FLIGHT: usize = 2;
+
+/// Number of frames exchanged by `operation`
+fn operation_len<Word>(operation: &Operation<'_, Word>) -> usize {
+    match operation {
+        Operation::Read(words) => words.len(),
+        Operation::Write(words) => words.len(),
+        Operation::Transfer(read, write) => read.len().max(write.len()),
+        Operation::TransferInPlace(words) => words.len(),
+        Operation::DelayNs(_) => 0,
+    }
+}
+
+/// Position of the next frame in a list of operations
+#[derive(Default)]
+struct Cursor {
+    operation: usize,
+    index: usize,
+}
+
+impl Cursor {
+    /// Return the position of the next frame and move past it
+    ///
+    /// Must only be called while frames remain.
+    fn next<Word>(&mut self, operations: &[Operation<'_, Word>]) -> (usize, usize) {
+        while self.index >= operation_len(&operations[self.operation]) {
+            self.operation += 1;
+            self.index = 0;
+        }
+        let position = (self.operation, self.index);
+        self.index += 1;
+        position
+    }
+}
+
+/// Exchange the frames of all `operations` as one continuous stream
+///
+/// The next frame is written while the previous one is shifted out, so the
+/// transmitter does not run dry between frames or operations and AUTOCS keeps
+/// CS asserted. Read operations send zeros, and transfers with a shorter write
+/// buffer pad it with zeros.
+fn stream<Word: Copy + Default>(
+    operations: &mut [Operation<'_, Word>],
+    mut tx_ready: impl FnMut() -> bool,
+    mut transmit: impl FnMut(Word),
+    mut rx_ready: impl FnMut() -> bool,
+    mut receive: impl FnMut() -> Word,
+) {
+    let total: usize = operations.iter().map(operation_len).sum();
+    let mut tx = Cursor::default();
+    let mut rx = Cursor::default();
+    let mut sent = 0;
+    let mut received = 0;
+
+    while received < total {
+        if sent < total && sent - received < FRAMES_IN_FLIGHT && tx_ready() {
+            let (operation, index) = tx.next(operations);
+            let word = match &operations[operation] {
+                Operation::Write(words) => words[index],
+                Operation::TransferInPlace(words) => words[index],
+                Operation::Transfer(_, write) => write.get(index).copied().unwrap_or_default(),
+                _ => Word::default(),
+            };
+            transmit(word);
+            sent += 1;
+        }
+
+        if received < sent && rx_ready() {
+            let word = receive();
+            let (operation, index) = rx.next(operations);
+            match &mut operations[operation] {
+                Operation::Read(words) | Operation::TransferInPlace(words) => words[index] = word,
+                Operation::Transfer(read, _) => {
+                    if let Some(slot) = read.get_mut(index) {
+                        *slot = word;
+                    }
+                }
+                _ => {}
+            }
+            received += 1;
+        }
+    }
+}
+
+macro_rules! impl_hardware_cs_device {
+    ($Word:ty, $check_word:expr) => {
+        impl<I: Instance> SpiDevice<$Word> for Spi<I> {
+            /// Run `operations` with the hardware chip select asserted
+            ///
+            /// Returns [`Error::InvalidConfig`] if the driver was created without
+            /// [`Config::with_hardware_cs`](super::Config::with_hardware_cs), if
+            /// `operations` contains a delay, or for `u8` words on frames wider
+            /// than 8 bits.
+            fn transaction(
+                &mut self,
+                operations: &mut [Operation<'_, $Word>],
+            ) -> Result<(), Self::Error> {
+                ($check_word)(&*self)?;
+                let regs = I::regs();
+                if !regs.autocs()
+                    || operations
+                        .iter()
+                        .any(|operation| matches!(operation, Operation::DelayNs(_)))
+                {
+                    return Err(Error::InvalidConfig);
+                }
+
+                let spi = &*self;
+                stream(
+                    operations,
+                    || regs.tx_ready(),
+                    |word| spi.write_frame(u16::from(word)),
+                    || regs.rx_ready(),
+                    || spi.read_frame() as $Word,
+                );
+
+                SpiBus::<$Word>::flush(self)
+            }
+        }
+    };
+}
+
+impl_hardware_cs_device!(u8, |spi: &Spi<I>| spi.check_byte_frames());
+impl_hardware_cs_device!(u16, |_: &Spi<I>| Ok(()));
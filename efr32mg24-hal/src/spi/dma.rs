@@ -0,0 +1,438 @@
+//! LDMA-driven SPI transfers
+//!
+//! [`SpiDma`] pairs an SPI driver with two LDMA channels. The RX channel is
+//! paced by the peripheral's receive request (USART0 RXDATAV, EUSART RXFL) and
+//! the TX channel by its transmit request (USART0 TXBL, EUSART TXFL), both
+//! routed through LDMAXBAR. The CPU only sets up one descriptor pair per 2048
+//! frames.
+//!
+//! Frames of up to 8 bits are moved as bytes (`SpiBus<u8>`), wider frames as
+//! halfwords (`SpiBus<u16>`). On USART0, 9-bit frames go through TXDATAX/RXDATAX
+//! and wider ones through TXDOUBLE/RXDOUBLE. Using the other word type returns
+//! [`Error::InvalidConfig`].
+//!
+//! [`SpiDma`] implements the embedded-hal `SpiBus` trait, blocking until each
+//! transfer completes, so it can be wrapped in the [`device`](super::device)
+//! types like any other bus. With the `async` feature it also implements the
+//! embedded-hal-async `SpiBus`: the task sleeps on the LDMA interrupt while the
+//! transfer runs in the background. The DMA controller must then be created with
+//! [`Dma::new_async`](crate::dma::Dma::new_async).
+//!
+//! Reads transmit zeros, and the frames received during writes are discarded, so
+//! both channels are busy in every transfer.
+
+use core::ptr::addr_of_mut;
+
+use embedded_hal::spi::{ErrorType, SpiBus};
+
+use super::{Error, Instance, Spi};
+use crate::dma::{Channel, Descriptor, Request, TransferSize, MAX_TRANSFER_UNITS};
+
+/// Transmitted by reads
+static ZERO: u16 = 0;
+
+/// Destination of the frames received during writes
+static mut DISCARD: u16 = 0;
+
+/// Word type of a DMA transfer
+trait DmaWord: Copy {
+    /// LDMA unit size
+    const SIZE: TransferSize;
+}
+
+impl DmaWord for u8 {
+    const SIZE: TransferSize = TransferSize::Byte;
+}
+
+impl DmaWord for u16 {
+    const SIZE: TransferSize = TransferSize::Halfword;
+}
+
+/// Check that `W` matches a frame size of `data_bits`
+fn check_word<W: DmaWord>(data_bits: u8) -> Result<(), Error> {
+    if (data_bits > 8) == (W::SIZE == TransferSize::Halfword) {
+        Ok(())
+    } else {
+        Err(Error::InvalidConfig)
+    }
+}
+
+/// SPI driver with LDMA channels `RX` and `TX`
+///
+/// Created with [`Spi::with_dma`].
+pub struct SpiDma<SPI, const RX: u8, const TX: u8> {
+    spi: SPI,
+    rx: Channel<RX>,
+    tx: Channel<TX>,
+}
+
+/// Data registers and LDMA requests of an SPI peripheral
+#[derive(Clone, Copy)]
+struct Endpoints {
+    rxdata: u32,
+    txdata: u32,
+    rx_request: Request,
+    tx_request: Request,
+}
+
+/// Memory side of one transfer direction: a buffer, or a single fixed word
+#[derive(Clone, Copy)]
+struct Stream {
+    address: u32,
+    increment: bool,
+}
+
+impl Stream {
+    /// Transmit `words`
+    fn source<W>(words: &[W]) -> Self {
+        Self {
+            address: words.as_ptr() as u32,
+            increment: true,
+        }
+    }
+
+    /// Receive into `words`
+    fn sink<W>(words: &mut [W]) -> Self {
+        Self {
+            address: words.as_mut_ptr() as u32,
+            increment: true,
+        }
+    }
+
+    /// Transmit 0x00
+    fn zeros() -> Self {
+        Self {
+            address: &ZERO as *const u16 as u32,
+            increment: false,
+        }
+    }
+
+    /// Discard received frames
+    fn discard() -> Self {
+        Self {
+            address: addr_of_mut!(DISCARD) as u32,
+            increment: false,
+        }
+    }
+
+    /// Address of the `size` word at `offset`
+    fn at(self, offset: usize, size: TransferSize) -> u32 {
+        if self.increment {
+            self.address + ((offset as u32) << size as u32)
+        } else {
+            self.address
+        }
+    }
+}
+
+/// `len` words of `size` exchanged between `source` and `sink`
+#[derive(Clone, Copy)]
+struct Exchange {
+    source: Stream,
+    sink: Stream,
+    len: usize,
+    size: TransferSize,
+}
+
+impl Exchange {
+    /// The exchange of a read
+    fn read<W: DmaWord>(words: &mut [W]) -> Self {
+        Self {
+            source: Stream::zeros(),
+            sink: Stream::sink(words),
+            len: words.len(),
+            size: W::SIZE,
+        }
+    }
+
+    /// The exchange of a write
+    fn write<W: DmaWord>(words: &[W]) -> Self {
+        Self {
+            source: Stream::source(words),
+            sink: Stream::discard(),
+            len: words.len(),
+            size: W::SIZE,
+        }
+    }
+
+    /// The exchanges of a full-duplex transfer
+    ///
+    /// Clocks `max(read.len(), write.len())` words: missing write words are sent
+    /// as zeros and extra received words are discarded.
+    fn transfer<W: DmaWord>(read: &mut [W], write: &[W]) -> [Self; 2] {
+        let common = read.len().min(write.len());
+        let (read, read_tail) = read.split_at_mut(common);
+        let (write, write_tail) = write.split_at(common);
+
+        let tail = if write_tail.is_empty() {
+            Self::read(read_tail)
+        } else {
+            Self::write(write_tail)
+        };
+
+        [
+            Self {
+                source: Stream::source(write),
+                sink: Stream::sink(read),
+                len: common,
+                size: W::SIZE,
+            },
+            tail,
+        ]
+    }
+
+    /// The exchange of an in-place transfer
+    fn in_place<W: DmaWord>(words: &mut [W]) -> Self {
+        Self {
+            source: Stream::source(words),
+            sink: Stream::sink(words),
+            len: words.len(),
+            size: W::SIZE,
+        }
+    }
+}
+
+/// Start the chunk of `exchange` at `offset` on both channels
+///
+/// Returns the chunk length.
+///
+/// # Safety
+///
+/// The memory of `exchange` must stay valid until the RX channel reports done,
+/// or both channels are stopped.
+unsafe fn start_chunk<const RX: u8, const TX: u8>(
+    rx: &mut Channel<RX>,
+    tx: &mut Channel<TX>,
+    endpoints: Endpoints,
+    exchange: Exchange,
+    offset: usize,
+) -> usize {
+    let len = (exchange.len - offset).min(MAX_TRANSFER_UNITS);
+    let receive = Descriptor::peripheral(
+        exchange.size,
+        len,
+        endpoints.rxdata,
+        false,
+        exchange.sink.at(offset, exchange.size),
+        exchange.sink.increment,
+    );
+    let transmit = Descriptor::peripheral(
+        exchange.size,
+        len,
+        exchange.source.at(offset, exchange.size),
+        exchange.source.increment,
+        endpoints.txdata,
+        false,
+    );
+
+    // RX first, so no received frame is missed
+    // SAFETY: Forwarded to the caller.
+    unsafe {
+        rx.start(endpoints.rx_request, receive, false);
+        tx.start(endpoints.tx_request, transmit, false);
+    }
+    len
+}
+
+/// Run `exchange` (blocking)
+fn exchange_blocking<const RX: u8, const TX: u8>(
+    rx: &mut Channel<RX>,
+    tx: &mut Channel<TX>,
+    endpoints: Endpoints,
+    exchange: Exchange,
+) {
+    let mut offset = 0;
+    while offset < exchange.len {
+        // SAFETY: The chunk is complete before this function returns.
+        offset += unsafe { start_chunk(rx, tx, endpoints, exchange, offset) };
+
+        // The last frame is received after the last frame is sent
+        while !rx.is_done() {
+            core::hint::spin_loop();
+        }
+        rx.clear_done();
+        tx.clear_done();
+    }
+}
+
+/// Run `exchange`, sleeping on the LDMA interrupt until each chunk is done
+///
+/// If the future is dropped before completion, both channels are stopped.
+#[cfg(feature = "async")]
+async fn exchange_async<const RX: u8, const TX: u8>(
+    rx: &mut Channel<RX>,
+    tx: &mut Channel<TX>,
+    endpoints: Endpoints,
+    exchange: Exchange,
+) {
+    /// Stops both channels unless the exchange completed
+    struct StopOnDrop<'a, const RX: u8, const TX: u8> {
+        rx: &'a mut Channel<RX>,
+        tx: &'a mut Channel<TX>,
+        running: bool,
+    }
+
+    impl<const RX: u8, const TX: u8> Drop for StopOnDrop<'_, RX, TX> {
+        fn drop(&mut self) {
+            if self.running {
+                self.rx.stop();
+                self.tx.stop();
+            }
+            self.rx.clear_done();
+            self.tx.clear_done();
+        }
+    }
+
+    let mut guard = StopOnDrop {
+        rx,
+        tx,
+        running: false,
+    };
+    let mut offset = 0;
+    while offset < exchange.len {
+        // SAFETY: The chunk is complete, or the channels are stopped by the
+        // guard, before the borrowed buffers are released.
+        offset += unsafe { start_chunk(guard.rx, guard.tx, endpoints, exchange, offset) };
+        guard.running = true;
+
+        guard.rx.wait_done().await;
+        guard.running = false;
+        guard.rx.clear_done();
+        guard.tx.clear_done();
+    }
+}
+
+impl<I: Instance> Spi<I> {
+    /// Use LDMA channels `RX` and `TX` for transfers
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let mut dma = Dma::new(dp.ldma_s);
+    /// let mut spi = spi.with_dma(dma.channel0(), dma.channel1());
+    ///
+    /// let mut page = [0u8; 4096];
+    /// spi.transfer(&mut page, &[0x03, 0x00, 0x10, 0x00])?;
+    /// ```
+    pub fn with_dma<const RX: u8, const TX: u8>(
+        self,
+        rx: Channel<RX>,
+        tx: Channel<TX>,
+    ) -> SpiDma<Spi<I>, RX, TX> {
+        SpiDma { spi: self, rx, tx }
+    }
+}
+
+impl<I: Instance, const RX: u8, const TX: u8> SpiDma<Spi<I>, RX, TX> {
+    /// Release the SPI driver and the channels
+    pub fn free(self) -> (Spi<I>, Channel<RX>, Channel<TX>) {
+        (self.spi, self.rx, self.tx)
+    }
+
+    fn endpoints(&self) -> Endpoints {
+        let (rxdata, txdata) = I::regs().data_registers(self.spi.data_bits);
+        Endpoints {
+            rxdata,
+            txdata,
+            rx_request: I::RX_REQUEST,
+            tx_request: I::TX_REQUEST,
+        }
+    }
+
+    /// Run `exchange` (blocking)
+    fn exchange(&mut self, exchange: Exchange) {
+        let endpoints = self.endpoints();
+        exchange_blocking(&mut self.rx, &mut self.tx, endpoints, exchange);
+    }
+
+    /// Run `exchange`, sleeping until it completes
+    #[cfg(feature = "async")]
+    async fn exchange_async(&mut self, exchange: Exchange) {
+        let endpoints = self.endpoints();
+        exchange_async(&mut self.rx, &mut self.tx, endpoints, exchange).await;
+    }
+}
+
+impl<I: Instance, const RX: u8, const TX: u8> ErrorType for SpiDma<Spi<I>, RX, TX> {
+    type Error = Error;
+}
+
+macro_rules! impl_spi_dma_bus {
+    ($Word:ty) => {
+        impl<I: Instance, const RX: u8, const TX: u8> SpiBus<$Word> for SpiDma<Spi<I>, RX, TX> {
+            fn read(&mut self, words: &mut [$Word]) -> Result<(), Self::Error> {
+                check_word::<$Word>(self.spi.data_bits)?;
+                self.exchange(Exchange::read(words));
+                Ok(())
+            }
+
+            fn write(&mut self, words: &[$Word]) -> Result<(), Self::Error> {
+                check_word::<$Word>(self.spi.data_bits)?;
+                self.exchange(Exchange::write(words));
+                Ok(())
+            }
+
+            fn transfer(&mut self, read: &mut [$Word], write: &[$Word]) -> Result<(), Self::Error> {
+                check_word::<$Word>(self.spi.data_bits)?;
+                for exchange in Exchange::transfer(read, write) {
+                    self.exchange(exchange);
+                }
+                Ok(())
+            }
+
+            fn transfer_in_place(&mut self, words: &mut [$Word]) -> Result<(), Self::Error> {
+                check_word::<$Word>(self.spi.data_bits)?;
+                self.exchange(Exchange::in_place(words));
+                Ok(())
+            }
+
+            fn flush(&mut self) -> Result<(), Self::Error> {
+                SpiBus::<$Word>::flush(&mut self.spi)
+            }
+        }
+
+        #[cfg(feature = "async")]
+        impl<I: Instance, const RX: u8, const TX: u8> embedded_hal_async::spi::SpiBus<$Word>
+            for SpiDma<Spi<I>, RX, TX>
+        {
+            async fn read(&mut self, words: &mut [$Word]) -> Result<(), Self::Error> {
+                check_word::<$Word>(self.spi.data_bits)?;
+                self.exchange_async(Exchange::read(words)).await;
+                Ok(())
+            }
+
+            async fn write(&mut self, words: &[$Word]) -> Result<(), Self::Error> {
+                check_word::<$Word>(self.spi.data_bits)?;
+                self.exchange_async(Exchange::write(words)).await;
+                Ok(())
+            }
+
+            async fn transfer(
+                &mut self,
+                read: &mut [$Word],
+                write: &[$Word],
+            ) -> Result<(), Self::Error> {
+                check_word::<$Word>(self.spi.data_bits)?;
+                for exchange in Exchange::transfer(read, write) {
+                    self.exchange_async(exchange).await;
+                }
+                Ok(())
+            }
+
+            async fn transfer_in_place(&mut self, words: &mut [$Word]) -> Result<(), Self::Error> {
+                check_word::<$Word>(self.spi.data_bits)?;
+                self.exchange_async(Exchange::in_place(words)).await;
+                Ok(())
+            }
+
+            /// The last frame has been received when a transfer completes, so
+            /// the bus is already idle
+            async fn flush(&mut self) -> Result<(), Self::Error> {
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_spi_dma_bus!(u8);
+impl_spi_dma_bus!(u16);
@@ -0,0 +1,315 @@
+//! SPI peripheral instances
+//!
+//! [`Instance`] is implemented for the three peripherals that run SPI: USART0,
+//! EUSART0 and EUSART1. It ties each of them to its register block, CMU clock
+//! enable bit, interrupt vectors, LDMA requests and chip select route registers,
+//! so the master and slave drivers are written once for all three.
+//!
+//! USART0 and the EUSARTs have different register layouts. [`Regs`] hides the
+//! difference behind the few operations the drivers need; the match on the
+//! variant is resolved at compile time for a concrete instance.
+
+use crate::dma::Request;
+use crate::interrupt::typelevel;
+use crate::pac;
+
+/// Register block of an SPI peripheral
+#[derive(Clone, Copy)]
+pub enum Regs {
+    /// USART0
+    Usart(&'static pac::usart0_s::RegisterBlock),
+    /// EUSART0 or EUSART1
+    Eusart(&'static pac::eusart0_s::RegisterBlock),
+}
+
+impl Regs {
+    /// Prepare the peripheral for its configuration registers to be written
+    ///
+    /// USART0 registers only take writes while it is enabled, so EN is set.
+    /// The EUSART configuration registers (CFG0-CFG2, FRAMECFG, TIMINGCFG) only
+    /// take writes while it is disabled, so EN is cleared, in case the EUSART was
+    /// left enabled, and the disable is waited for.
+    pub(super) fn begin_config(self) {
+        match self {
+            Regs::Usart(usart) => {
+                usart.en().write(|w| w.en().set_bit());
+            }
+            Regs::Eusart(eusart) => {
+                eusart.en().write(|w| w.en().clear_bit());
+                while eusart.en().read().disabling().bit_is_set() {
+                    core::hint::spin_loop();
+                }
+            }
+        }
+    }
+
+    /// Panic unless frames of `data_bits` are supported
+    pub(super) fn check_data_bits(self, data_bits: u8) {
+        match self {
+            Regs::Usart(_) => assert!(
+                (4..=16).contains(&data_bits),
+                "USART0 SPI frames are 4 to 16 bits"
+            ),
+            Regs::Eusart(_) => assert!(
+                (7..=16).contains(&data_bits),
+                "EUSART SPI frames are 7 to 16 bits"
+            ),
+        }
+    }
+
+    /// Check whether the TX buffer has room
+    pub(super) fn tx_ready(self) -> bool {
+        match self {
+            Regs::Usart(usart) => usart.status().read().txbl().bit_is_set(),
+            Regs::Eusart(eusart) => eusart.status().read().txfl().bit_is_set(),
+        }
+    }
+
+    /// Check whether a received frame is available
+    pub(super) fn rx_ready(self) -> bool {
+        match self {
+            Regs::Usart(usart) => usart.status().read().rxdatav().bit_is_set(),
+            Regs::Eusart(eusart) => eusart.status().read().rxfl().bit_is_set(),
+        }
+    }
+
+    /// Check whether the last frame has left the shift register
+    pub(super) fn tx_complete(self) -> bool {
+        match self {
+            Regs::Usart(usart) => usart.status().read().txc().bit_is_set(),
+            Regs::Eusart(eusart) => eusart.status().read().txc().bit_is_set(),
+        }
+    }
+
+    /// Check whether the peripheral drives CS (AUTOCS)
+    pub(super) fn autocs(self) -> bool {
+        match self {
+            Regs::Usart(usart) => usart.ctrl().read().autocs().bit_is_set(),
+            Regs::Eusart(eusart) => eusart.cfg2().read().autocs().bit_is_set(),
+        }
+    }
+
+    /// Write a `data_bits` frame to the TX buffer
+    pub(super) fn write_frame(self, data_bits: u8, word: u16) {
+        match self {
+            Regs::Usart(usart) => match data_bits {
+                // Up to 8 bits fit TXDATA
+                ..=8 => {
+                    usart.txdata().write(|w| unsafe { w.bits(word as u32) });
+                }
+                // The 9th bit is only reachable through TXDATAX
+                9 => {
+                    usart.txdatax().write(|w| unsafe { w.txdatax().bits(word) });
+                }
+                // Larger frames occupy both buffer elements, low byte first
+                _ => {
+                    usart.txdouble().write(|w| unsafe { w.bits(word as u32) });
+                }
+            },
+            Regs::Eusart(eusart) => {
+                eusart.txdata().write(|w| unsafe { w.bits(word as u32) });
+            }
+        }
+    }
+
+    /// Read a `data_bits` frame from the RX buffer
+    pub(super) fn read_frame(self, data_bits: u8) -> u16 {
+        match self {
+            Regs::Usart(usart) => match data_bits {
+                ..=8 => usart.rxdata().read().bits() as u16,
+                9 => usart.rxdatax().read().rxdata().bits(),
+                _ => usart.rxdouble().read().bits() as u16,
+            },
+            Regs::Eusart(eusart) => eusart.rxdata().read().bits() as u16,
+        }
+    }
+
+    /// RX and TX data registers matching a frame size of `data_bits`, for the LDMA
+    pub(super) fn data_registers(self, data_bits: u8) -> (u32, u32) {
+        match self {
+            Regs::Usart(usart) => match data_bits {
+                ..=8 => (
+                    usart.rxdata().as_ptr() as u32,
+                    usart.txdata().as_ptr() as u32,
+                ),
+                9 => (
+                    usart.rxdatax().as_ptr() as u32,
+                    usart.txdatax().as_ptr() as u32,
+                ),
+                _ => (
+                    usart.rxdouble().as_ptr() as u32,
+                    usart.txdouble().as_ptr() as u32,
+                ),
+            },
+            Regs::Eusart(eusart) => (
+                eusart.rxdata().as_ptr() as u32,
+                eusart.txdata().as_ptr() as u32,
+            ),
+        }
+    }
+
+    /// Empty the TX and RX buffers
+    pub(super) fn clear_buffers(self, data_bits: u8) {
+        match self {
+            Regs::Usart(usart) => {
+                usart
+                    .cmd()
+                    .write(|w| w.cleartx().set_bit().clearrx().set_bit());
+            }
+            Regs::Eusart(eusart) => {
+                eusart.cmd().write(|w| w.cleartx().set_bit());
+                while eusart.status().read().cleartxbusy().bit_is_set() {}
+
+                // The RX FIFO has no clear command
+                while self.rx_ready() {
+                    self.read_frame(data_bits);
+                }
+            }
+        }
+    }
+
+    /// Pending interrupt flags (IF)
+    pub(super) fn flags(self) -> u32 {
+        match self {
+            Regs::Usart(usart) => usart.if_().read().bits(),
+            Regs::Eusart(eusart) => eusart.if_().read().bits(),
+        }
+    }
+
+    /// Clear the interrupt flags in `mask`
+    pub(super) fn clear_flags(self, mask: u32) {
+        match self {
+            Regs::Usart(usart) => crate::regs::clear_bits(usart.if_(), mask),
+            Regs::Eusart(eusart) => crate::regs::clear_bits(eusart.if_(), mask),
+        }
+    }
+
+    /// Enable the interrupts in `mask` (IEN)
+    pub(super) fn enable_interrupts(self, mask: u32) {
+        match self {
+            Regs::Usart(usart) => crate::regs::set_bits(usart.ien(), mask),
+            Regs::Eusart(eusart) => crate::regs::set_bits(eusart.ien(), mask),
+        }
+    }
+
+    /// Disable the interrupts in `mask` (IEN)
+    pub(super) fn disable_interrupts(self, mask: u32) {
+        match self {
+            Regs::Usart(usart) => crate::regs::clear_bits(usart.ien(), mask),
+            Regs::Eusart(eusart) => crate::regs::clear_bits(eusart.ien(), mask),
+        }
+    }
+}
+
+mod sealed {
+    use super::Regs;
+    use crate::dma::Request;
+    use crate::pac;
+
+    pub trait Instance {
+        /// LDMA request for a received frame (USART0 RXDATAV, EUSART RXFL)
+        const RX_REQUEST: Request;
+
+        /// LDMA request for room in the TX buffer (USART0 TXBL, EUSART TXFL)
+        const TX_REQUEST: Request;
+
+        /// Register block
+        fn regs() -> Regs;
+
+        /// Enable the peripheral clock in the CMU
+        fn enable_clock(cmu: &pac::CmuS);
+
+        /// Route the chip select (CSROUTE) to `port`/`pin`
+        fn route_cs(gpio: &pac::gpio_s::RegisterBlock, port: u8, pin: u8);
+
+        /// Enable the chip select output (ROUTEEN.CSPEN)
+        fn enable_cs_output(gpio: &pac::gpio_s::RegisterBlock);
+    }
+}
+
+/// A peripheral that can run SPI: USART0, EUSART0 or EUSART1
+pub trait Instance: sealed::Instance + 'static {
+    /// RX interrupt vector
+    type RxInterrupt: typelevel::Interrupt;
+
+    /// TX interrupt vector
+    type TxInterrupt: typelevel::Interrupt;
+}
+
+/// GPIO USART0/EUSARTn_ROUTEEN: CS output enable
+const ROUTEEN_CSPEN: u32 = 1 << 0;
+
+macro_rules! impl_instance {
+    (
+        $Periph:ident, $regs:expr, $clken:ident, $clk:ident,
+        $csroute:ident, $routeen:ident, $RxRequest:ident, $TxRequest:ident,
+        $RX:ident, $TX:ident
+    ) => {
+        impl sealed::Instance for pac::$Periph {
+            const RX_REQUEST: Request = Request::$RxRequest;
+            const TX_REQUEST: Request = Request::$TxRequest;
+
+            fn regs() -> Regs {
+                $regs
+            }
+
+            fn enable_clock(cmu: &pac::CmuS) {
+                cmu.$clken().modify(|_, w| w.$clk().set_bit());
+            }
+
+            fn route_cs(gpio: &pac::gpio_s::RegisterBlock, port: u8, pin: u8) {
+                gpio.$csroute()
+                    .write(|w| unsafe { w.port().bits(port).pin().bits(pin) });
+            }
+
+            fn enable_cs_output(gpio: &pac::gpio_s::RegisterBlock) {
+                crate::regs::set_bits(gpio.$routeen(), ROUTEEN_CSPEN);
+            }
+        }
+
+        impl Instance for pac::$Periph {
+            type RxInterrupt = typelevel::$RX;
+            type TxInterrupt = typelevel::$TX;
+        }
+    };
+}
+
+// SAFETY (all three): The register block is only accessed by the driver that
+// owns the peripheral.
+impl_instance!(
+    Usart0S,
+    Regs::Usart(unsafe { &*pac::Usart0S::ptr() }),
+    clken0,
+    usart0,
+    usart0_csroute,
+    usart0_routeen,
+    Usart0RxDataValid,
+    Usart0TxBufferLevel,
+    USART0_RX,
+    USART0_TX
+);
+impl_instance!(
+    Eusart0S,
+    Regs::Eusart(unsafe { &*pac::Eusart0S::ptr() }),
+    clken1,
+    eusart0,
+    eusart0_csroute,
+    eusart0_routeen,
+    Eusart0RxFifoLevel,
+    Eusart0TxFifoLevel,
+    EUSART0_RX,
+    EUSART0_TX
+);
+// EUSART1 has the same register layout as EUSART0.
+impl_instance!(
+    Eusart1S,
+    Regs::Eusart(unsafe { &*(pac::Eusart1S::ptr() as *const pac::eusart0_s::RegisterBlock) }),
+    clken1,
+    eusart1,
+    eusart1_csroute,
+    eusart1_routeen,
+    Eusart1RxFifoLevel,
+    Eusart1TxFifoLevel,
+    EUSART1_RX,
+    EUSART1_TX
+);
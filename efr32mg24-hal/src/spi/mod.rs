@@ -1,6 +1,6 @@
-//! SPI Master Mode Driver
+//! SPI Master and Slave Mode Drivers
 //!
-//! This module provides SPI (Serial Peripheral Interface) master mode functionality using
+//! This module provides SPI (Serial Peripheral Interface) master and slave mode functionality using
 //! USART peripherals in SPI mode for the EFR32MG24 microcontroller.
 //!
 //! # Hardware Registers
@@ -20,8 +20,19 @@
 //! - Configurable clock frequency
 //! - All 4 SPI modes (Mode 0-3)
 //! - MSB-first and LSB-first bit order
+//! - Frame sizes of 4 to 16 bits on USART0 and 7 to 16 bits on the EUSARTs
+//!   ([`Config::with_data_bits`]); frames above 8 bits use `u16` words
 //! - Blocking transfer operations
 //! - embedded-hal v1.0 SPI traits
+//! - embedded-hal-async `SpiBus` (`async` feature)
+//! - `SpiDevice` with GPIO chip selects on an exclusive or shared bus
+//!   ([`device`]), or with a hardware chip select ([`Config::with_hardware_cs`])
+//! - LDMA transfers, blocking or async, through [`SpiDma`]
+//! - Slave mode with an external SCLK and CS ([`SpiSlave0`], [`SpiSlave1`],
+//!   [`SpiSlave2`]): TX preload, interrupt or LDMA reception, CS-deasserted
+//!   event, overflow/underflow errors
+//! - One generic driver per role ([`Spi`], [`SpiSlave`]) over the sealed
+//!   [`Instance`] trait; `Spi0`-`Spi2` and `SpiSlave0`-`SpiSlave2` are aliases
 //!
 //! # Example
 //!
@@ -30,20 +41,21 @@
 //!     clock::{Clocks, ClockConfig, HfxoConfig},
 //!     spi::{Spi0, Config, Mode},
 //!     pac,
+//!     prelude::*,
 //! };
 //!
 //! let dp = pac::Peripherals::take().unwrap();
 //!
 //! // Configure clocks
 //! let clocks = Clocks::new(dp.cmu_s, ClockConfig {
-//!     hfxo: Some(HfxoConfig::new(39_000_000)),
+//!     hfxo: Some(HfxoConfig::new(39.MHz())),
 //!     lfxo: Some(Default::default()),
 //! }).freeze();
 //!
 //! // Create SPI instance (1 MHz, Mode 0)
 //! let mut spi = Spi0::new(
 //!     dp.usart0_s,
-//!     Config::new(Mode::Mode0, 1_000_000),
+//!     Config::new(Mode::Mode0, 1.MHz()),
 //!     &clocks
 //! );
 //!
@@ -53,77 +65,172 @@
 //! spi.transfer(&mut rx_data, &tx_data).unwrap();
 //! ```
 
+#[cfg(feature = "async")]
+mod asynch;
+pub mod device;
+mod dma;
+mod instance;
+mod slave;
 mod traits;
 mod types;
 
-pub use types::{BitOrder, Config, Error, Mode, Phase, Polarity};
+#[cfg(feature = "async")]
+pub use asynch::InterruptHandler;
+pub use device::{
+    CriticalSectionCsDevice, DeviceDelay, DeviceError, ExclusiveCsDevice, NoDelay, RefCellCsDevice,
+};
+pub use dma::SpiDma;
+pub use instance::Instance;
+pub use slave::{SpiSlave, SpiSlave0, SpiSlave1, SpiSlave2, SpiSlaveDma};
+pub use types::{
+    BitOrder, Config, Error, HardwareCs, Mode, Phase, Polarity, SlaveConfig, SlaveEvent, Word,
+};
 
 use crate::clock::FrozenClocks;
 use crate::pac;
+use instance::Regs;
+
+/// SPI master driver on USART0, EUSART0 or EUSART1
+///
+/// Use the [`Spi0`], [`Spi1`] and [`Spi2`] aliases to name a concrete driver.
+pub struct Spi<I: Instance> {
+    _periph: I,
+    /// Frame size in bits
+    data_bits: u8,
+}
 
 /// SPI0 using USART0 in SPI master mode
-pub struct Spi0 {
-    usart: pac::Usart0S,
-}
+pub type Spi0 = Spi<pac::Usart0S>;
 
 /// SPI1 using EUSART0 in SPI master mode
-pub struct Spi1 {
-    eusart: pac::Eusart0S,
-}
+pub type Spi1 = Spi<pac::Eusart0S>;
 
 /// SPI2 using EUSART1 in SPI master mode
-pub struct Spi2 {
-    eusart: pac::Eusart1S,
-}
+pub type Spi2 = Spi<pac::Eusart1S>;
 
-impl Spi0 {
-    /// Creates a new SPI0 instance using USART0
+impl<I: Instance> Spi<I> {
+    /// Creates a new SPI master instance
     ///
     /// # Arguments
     ///
-    /// * `usart` - USART0_S peripheral instance
+    /// * `periph` - USART0_S, EUSART0_S or EUSART1_S peripheral instance
     /// * `config` - SPI configuration (mode, frequency, bit order)
     /// * `clocks` - Frozen clock configuration
     ///
+    /// # Panics
+    ///
+    /// Panics if `config.data_bits` is out of range for the peripheral: 4 to 16
+    /// bits on USART0, 7 to 16 bits on the EUSARTs.
+    ///
     /// # Example
     ///
     /// ```no_run
-    /// let spi = Spi0::new(dp.usart0_s, Config::new(Mode::Mode0, 1_000_000), &clocks);
+    /// let spi = Spi0::new(dp.usart0_s, Config::new(Mode::Mode0, 1.MHz()), &clocks);
     /// ```
-    pub fn new(usart: pac::Usart0S, config: Config, clocks: &FrozenClocks) -> Self {
-        // Enable USART0 clock in CMU using safe accessor
-        clocks.enable_peripheral_clock(|cmu| {
-            cmu.clken0().modify(|_, w| w.usart0().set_bit());
-        });
-
-        // Enable USART peripheral
-        usart.en().write(|w| w.en().set_bit());
-
-        // Configure USART for SPI master mode
-        usart.ctrl().write(|w| {
-            w.sync()
-                .set_bit() // Synchronous mode (SPI)
-                .clkpol()
-                .bit(config.mode.polarity() == Polarity::IdleHigh)
-                .clkpha()
-                .bit(config.mode.phase() == Phase::CaptureOnSecondTransition)
-                .msbf()
-                .bit(config.bit_order == BitOrder::MsbFirst)
-        });
-
-        // Configure frame format
-        usart.frame().write(|w| unsafe {
-            w.databits().bits(0x5) // 8 data bits (value 0x5 = 8 bits)
-        });
-
-        // Calculate and set clock divider for SPI frequency
-        let clkdiv = Self::calculate_clkdiv(clocks.hfclk().into(), config.frequency);
-        usart.clkdiv().write(|w| unsafe { w.bits(clkdiv) });
-
-        // Enable TX and RX
-        usart.cmd().write(|w| w.txen().set_bit().rxen().set_bit());
-
-        Self { usart }
+    pub fn new(periph: I, config: Config, clocks: &FrozenClocks) -> Self {
+        let regs = I::regs();
+        regs.check_data_bits(config.data_bits);
+
+        // Enable the peripheral clock in CMU using safe accessor
+        clocks.enable_peripheral_clock(|cmu| I::enable_clock(cmu));
+
+        let clkdiv = Self::calculate_clkdiv(clocks.hfclk().to_Hz(), config.frequency.to_Hz());
+        regs.begin_config();
+        match regs {
+            Regs::Usart(usart) => {
+                // Configure USART for SPI master mode
+                usart.ctrl().write(|w| {
+                    w.sync()
+                        .set_bit() // Synchronous mode (SPI)
+                        .clkpol()
+                        .bit(config.mode.polarity() == Polarity::IdleHigh)
+                        .clkpha()
+                        .bit(config.mode.phase() == Phase::CaptureOnSecondTransition)
+                        .msbf()
+                        .bit(config.bit_order == BitOrder::MsbFirst)
+                        .autocs()
+                        .bit(config.hardware_cs.is_some())
+                });
+
+                if let Some(cs) = config.hardware_cs {
+                    // CSSETUP/CSHOLD encode 0, 1, 2, 3 or 7 bit times
+                    let delay = |bits: u8| match bits {
+                        0..=3 => bits,
+                        _ => 4,
+                    };
+                    usart.timing().modify(|_, w| {
+                        w.cssetup().set(delay(cs.setup_bits));
+                        w.cshold().set(delay(cs.hold_bits))
+                    });
+                }
+
+                // Configure frame format (DATABITS = bits - 3)
+                usart
+                    .frame()
+                    .write(|w| unsafe { w.databits().bits(config.data_bits - 3) });
+
+                usart.clkdiv().write(|w| unsafe { w.bits(clkdiv) });
+
+                // Enable TX and RX
+                usart.cmd().write(|w| w.txen().set_bit().rxen().set_bit());
+            }
+            Regs::Eusart(eusart) => {
+                // Configuration registers are written while the EUSART is disabled
+                eusart.cfg0().write(|w| {
+                    w.sync()
+                        .sync() // Synchronous mode (SPI)
+                        .msbf()
+                        .bit(config.bit_order == BitOrder::MsbFirst)
+                });
+
+                eusart.cfg2().write(|w| {
+                    w.clkpol()
+                        .bit(config.mode.polarity() == Polarity::IdleHigh)
+                        .clkpha()
+                        .bit(config.mode.phase() == Phase::CaptureOnSecondTransition)
+                        .master()
+                        .set_bit() // Master mode
+                        .autocs()
+                        .bit(config.hardware_cs.is_some())
+                });
+
+                if let Some(cs) = config.hardware_cs {
+                    // CSSETUP/CSHOLD count whole bit times, up to 7
+                    eusart.timingcfg().modify(|_, w| {
+                        w.cssetup().set(cs.setup_bits.min(7));
+                        w.cshold().set(cs.hold_bits.min(7))
+                    });
+                }
+
+                // Configure frame format (DATABITS = bits - 6)
+                eusart
+                    .framecfg()
+                    .write(|w| unsafe { w.databits().bits(config.data_bits - 6) });
+
+                // Enable EUSART peripheral
+                eusart.en().write(|w| w.en().set_bit());
+
+                eusart.clkdiv().write(|w| unsafe { w.bits(clkdiv) });
+
+                // Enable TX and RX
+                eusart.cmd().write(|w| w.txen().set_bit().rxen().set_bit());
+            }
+        }
+
+        // Route the hardware chip select
+        if let Some(cs) = config.hardware_cs {
+            // SAFETY: The CS route register belongs to the peripheral's owner,
+            // and ROUTEEN is updated through its SET/CLR aliases.
+            let gpio = unsafe { &*pac::GpioS::ptr() };
+            let (port, pin) = cs.cs;
+            I::route_cs(gpio, port, pin);
+            I::enable_cs_output(gpio);
+        }
+
+        Self {
+            _periph: periph,
+            data_bits: config.data_bits,
+        }
     }
 
     /// Calculate clock divider for desired SPI frequency
@@ -152,6 +259,7 @@ impl Spi0 {
     ///
     /// `Ok(())` on success, `Err(Error)` on failure
     pub fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Error> {
+        self.check_byte_frames()?;
         if read.len() != write.len() {
             return Err(Error::InvalidConfig);
         }
@@ -173,6 +281,7 @@ impl Spi0 {
     ///
     /// `Ok(())` on success, `Err(Error)` on failure
     pub fn write(&mut self, words: &[u8]) -> Result<(), Error> {
+        self.check_byte_frames()?;
         for &word in words {
             self.transfer_byte(word)?;
         }
@@ -189,278 +298,50 @@ impl Spi0 {
     ///
     /// `Ok(())` on success, `Err(Error)` on failure
     pub fn read(&mut self, words: &mut [u8]) -> Result<(), Error> {
+        self.check_byte_frames()?;
         for word in words.iter_mut() {
             *word = self.transfer_byte(0x00)?;
         }
         Ok(())
     }
 
-    /// Transfer a single byte (blocking)
-    fn transfer_byte(&mut self, byte: u8) -> Result<u8, Error> {
-        // Wait for TX buffer to be ready
-        while self.usart.status().read().txbl().bit_is_clear() {}
-
-        // Write byte to TX buffer
-        self.usart
-            .txdata()
-            .write(|w| unsafe { w.bits(byte as u32) });
-
-        // Wait for RX data to be valid
-        while self.usart.status().read().rxdatav().bit_is_clear() {}
-
-        // Read received byte
-        let received = self.usart.rxdata().read().bits() as u8;
-
-        Ok(received)
+    /// Frame size in bits
+    pub fn data_bits(&self) -> u8 {
+        self.data_bits
     }
-}
 
-impl Spi1 {
-    /// Creates a new SPI1 instance using EUSART0
-    ///
-    /// # Arguments
-    ///
-    /// * `eusart` - EUSART0_S peripheral instance
-    /// * `config` - SPI configuration (mode, frequency, bit order)
-    /// * `clocks` - Frozen clock configuration
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// let spi = Spi1::new(dp.eusart0_s, Config::new(Mode::Mode0, 1_000_000), &clocks);
-    /// ```
-    pub fn new(eusart: pac::Eusart0S, config: Config, clocks: &FrozenClocks) -> Self {
-        // Enable EUSART0 clock in CMU using safe accessor
-        clocks.enable_peripheral_clock(|cmu| {
-            cmu.clken1().modify(|_, w| w.eusart0().set_bit());
-        });
-
-        // Enable EUSART peripheral
-        eusart.en().write(|w| w.en().set_bit());
-
-        // Configure EUSART for SPI master mode
-        eusart.cfg0().write(|w| {
-            w.sync()
-                .sync() // Synchronous mode (SPI)
-                .msbf()
-                .bit(config.bit_order == BitOrder::MsbFirst)
-        });
-
-        eusart.cfg2().write(|w| {
-            w.clkpol()
-                .bit(config.mode.polarity() == Polarity::IdleHigh)
-                .clkpha()
-                .bit(config.mode.phase() == Phase::CaptureOnSecondTransition)
-                .master()
-                .set_bit() // Master mode
-        });
-
-        // Calculate and set clock divider
-        let clkdiv = Self::calculate_clkdiv(clocks.hfclk().into(), config.frequency);
-        eusart.clkdiv().write(|w| unsafe { w.bits(clkdiv) });
-
-        // Enable TX and RX
-        eusart.cmd().write(|w| w.txen().set_bit().rxen().set_bit());
-
-        Self { eusart }
-    }
-
-    /// Calculate clock divider for desired SPI frequency
-    fn calculate_clkdiv(pclk_hz: u32, spi_hz: u32) -> u32 {
-        let divider = (pclk_hz / (2 * spi_hz)).saturating_sub(1);
-        (divider * 256).min(0x1FFFFF)
-    }
-
-    /// Transfer data (full-duplex)
-    ///
-    /// # Arguments
-    ///
-    /// * `read` - Buffer to store received data
-    /// * `write` - Data to transmit
-    ///
-    /// # Returns
-    ///
-    /// `Ok(())` on success, `Err(Error)` on failure
-    pub fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Error> {
-        if read.len() != write.len() {
-            return Err(Error::InvalidConfig);
-        }
-
-        for (rx, tx) in read.iter_mut().zip(write.iter()) {
-            *rx = self.transfer_byte(*tx)?;
-        }
-
-        Ok(())
-    }
-
-    /// Write data (half-duplex)
-    ///
-    /// # Arguments
-    ///
-    /// * `words` - Data to write
-    ///
-    /// # Returns
-    ///
-    /// `Ok(())` on success, `Err(Error)` on failure
-    pub fn write(&mut self, words: &[u8]) -> Result<(), Error> {
-        for &word in words {
-            self.transfer_byte(word)?;
+    /// Reject `u8` transfers when frames are larger than 8 bits
+    fn check_byte_frames(&self) -> Result<(), Error> {
+        if self.data_bits > 8 {
+            Err(Error::InvalidConfig)
+        } else {
+            Ok(())
         }
-        Ok(())
-    }
-
-    /// Read data (half-duplex)
-    ///
-    /// # Arguments
-    ///
-    /// * `words` - Buffer to store read data
-    ///
-    /// # Returns
-    ///
-    /// `Ok(())` on success, `Err(Error)` on failure
-    pub fn read(&mut self, words: &mut [u8]) -> Result<(), Error> {
-        for word in words.iter_mut() {
-            *word = self.transfer_byte(0x00)?;
-        }
-        Ok(())
     }
 
     /// Transfer a single byte (blocking)
     fn transfer_byte(&mut self, byte: u8) -> Result<u8, Error> {
-        // Wait for TX buffer to be ready (TXFL > 0 means space available)
-        while self.eusart.status().read().txfl().bit_is_clear() {}
-
-        // Write byte to TX buffer
-        self.eusart
-            .txdata()
-            .write(|w| unsafe { w.bits(byte as u32) });
-
-        // Wait for RX data to be valid (RXFL > 0 means data available)
-        while self.eusart.status().read().rxfl().bit_is_clear() {}
-
-        // Read received byte
-        let received = self.eusart.rxdata().read().bits() as u8;
-
-        Ok(received)
-    }
-}
-
-impl Spi2 {
-    /// Creates a new SPI2 instance using EUSART1
-    ///
-    /// # Arguments
-    ///
-    /// * `eusart` - EUSART1_S peripheral instance
-    /// * `config` - SPI configuration (mode, frequency, bit order)
-    /// * `clocks` - Frozen clock configuration
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// let spi = Spi2::new(dp.eusart1_s, Config::new(Mode::Mode0, 1_000_000), &clocks);
-    /// ```
-    pub fn new(eusart: pac::Eusart1S, config: Config, clocks: &FrozenClocks) -> Self {
-        // Enable EUSART1 clock in CMU using safe accessor
-        clocks.enable_peripheral_clock(|cmu| {
-            cmu.clken1().modify(|_, w| w.eusart1().set_bit());
-        });
-
-        // Enable EUSART peripheral
-        eusart.en().write(|w| w.en().set_bit());
-
-        // Configure EUSART for SPI master mode
-        eusart.cfg0().write(|w| {
-            w.sync()
-                .sync()
-                .msbf()
-                .bit(config.bit_order == BitOrder::MsbFirst)
-        });
-
-        eusart.cfg2().write(|w| {
-            w.clkpol()
-                .bit(config.mode.polarity() == Polarity::IdleHigh)
-                .clkpha()
-                .bit(config.mode.phase() == Phase::CaptureOnSecondTransition)
-                .master()
-                .set_bit()
-        });
-
-        // Calculate and set clock divider
-        let clkdiv = Self::calculate_clkdiv(clocks.hfclk().into(), config.frequency);
-        eusart.clkdiv().write(|w| unsafe { w.bits(clkdiv) });
-
-        // Enable TX and RX
-        eusart.cmd().write(|w| w.txen().set_bit().rxen().set_bit());
-
-        Self { eusart }
+        self.transfer_word(byte as u16).map(|word| word as u8)
     }
 
-    fn calculate_clkdiv(pclk_hz: u32, spi_hz: u32) -> u32 {
-        let divider = (pclk_hz / (2 * spi_hz)).saturating_sub(1);
-        (divider * 256).min(0x1FFFFF)
-    }
-
-    /// Transfer data (full-duplex)
-    ///
-    /// # Arguments
-    ///
-    /// * `read` - Buffer to store received data
-    /// * `write` - Data to transmit
-    ///
-    /// # Returns
-    ///
-    /// `Ok(())` on success, `Err(Error)` on failure
-    pub fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Error> {
-        if read.len() != write.len() {
-            return Err(Error::InvalidConfig);
-        }
-
-        for (rx, tx) in read.iter_mut().zip(write.iter()) {
-            *rx = self.transfer_byte(*tx)?;
-        }
+    /// Transfer a single frame (blocking)
+    fn transfer_word(&mut self, word: u16) -> Result<u16, Error> {
+        // Wait for room in the TX buffer
+        while !I::regs().tx_ready() {}
+        self.write_frame(word);
 
-        Ok(())
-    }
-
-    /// Write data (half-duplex)
-    ///
-    /// # Arguments
-    ///
-    /// * `words` - Data to write
-    ///
-    /// # Returns
-    ///
-    /// `Ok(())` on success, `Err(Error)` on failure
-    pub fn write(&mut self, words: &[u8]) -> Result<(), Error> {
-        for &word in words {
-            self.transfer_byte(word)?;
-        }
-        Ok(())
+        // Wait for the received frame
+        while !I::regs().rx_ready() {}
+        Ok(self.read_frame())
     }
 
-    /// Read data (half-duplex)
-    ///
-    /// # Arguments
-    ///
-    /// * `words` - Buffer to store read data
-    ///
-    /// # Returns
-    ///
-    /// `Ok(())` on success, `Err(Error)` on failure
-    pub fn read(&mut self, words: &mut [u8]) -> Result<(), Error> {
-        for word in words.iter_mut() {
-            *word = self.transfer_byte(0x00)?;
-        }
-        Ok(())
+    /// Write a frame to the TX buffer
+    fn write_frame(&self, word: u16) {
+        I::regs().write_frame(self.data_bits, word);
     }
 
-    fn transfer_byte(&mut self, byte: u8) -> Result<u8, Error> {
-        while self.eusart.status().read().txfl().bit_is_clear() {}
-        self.eusart
-            .txdata()
-            .write(|w| unsafe { w.bits(byte as u32) });
-        while self.eusart.status().read().rxfl().bit_is_clear() {}
-        let received = self.eusart.rxdata().read().bits() as u8;
-        Ok(received)
+    /// Read a frame from the RX buffer
+    fn read_frame(&self) -> u16 {
+        I::regs().read_frame(self.data_bits)
     }
 }
@@ -0,0 +1,582 @@
+//! SPI slave (secondary) mode
+//!
+//! [`SpiSlave`], with the aliases [`SpiSlave0`] (USART0), [`SpiSlave1`]
+//! (EUSART0) and [`SpiSlave2`] (EUSART1), shifts frames on the SCLK and CS of
+//! an external SPI master, e.g. a host MCU using this device as a
+//! co-processor. The CS input is routed through
+//! `USART0_CSROUTE`/`EUSARTn_CSROUTE`; SCLK, MOSI and MISO are routed
+//! separately, like for the master drivers.
+//!
+//! # Transactions
+//!
+//! The master starts a transaction by asserting CS, so the reply must be queued
+//! in the TX buffer (2 frames on USART0, a 16-frame FIFO on the EUSARTs) before
+//! that. The transaction ends when the master deasserts CS, which is detected as
+//! a rising edge on the EXTI line of the CS pin (the line with the pin's number,
+//! so it cannot be shared with an `ExtiInput` on another port).
+//!
+//! - [`transaction`](SpiSlave::transaction) queues the reply, stores the
+//!   received frames and returns when CS is deasserted (blocking).
+//! - [`write_frame`](SpiSlave::write_frame) and
+//!   [`read_frame`](SpiSlave::read_frame) are non-blocking, for interrupt
+//!   handlers enabled with [`listen`](SpiSlave::listen).
+//! - [`with_dma`](SpiSlave::with_dma) moves both directions with the LDMA
+//!   ([`SpiSlaveDma`]), blocking or async.
+//!
+//! # Errors
+//!
+//! A frame received while the RX buffer is full is lost and reported as
+//! [`Error::Overrun`]. A frame clocked by the master while the TX buffer is
+//! empty is reported as [`Error::Underflow`]; the master then reads undefined
+//! data. Both are latched in IF and returned by
+//! [`take_errors`](SpiSlave::take_errors) and at the end of a transaction.
+
+use super::instance::Regs;
+use super::{BitOrder, Error, Instance, Phase, Polarity, SlaveConfig, SlaveEvent, Word};
+use crate::clock::FrozenClocks;
+use crate::dma::{Channel, Descriptor, TransferSize, MAX_TRANSFER_UNITS};
+use crate::gpio::{Input, Pin};
+use crate::pac;
+use crate::regs;
+
+#[cfg(feature = "async")]
+use crate::asynch::{self, wait_for};
+#[cfg(feature = "async")]
+use crate::gpio::InterruptHandler;
+#[cfg(feature = "async")]
+use crate::interrupt::{
+    self,
+    typelevel::{self, Binding},
+};
+
+/// USART/EUSART IF bit positions (identical on both peripherals)
+const IF_TX_READY: u32 = 1 << 1;
+const IF_RX_READY: u32 = 1 << 2;
+const IF_RXOF: u32 = 1 << 4;
+const IF_TXUF: u32 = 1 << 7;
+
+/// Check that `W` can hold frames of `data_bits`
+fn check_word<W: Word>(data_bits: u8) -> Result<(), Error> {
+    if data_bits > 8 && !W::WIDE {
+        Err(Error::InvalidConfig)
+    } else {
+        Ok(())
+    }
+}
+
+/// Access the GPIO register block
+fn gpio() -> &'static pac::gpio_s::RegisterBlock {
+    // SAFETY: Only the CS pin's EXTI bits are modified, through the SET/CLR
+    // aliases, and the CS route register belongs to the slave's peripheral.
+    unsafe { &*pac::GpioS::ptr() }
+}
+
+/// Detect rising edges of `cs` on its EXTI line
+///
+/// Returns the port and pin numbers of `cs`.
+fn watch_cs<const PORT: char, const PIN: u8, PULL>(cs: Pin<PORT, PIN, Input<PULL>>) -> (u8, u8) {
+    cs.select_exti_line();
+    let line = 1 << PIN;
+    regs::clear_bits(gpio().extifall(), line);
+    regs::set_bits(gpio().extirise(), line);
+    regs::clear_bits(gpio().if_(), line);
+    cs.route_location()
+}
+
+/// LDMA unit size of `W`
+fn transfer_size<W: Word>() -> TransferSize {
+    if W::WIDE {
+        TransferSize::Halfword
+    } else {
+        TransferSize::Byte
+    }
+}
+
+/// SPI slave driver on USART0, EUSART0 or EUSART1
+///
+/// Use the [`SpiSlave0`], [`SpiSlave1`] and [`SpiSlave2`] aliases to name a
+/// concrete driver.
+pub struct SpiSlave<I: Instance> {
+    periph: I,
+    /// Frame size in bits
+    data_bits: u8,
+    /// CS (port, pin)
+    cs: (u8, u8),
+}
+
+/// SPI slave using USART0
+pub type SpiSlave0 = SpiSlave<pac::Usart0S>;
+
+/// SPI slave using EUSART0
+pub type SpiSlave1 = SpiSlave<pac::Eusart0S>;
+
+/// SPI slave using EUSART1
+pub type SpiSlave2 = SpiSlave<pac::Eusart1S>;
+
+/// SPI slave with LDMA channels `RX` and `TX`
+///
+/// Created with [`SpiSlave::with_dma`].
+pub struct SpiSlaveDma<SLAVE, const RX: u8, const TX: u8> {
+    slave: SLAVE,
+    rx: Channel<RX>,
+    tx: Channel<TX>,
+}
+
+impl<I: Instance> SpiSlave<I> {
+    /// Creates a new SPI slave, selected by the master through `cs`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `config.data_bits` is out of range for the peripheral: 4 to 16
+    /// bits on USART0, 7 to 16 bits on the EUSARTs.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let cs = gpio.portc.pc3.into_pull_up_input();
+    /// let slave = SpiSlave0::new(dp.usart0_s, cs, SlaveConfig::new(Mode::Mode0), &clocks);
+    /// ```
+    pub fn new<const PORT: char, const PIN: u8, PULL>(
+        periph: I,
+        cs: Pin<PORT, PIN, Input<PULL>>,
+        config: SlaveConfig,
+        clocks: &FrozenClocks,
+    ) -> Self {
+        let regs = I::regs();
+        regs.check_data_bits(config.data_bits);
+
+        clocks.enable_peripheral_clock(|cmu| I::enable_clock(cmu));
+        let (port, pin) = watch_cs(cs);
+
+        regs.begin_config();
+        match regs {
+            Regs::Usart(usart) => {
+                // Synchronous mode; the master provides SCLK and CS
+                usart.ctrl().write(|w| {
+                    w.sync()
+                        .set_bit()
+                        .clkpol()
+                        .bit(config.mode.polarity() == Polarity::IdleHigh)
+                        .clkpha()
+                        .bit(config.mode.phase() == Phase::CaptureOnSecondTransition)
+                        .msbf()
+                        .bit(config.bit_order == BitOrder::MsbFirst)
+                });
+                usart
+                    .frame()
+                    .write(|w| unsafe { w.databits().bits(config.data_bits - 3) });
+
+                I::route_cs(gpio(), port, pin);
+
+                usart.cmd().write(|w| {
+                    w.masterdis()
+                        .set_bit()
+                        .clearrx()
+                        .set_bit()
+                        .cleartx()
+                        .set_bit()
+                        .txen()
+                        .set_bit()
+                        .rxen()
+                        .set_bit()
+                });
+            }
+            Regs::Eusart(eusart) => {
+                // Configuration registers are written while the EUSART is disabled
+                eusart.cfg0().write(|w| {
+                    w.sync()
+                        .sync()
+                        .msbf()
+                        .bit(config.bit_order == BitOrder::MsbFirst)
+                });
+                eusart.cfg2().write(|w| {
+                    w.clkpol()
+                        .bit(config.mode.polarity() == Polarity::IdleHigh)
+                        .clkpha()
+                        .bit(config.mode.phase() == Phase::CaptureOnSecondTransition)
+                        .master()
+                        .clear_bit() // Secondary (slave) mode
+                });
+                eusart
+                    .framecfg()
+                    .write(|w| unsafe { w.databits().bits(config.data_bits - 6) });
+
+                I::route_cs(gpio(), port, pin);
+
+                eusart.en().write(|w| w.en().set_bit());
+                eusart.cmd().write(|w| w.txen().set_bit().rxen().set_bit());
+            }
+        }
+
+        Self {
+            periph,
+            data_bits: config.data_bits,
+            cs: (port, pin),
+        }
+    }
+
+    /// Write a frame to the TX buffer
+    fn put_frame(&self, word: u16) {
+        I::regs().write_frame(self.data_bits, word);
+    }
+
+    /// Read a frame from the RX buffer
+    fn get_frame(&self) -> u16 {
+        I::regs().read_frame(self.data_bits)
+    }
+
+    /// Frame size in bits
+    pub fn data_bits(&self) -> u8 {
+        self.data_bits
+    }
+
+    /// EXTI line bit of the CS pin
+    fn cs_line(&self) -> u32 {
+        1 << self.cs.1
+    }
+
+    /// Check whether the master deasserted CS since the flag was cleared
+    pub fn is_cs_deasserted(&self) -> bool {
+        gpio().if_().read().bits() & self.cs_line() != 0
+    }
+
+    /// Clear the CS-deasserted flag
+    pub fn clear_cs_deasserted(&mut self) {
+        regs::clear_bits(gpio().if_(), self.cs_line());
+    }
+
+    /// Queue a frame for the master (non-blocking)
+    ///
+    /// Returns `WouldBlock` while the TX buffer is full.
+    pub fn write_frame(&mut self, word: u16) -> nb::Result<(), Error> {
+        if !I::regs().tx_ready() {
+            return Err(nb::Error::WouldBlock);
+        }
+        self.put_frame(word);
+        Ok(())
+    }
+
+    /// Read a received frame (non-blocking)
+    ///
+    /// Returns `WouldBlock` while the RX buffer is empty.
+    pub fn read_frame(&mut self) -> nb::Result<u16, Error> {
+        if !I::regs().rx_ready() {
+            return Err(nb::Error::WouldBlock);
+        }
+        Ok(self.get_frame())
+    }
+
+    /// Return and clear a latched overflow or underflow
+    ///
+    /// An overflow is reported before an underflow if both occurred.
+    pub fn take_errors(&mut self) -> Result<(), Error> {
+        let flags = I::regs().flags() & (IF_RXOF | IF_TXUF);
+        I::regs().clear_flags(flags);
+        if flags & IF_RXOF != 0 {
+            Err(Error::Overrun)
+        } else if flags & IF_TXUF != 0 {
+            Err(Error::Underflow)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Enable the interrupt for `event`
+    ///
+    /// [`SlaveEvent::CsDeasserted`] raises GPIO_EVEN or GPIO_ODD,
+    /// depending on the CS pin number; the other events raise the
+    /// peripheral's RX or TX interrupt. Flags stay set until cleared with
+    /// [`clear_interrupt`](Self::clear_interrupt) (or, for errors,
+    /// [`take_errors`](Self::take_errors)).
+    pub fn listen(&mut self, event: SlaveEvent) {
+        match self.event_bit(event) {
+            (true, bit) => regs::set_bits(gpio().ien(), bit),
+            (false, bit) => I::regs().enable_interrupts(bit),
+        }
+    }
+
+    /// Disable the interrupt for `event`
+    pub fn unlisten(&mut self, event: SlaveEvent) {
+        match self.event_bit(event) {
+            (true, bit) => regs::clear_bits(gpio().ien(), bit),
+            (false, bit) => I::regs().disable_interrupts(bit),
+        }
+    }
+
+    /// Clear the interrupt flag of `event`
+    pub fn clear_interrupt(&mut self, event: SlaveEvent) {
+        match self.event_bit(event) {
+            (true, bit) => regs::clear_bits(gpio().if_(), bit),
+            (false, bit) => I::regs().clear_flags(bit),
+        }
+    }
+
+    /// IF/IEN bit of `event`, and whether it is in the GPIO peripheral
+    fn event_bit(&self, event: SlaveEvent) -> (bool, u32) {
+        match event {
+            SlaveEvent::RxReady => (false, IF_RX_READY),
+            SlaveEvent::TxReady => (false, IF_TX_READY),
+            SlaveEvent::Overflow => (false, IF_RXOF),
+            SlaveEvent::Underflow => (false, IF_TXUF),
+            SlaveEvent::CsDeasserted => (true, self.cs_line()),
+        }
+    }
+
+    /// Discard queued and received frames and clear the error and
+    /// CS-deasserted flags
+    ///
+    /// Call between transactions, while CS is deasserted.
+    pub fn reset(&mut self) {
+        I::regs().clear_buffers(self.data_bits);
+        I::regs().clear_flags(IF_RXOF | IF_TXUF);
+        self.clear_cs_deasserted();
+    }
+
+    /// Run one transaction with the master (blocking)
+    ///
+    /// Discards stale frames, queues `write` as the reply and returns once
+    /// the master deasserts CS, with the number of frames stored in
+    /// `read`. Call it before the master asserts CS, so the first frames
+    /// are queued in time.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidConfig`] for `u8` words on frames wider than 8
+    ///   bits
+    /// - [`Error::Overrun`] if the master sent more frames than `read`
+    ///   holds, or a frame was lost
+    /// - [`Error::Underflow`] if the master clocked more frames than
+    ///   `write` holds, or the reply was not queued in time
+    pub fn transaction<W: Word>(&mut self, read: &mut [W], write: &[W]) -> Result<usize, Error> {
+        check_word::<W>(self.data_bits)?;
+        self.reset();
+
+        let mut sent = 0;
+        let mut received = 0;
+        let mut overflow = false;
+        loop {
+            // Sampled first, so the frames received before the edge are
+            // read below
+            let deasserted = self.is_cs_deasserted();
+
+            while sent < write.len() && I::regs().tx_ready() {
+                self.put_frame(write[sent].into_frame());
+                sent += 1;
+            }
+
+            while I::regs().rx_ready() {
+                let frame = self.get_frame();
+                match read.get_mut(received) {
+                    Some(slot) => {
+                        *slot = W::from_frame(frame);
+                        received += 1;
+                    }
+                    None => overflow = true,
+                }
+            }
+
+            if deasserted {
+                break;
+            }
+        }
+
+        self.take_errors()?;
+        if overflow {
+            return Err(Error::Overrun);
+        }
+        Ok(received)
+    }
+
+    /// Use LDMA channels `RX` and `TX` for transactions
+    pub fn with_dma<const RX: u8, const TX: u8>(
+        self,
+        rx: Channel<RX>,
+        tx: Channel<TX>,
+    ) -> SpiSlaveDma<SpiSlave<I>, RX, TX> {
+        SpiSlaveDma {
+            slave: self,
+            rx,
+            tx,
+        }
+    }
+
+    /// Wait until the master deasserts CS
+    ///
+    /// Returns immediately if the CS-deasserted flag is already set; the
+    /// flag is left set. The slave must be created with `new_async`.
+    #[cfg(feature = "async")]
+    pub async fn wait_cs_deasserted(&mut self) {
+        let line = self.cs_line();
+        wait_for(
+            &asynch::GPIO_EXTI[self.cs.1 as usize],
+            || gpio().if_().read().bits() & line != 0,
+            || regs::set_bits(gpio().ien(), line),
+        )
+        .await
+    }
+
+    /// Release the peripheral
+    ///
+    /// Stops detecting CS edges on the pin's EXTI line.
+    pub fn free(self) -> I {
+        let line = self.cs_line();
+        regs::clear_bits(gpio().ien(), line);
+        regs::clear_bits(gpio().extirise(), line);
+        regs::clear_bits(gpio().if_(), line);
+        self.periph
+    }
+}
+
+impl<I: Instance, const RX: u8, const TX: u8> SpiSlaveDma<SpiSlave<I>, RX, TX> {
+    /// Release the slave driver and the channels
+    pub fn free(self) -> (SpiSlave<I>, Channel<RX>, Channel<TX>) {
+        (self.slave, self.rx, self.tx)
+    }
+
+    /// Reset the slave and start both channels on `read` and `write`
+    ///
+    /// # Safety
+    ///
+    /// `read` and `write` must stay valid until [`finish`](Self::finish)
+    /// stops the channels.
+    unsafe fn start<W: Word>(&mut self, read: &mut [W], write: &[W]) -> Result<(), Error> {
+        check_word::<W>(self.slave.data_bits)?;
+        if read.len() > MAX_TRANSFER_UNITS || write.len() > MAX_TRANSFER_UNITS {
+            return Err(Error::InvalidConfig);
+        }
+        self.slave.reset();
+
+        let size = transfer_size::<W>();
+        let (rxdata, txdata) = I::regs().data_registers(self.slave.data_bits);
+        // SAFETY: Forwarded to the caller.
+        unsafe {
+            if !read.is_empty() {
+                let receive = Descriptor::peripheral(
+                    size,
+                    read.len(),
+                    rxdata,
+                    false,
+                    read.as_mut_ptr() as u32,
+                    true,
+                );
+                self.rx.start(I::RX_REQUEST, receive, false);
+            }
+            if !write.is_empty() {
+                let transmit = Descriptor::peripheral(
+                    size,
+                    write.len(),
+                    write.as_ptr() as u32,
+                    true,
+                    txdata,
+                    false,
+                );
+                self.tx.start(I::TX_REQUEST, transmit, false);
+            }
+        }
+        Ok(())
+    }
+
+    /// Stop both channels once CS is deasserted, and count the frames
+    /// received into `read`
+    fn finish<W: Word>(&mut self, read: &[W]) -> Result<usize, Error> {
+        // Let the RX channel move the frames received before the edge
+        while !read.is_empty() && !self.rx.is_done() && I::regs().rx_ready() {
+            core::hint::spin_loop();
+        }
+
+        self.rx.stop();
+        self.tx.stop();
+        self.rx.clear_done();
+        self.tx.clear_done();
+
+        let received = if read.is_empty() {
+            0
+        } else {
+            let bytes = self.rx.destination() - read.as_ptr() as u32;
+            (bytes as usize / core::mem::size_of::<W>()).min(read.len())
+        };
+
+        self.slave.take_errors()?;
+        // Frames that did not fit in `read` are left in the RX buffer
+        if I::regs().rx_ready() {
+            return Err(Error::Overrun);
+        }
+        Ok(received)
+    }
+
+    /// Run one transaction with the master (blocking)
+    ///
+    /// Like the slave driver's `transaction`, with the LDMA moving
+    /// the frames. `read` and `write` hold at most 2048 frames each.
+    /// Returns [`Error::Overrun`] if the master sent more frames than
+    /// `read` holds.
+    pub fn transaction<W: Word>(&mut self, read: &mut [W], write: &[W]) -> Result<usize, Error> {
+        // SAFETY: `finish` stops both channels before the buffers are
+        // released.
+        unsafe { self.start(read, write)? };
+        while !self.slave.is_cs_deasserted() {
+            core::hint::spin_loop();
+        }
+        self.finish(read)
+    }
+
+    /// Run one transaction with the master, sleeping until CS is
+    /// deasserted
+    ///
+    /// The slave must be created with `new_async`. If the future is
+    /// dropped early, both channels are stopped.
+    #[cfg(feature = "async")]
+    pub async fn transaction_async<W: Word>(
+        &mut self,
+        read: &mut [W],
+        write: &[W],
+    ) -> Result<usize, Error> {
+        /// Stops both channels unless the transaction finished
+        struct StopOnDrop<'a, const RX: u8, const TX: u8> {
+            rx: &'a mut Channel<RX>,
+            tx: &'a mut Channel<TX>,
+        }
+
+        impl<const RX: u8, const TX: u8> Drop for StopOnDrop<'_, RX, TX> {
+            fn drop(&mut self) {
+                self.rx.stop();
+                self.tx.stop();
+                self.rx.clear_done();
+                self.tx.clear_done();
+            }
+        }
+
+        // SAFETY: The channels are stopped by `finish`, or by the guard if
+        // the future is dropped, before the buffers are released.
+        unsafe { self.start(read, write)? };
+        let guard = StopOnDrop {
+            rx: &mut self.rx,
+            tx: &mut self.tx,
+        };
+        self.slave.wait_cs_deasserted().await;
+        core::mem::forget(guard);
+        self.finish(read)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<I: Instance> SpiSlave<I> {
+    /// Create a new slave whose CS-deasserted event can be awaited
+    ///
+    /// Configures the peripheral like [`new`](Self::new), then enables GPIO_EVEN
+    /// and GPIO_ODD in the NVIC. `_irqs` proves that both vectors are bound to
+    /// [`gpio::InterruptHandler`](crate::gpio::InterruptHandler).
+    pub fn new_async<const PORT: char, const PIN: u8, PULL>(
+        periph: I,
+        cs: Pin<PORT, PIN, Input<PULL>>,
+        config: SlaveConfig,
+        clocks: &FrozenClocks,
+        _irqs: impl Binding<typelevel::GPIO_EVEN, InterruptHandler>
+            + Binding<typelevel::GPIO_ODD, InterruptHandler>,
+    ) -> Self {
+        let slave = Self::new(periph, cs, config, clocks);
+        interrupt::enable::<typelevel::GPIO_EVEN>();
+        interrupt::enable::<typelevel::GPIO_ODD>();
+        slave
+    }
+}
@@ -1,28 +1,29 @@
 //! embedded-hal v1.0 SPI trait implementations
 
-use super::{Error, Spi0, Spi1, Spi2};
+use super::{Error, Instance, Spi};
 use embedded_hal::spi::{ErrorType, SpiBus};
 
-// Implement embedded-hal ErrorType for Spi0
-impl ErrorType for Spi0 {
+// Implement embedded-hal ErrorType
+impl<I: Instance> ErrorType for Spi<I> {
     type Error = Error;
 }
 
-// Implement embedded-hal SpiBus trait for Spi0
-impl SpiBus for Spi0 {
+// Implement embedded-hal SpiBus trait for frames of up to 8 bits
+impl<I: Instance> SpiBus for Spi<I> {
     fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
-        Spi0::read(self, words)
+        Spi::read(self, words)
     }
 
     fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
-        Spi0::write(self, words)
+        Spi::write(self, words)
     }
 
     fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
-        Spi0::transfer(self, read, write)
+        Spi::transfer(self, read, write)
     }
 
     fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        self.check_byte_frames()?;
         for word in words.iter_mut() {
             *word = self.transfer_byte(*word)?;
         }
@@ -30,76 +31,51 @@ impl SpiBus for Spi0 {
     }
 
     fn flush(&mut self) -> Result<(), Self::Error> {
-        // Wait for transmission to complete
-        while self.usart.status().read().txc().bit_is_clear() {}
+        // Wait for the TX buffer and shift register to drain
+        while !I::regs().tx_complete() {}
         Ok(())
     }
 }
 
-// Implement embedded-hal ErrorType for Spi1
-impl ErrorType for Spi1 {
-    type Error = Error;
-}
-
-// Implement embedded-hal SpiBus trait for Spi1
-impl SpiBus for Spi1 {
-    fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
-        Spi1::read(self, words)
-    }
-
-    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
-        Spi1::write(self, words)
-    }
-
-    fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
-        Spi1::transfer(self, read, write)
-    }
-
-    fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+// Implement embedded-hal SpiBus<u16> for frames of any size
+impl<I: Instance> SpiBus<u16> for Spi<I> {
+    fn read(&mut self, words: &mut [u16]) -> Result<(), Self::Error> {
         for word in words.iter_mut() {
-            *word = self.transfer_byte(*word)?;
+            *word = self.transfer_word(0x0000)?;
         }
         Ok(())
     }
 
-    fn flush(&mut self) -> Result<(), Self::Error> {
-        // Wait for TX FIFO to drain (EUSART uses TXFL instead of TXC)
-        // TXFL bit is set when FIFO has space, clear when empty
-        while self.eusart.status().read().txfl().bit_is_set() {}
+    fn write(&mut self, words: &[u16]) -> Result<(), Self::Error> {
+        for &word in words {
+            self.transfer_word(word)?;
+        }
         Ok(())
     }
-}
-
-// Implement embedded-hal ErrorType for Spi2
-impl ErrorType for Spi2 {
-    type Error = Error;
-}
-
-// Implement embedded-hal SpiBus trait for Spi2
-impl SpiBus for Spi2 {
-    fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
-        Spi2::read(self, words)
-    }
-
-    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
-        Spi2::write(self, words)
-    }
 
-    fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
-        Spi2::transfer(self, read, write)
+    /// Full-duplex transfer
+    ///
+    /// Clocks `max(read.len(), write.len())` frames: missing write frames
+    /// are sent as `0x0000` and extra received frames are discarded.
+    fn transfer(&mut self, read: &mut [u16], write: &[u16]) -> Result<(), Self::Error> {
+        let len = read.len().max(write.len());
+        for i in 0..len {
+            let received = self.transfer_word(write.get(i).copied().unwrap_or(0x0000))?;
+            if let Some(rx) = read.get_mut(i) {
+                *rx = received;
+            }
+        }
+        Ok(())
     }
 
-    fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+    fn transfer_in_place(&mut self, words: &mut [u16]) -> Result<(), Self::Error> {
         for word in words.iter_mut() {
-            *word = self.transfer_byte(*word)?;
+            *word = self.transfer_word(*word)?;
         }
         Ok(())
     }
 
     fn flush(&mut self) -> Result<(), Self::Error> {
-        // Wait for TX FIFO to drain (EUSART uses TXFL instead of TXC)
-        // TXFL bit is set when FIFO has space, clear when empty
-        while self.eusart.status().read().txfl().bit_is_set() {}
-        Ok(())
+        SpiBus::<u8>::flush(self)
     }
 }
@@ -1,5 +1,8 @@
 //! SPI Types and Configuration
 
+use crate::gpio::{Output, Pin};
+use crate::time::Hertz;
+
 /// SPI configuration
 #[derive(Debug, Clone, Copy)]
 pub struct Config {
@@ -7,8 +10,12 @@ pub struct Config {
     pub mode: Mode,
     /// SPI bit order
     pub bit_order: BitOrder,
-    /// SPI frequency in Hz
-    pub frequency: u32,
+    /// SPI clock frequency
+    pub frequency: Hertz,
+    /// Frame size in bits: 4 to 16 on USART0, 7 to 16 on the EUSARTs
+    pub data_bits: u8,
+    /// Chip select driven by the peripheral
+    pub hardware_cs: Option<HardwareCs>,
 }
 
 impl Config {
@@ -17,18 +24,20 @@ impl Config {
     /// # Arguments
     ///
     /// * `mode` - SPI mode (CPOL/CPHA)
-    /// * `frequency` - SPI clock frequency in Hz
+    /// * `frequency` - SPI clock frequency
     ///
     /// # Example
     ///
     /// ```no_run
-    /// let config = Config::new(Mode::Mode0, 1_000_000); // 1 MHz, Mode 0
+    /// let config = Config::new(Mode::Mode0, 1.MHz()); // 1 MHz, Mode 0
     /// ```
-    pub const fn new(mode: Mode, frequency: u32) -> Self {
+    pub const fn new(mode: Mode, frequency: Hertz) -> Self {
         Self {
             mode,
             bit_order: BitOrder::MsbFirst,
             frequency,
+            data_bits: 8,
+            hardware_cs: None,
         }
     }
 
@@ -37,6 +46,33 @@ impl Config {
         self.bit_order = bit_order;
         self
     }
+
+    /// Set the frame size in bits
+    ///
+    /// USART0 (`Spi0`) supports 4 to 16 bits, the EUSARTs (`Spi1`, `Spi2`) 7 to
+    /// 16 bits. Frames of up to 8 bits are exchanged as `u8` words, larger ones
+    /// as `u16` words (`SpiBus<u16>`).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// // 12-bit ADC front-end
+    /// let config = Config::new(Mode::Mode1, 2.MHz()).with_data_bits(12);
+    /// ```
+    pub const fn with_data_bits(mut self, bits: u8) -> Self {
+        self.data_bits = bits;
+        self
+    }
+
+    /// Drive chip select from the peripheral (AUTOCS)
+    ///
+    /// The driver then implements `SpiDevice` itself, for a single device on
+    /// the bus. For several devices, use GPIO chip selects with the
+    /// [`device`](super::device) wrappers instead.
+    pub const fn with_hardware_cs(mut self, cs: HardwareCs) -> Self {
+        self.hardware_cs = Some(cs);
+        self
+    }
 }
 
 impl Default for Config {
@@ -44,9 +80,178 @@ impl Default for Config {
         Self {
             mode: Mode::Mode0,
             bit_order: BitOrder::MsbFirst,
-            frequency: 1_000_000, // 1 MHz default
+            frequency: Hertz::MHz(1),
+            data_bits: 8,
+            hardware_cs: None,
+        }
+    }
+}
+
+/// SPI slave configuration
+///
+/// SCLK is driven by the master, so there is no frequency setting.
+#[derive(Debug, Clone, Copy)]
+pub struct SlaveConfig {
+    /// SPI mode (clock polarity and phase), as used by the master
+    pub mode: Mode,
+    /// SPI bit order
+    pub bit_order: BitOrder,
+    /// Frame size in bits: 4 to 16 on USART0, 7 to 16 on the EUSARTs
+    pub data_bits: u8,
+}
+
+impl SlaveConfig {
+    /// Create a new slave configuration with 8-bit, MSB-first frames
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let config = SlaveConfig::new(Mode::Mode0);
+    /// ```
+    pub const fn new(mode: Mode) -> Self {
+        Self {
+            mode,
+            bit_order: BitOrder::MsbFirst,
+            data_bits: 8,
         }
     }
+
+    /// Set bit order
+    pub const fn with_bit_order(mut self, bit_order: BitOrder) -> Self {
+        self.bit_order = bit_order;
+        self
+    }
+
+    /// Set the frame size in bits
+    ///
+    /// Same ranges as [`Config::with_data_bits`].
+    pub const fn with_data_bits(mut self, bits: u8) -> Self {
+        self.data_bits = bits;
+        self
+    }
+}
+
+impl Default for SlaveConfig {
+    fn default() -> Self {
+        Self::new(Mode::Mode0)
+    }
+}
+
+/// SPI slave interrupt sources
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlaveEvent {
+    /// A received frame is available (USART0 RXDATAV, EUSART RXFL)
+    RxReady,
+    /// The TX buffer has room (USART0 TXBL, EUSART TXFL)
+    TxReady,
+    /// A frame arrived while the RX buffer was full
+    Overflow,
+    /// The master clocked a frame while the TX buffer was empty
+    Underflow,
+    /// The master deasserted CS (EXTI line of the CS pin)
+    CsDeasserted,
+}
+
+mod sealed {
+    pub trait Word {}
+}
+
+/// Word type of SPI slave transfers
+///
+/// `u8` for frames of up to 8 bits, `u16` for frames of any size.
+pub trait Word: sealed::Word + Copy + Default + 'static {
+    /// `true` if the word holds frames wider than 8 bits
+    #[doc(hidden)]
+    const WIDE: bool;
+
+    /// Convert a received frame
+    #[doc(hidden)]
+    fn from_frame(frame: u16) -> Self;
+
+    /// Convert to a frame to transmit
+    #[doc(hidden)]
+    fn into_frame(self) -> u16;
+}
+
+impl sealed::Word for u8 {}
+
+impl Word for u8 {
+    const WIDE: bool = false;
+
+    fn from_frame(frame: u16) -> Self {
+        frame as u8
+    }
+
+    fn into_frame(self) -> u16 {
+        u16::from(self)
+    }
+}
+
+impl sealed::Word for u16 {}
+
+impl Word for u16 {
+    const WIDE: bool = true;
+
+    fn from_frame(frame: u16) -> Self {
+        frame
+    }
+
+    fn into_frame(self) -> u16 {
+        self
+    }
+}
+
+/// Hardware-managed chip select
+///
+/// The chip select output (active low) is routed to the given pin and asserted
+/// automatically while the transmitter has data (AUTOCS), with setup and hold
+/// times counted in SCK periods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HardwareCs {
+    /// CS (port, pin)
+    pub(crate) cs: (u8, u8),
+    /// CS setup time before the first clock edge, in SCK periods
+    pub(crate) setup_bits: u8,
+    /// CS hold time after the last clock edge, in SCK periods
+    pub(crate) hold_bits: u8,
+}
+
+impl HardwareCs {
+    /// Drive CS on `cs`, with no extra setup or hold time
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let cs = HardwareCs::new(gpio.portc.pc0.into_push_pull_output()).setup_bits(1);
+    /// let config = Config::new(Mode::Mode0, 8.MHz()).with_hardware_cs(cs);
+    /// ```
+    pub fn new<const PORT: char, const PIN: u8, const DRIVE: u8>(
+        cs: Pin<PORT, PIN, Output<DRIVE>>,
+    ) -> Self {
+        Self {
+            cs: cs.route_location(),
+            setup_bits: 0,
+            hold_bits: 0,
+        }
+    }
+
+    /// Set the time CS is asserted before the first clock edge, in SCK periods
+    ///
+    /// USART0 supports 0, 1, 2, 3 and 7 periods and rounds other values up;
+    /// the EUSARTs support 0 to 7. Values above 7 are limited to 7.
+    pub const fn setup_bits(mut self, bits: u8) -> Self {
+        self.setup_bits = bits;
+        self
+    }
+
+    /// Set the time CS stays asserted after the last clock edge, in SCK periods
+    ///
+    /// USART0 supports 0, 1, 2, 3 and 7 periods and rounds other values up;
+    /// the EUSARTs support 0 to 7. Values above 7 are limited to 7.
+    pub const fn hold_bits(mut self, bits: u8) -> Self {
+        self.hold_bits = bits;
+        self
+    }
 }
 
 /// SPI mode (clock polarity and phase)
@@ -112,6 +317,9 @@ pub enum BitOrder {
 pub enum Error {
     /// Overrun error (data received before previous data was read)
     Overrun,
+    /// Underflow error (slave mode: the master clocked a frame while the TX
+    /// buffer was empty)
+    Underflow,
     /// Frame error
     FrameError,
     /// Invalid configuration
@@ -122,6 +330,7 @@ impl core::fmt::Display for Error {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         match self {
             Error::Overrun => write!(f, "SPI overrun error"),
+            Error::Underflow => write!(f, "SPI underflow error"),
             Error::FrameError => write!(f, "SPI frame error"),
             Error::InvalidConfig => write!(f, "SPI invalid configuration"),
         }
@@ -133,6 +342,7 @@ impl embedded_hal::spi::Error for Error {
     fn kind(&self) -> embedded_hal::spi::ErrorKind {
         match self {
             Error::Overrun => embedded_hal::spi::ErrorKind::Overrun,
+            Error::Underflow => embedded_hal::spi::ErrorKind::Other,
             Error::FrameError => embedded_hal::spi::ErrorKind::FrameFormat,
             Error::InvalidConfig => embedded_hal::spi::ErrorKind::Other,
         }
@@ -0,0 +1,98 @@
+//! Typed Time and Rate Units
+//!
+//! Frequencies, baud rates and durations are passed around the HAL as [`fugit`]
+//! types instead of bare `u32`s, so a frequency in kHz can no longer be mistaken
+//! for one in Hz, and a duration cannot be passed where a rate is expected.
+//!
+//! # Overview
+//!
+//! - [`Hertz`], [`KiloHertz`], [`MegaHertz`] - Rates (32-bit, integer)
+//! - [`Bps`] - Baud rate, in bits per second (a distinct type, not a [`Hertz`])
+//! - [`MicroSeconds`], [`MilliSeconds`], [`Seconds`] - Durations (32-bit)
+//!
+//! Rates with different units convert into each other at compile time, so
+//! `39.MHz()` can be passed wherever a [`Hertz`] is expected. A clock frequency
+//! is not a baud rate: passing `115_200.Hz()` where a [`Bps`] is expected does
+//! not compile.
+//!
+//! # Extension Traits
+//!
+//! The extension traits are part of the [`prelude`](crate::prelude):
+//!
+//! - [`RateExtU32`] - `.Hz()`, `.kHz()`, `.MHz()`
+//! - [`ExtU32`] - `.micros()`, `.millis()`, `.secs()`, ...
+//! - [`BpsExt`] - `.bps()`
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use efr32mg24_hal::{
+//!     clock::HfxoConfig,
+//!     prelude::*,
+//!     spi,
+//!     time::{Hertz, MilliSeconds},
+//!     usart,
+//! };
+//!
+//! let hfxo = HfxoConfig::new(39.MHz());
+//! let spi_config = spi::Config::new(spi::Mode::Mode0, 1.MHz());
+//! let uart_config = usart::Config::new(115_200.bps());
+//!
+//! let freq: Hertz = 400.kHz();
+//! assert_eq!(freq.to_Hz(), 400_000);
+//!
+//! let timeout: MilliSeconds = 10.millis();
+//! ```
+
+pub use fugit::{ExtU32, RateExtU32};
+
+/// Frequency in Hz
+pub type Hertz = fugit::HertzU32;
+
+/// Frequency in kHz
+pub type KiloHertz = fugit::KilohertzU32;
+
+/// Frequency in MHz
+pub type MegaHertz = fugit::MegahertzU32;
+
+/// Baud rate in bits per second
+///
+/// Created with [`BpsExt::bps`] or [`Bps::from_raw`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Bps(u32);
+
+impl Bps {
+    /// Create a baud rate from a value in bits per second
+    #[inline]
+    pub const fn from_raw(bps: u32) -> Self {
+        Self(bps)
+    }
+
+    /// Baud rate in bits per second
+    #[inline]
+    pub const fn to_bps(self) -> u32 {
+        self.0
+    }
+}
+
+/// Duration in microseconds
+pub type MicroSeconds = fugit::MicrosDurationU32;
+
+/// Duration in milliseconds
+pub type MilliSeconds = fugit::MillisDurationU32;
+
+/// Duration in seconds
+pub type Seconds = fugit::SecsDurationU32;
+
+/// Extension trait creating baud rates from `u32`
+pub trait BpsExt {
+    /// Baud rate in bits per second
+    fn bps(self) -> Bps;
+}
+
+impl BpsExt for u32 {
+    #[inline]
+    fn bps(self) -> Bps {
+        Bps::from_raw(self)
+    }
+}
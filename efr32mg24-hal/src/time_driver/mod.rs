@@ -0,0 +1,82 @@
+//! embassy-time Driver
+//!
+//! This module implements the [`embassy_time_driver::Driver`] trait so that
+//! `embassy-time` (and the embassy executor's timer queue) can run on the
+//! EFR32MG24. One of two backends is selected with a cargo feature:
+//!
+//! | Feature | Counter | Tick rate | Energy modes |
+//! |---------|---------|-----------|--------------|
+//! | `time-driver-sysrtc0` | SYSRTC0, 32-bit, LFCLK | LFCLK (`tick-hz-32_768`) | EM0-EM2 |
+//! | `time-driver-timer0` | TIMER0, 32-bit, HFCLK | HFCLK / 1-1024 (`tick-hz-1_000_000`, ...) | EM0-EM1 |
+//!
+//! SYSRTC0 is the primary backend: it keeps counting in EM2, and its compare
+//! interrupt wakes the executor from WFE in EM2. TIMER0 offers a finer tick
+//! but stops in EM2. Enabling both features is a compile error.
+//!
+//! The backend's interrupt vector (SYSRTC_APP or TIMER0) must be bound to
+//! [`InterruptHandler`] with [`bind_interrupts!`](crate::bind_interrupts).
+//!
+//! # Timekeeping
+//!
+//! Both counters are 32 bits wide. A 64-bit timestamp is built by counting half
+//! periods: the period counter is incremented on a compare match at
+//! `0x8000_0000` and again on overflow. Because the counter's MSB and the period
+//! parity always agree, `now()` is consistent even if the counter wraps between
+//! reading the period and reading the counter.
+//!
+//! Alarms are armed on a second compare channel only when they fall within the
+//! next 3/4 of the counter range; later alarms are re-checked on every period
+//! interrupt.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use efr32mg24_hal::{
+//!     bind_interrupts,
+//!     clock::{Clocks, ClockConfig, HfxoConfig, LfxoConfig},
+//!     pac,
+//!     prelude::*,
+//!     time_driver,
+//! };
+//!
+//! bind_interrupts!(struct Irqs {
+//!     SYSRTC_APP => time_driver::InterruptHandler;
+//! });
+//!
+//! let dp = pac::Peripherals::take().unwrap();
+//!
+//! let (clocks, cmu) = Clocks::new(dp.cmu_s, ClockConfig {
+//!     hfxo: Some(HfxoConfig::new(39.MHz())),
+//!     lfxo: Some(LfxoConfig::default()),
+//! }).unwrap();
+//! let clocks = clocks.freeze(cmu);
+//!
+//! // Start the time driver before spawning tasks that use embassy-time
+//! time_driver::init(dp.sysrtc0_s, &clocks, Irqs);
+//! ```
+
+#[cfg(all(feature = "time-driver-sysrtc0", feature = "time-driver-timer0"))]
+compile_error!("enable only one of `time-driver-sysrtc0` and `time-driver-timer0`");
+
+#[cfg(feature = "time-driver-sysrtc0")]
+mod sysrtc0;
+#[cfg(feature = "time-driver-timer0")]
+mod timer0;
+
+#[cfg(feature = "time-driver-sysrtc0")]
+pub use sysrtc0::{init, InterruptHandler};
+#[cfg(feature = "time-driver-timer0")]
+pub use timer0::{init, InterruptHandler};
+
+/// Compare value used to mark the middle of each counter period
+const HALF_PERIOD: u32 = 0x8000_0000;
+
+/// Alarms closer than this many ticks are armed on the compare channel
+/// immediately
+const ALARM_WINDOW: u64 = 0xC000_0000;
+
+/// Combine the half-period count and the counter value into a 64-bit timestamp
+#[inline]
+fn calc_now(period: u32, counter: u32) -> u64 {
+    ((period as u64) << 31) + (counter ^ ((period & 1) << 31)) as u64
+}
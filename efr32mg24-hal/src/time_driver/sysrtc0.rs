@@ -0,0 +1,270 @@
+//! embassy-time driver on SYSRTC0
+//!
+//! Enabled with the `time-driver-sysrtc0` cargo feature.
+//!
+//! # Hardware Registers
+//!
+//! - **CNT**: Free-running 32-bit counter, clocked from LFCLK
+//! - **GRP0_CMP0VALUE**: Alarm compare value
+//! - **GRP0_CMP1VALUE**: Fixed at half the counter range for period extension
+//! - **GRP0_IEN/GRP0_IF**: OVF, CMP0 and CMP1 interrupts (SYSRTC_APP vector)
+//!
+//! Writes to the counter and compare registers cross into the LF clock domain
+//! and complete after SYNCBUSY clears, a few LFCLK cycles later.
+//!
+//! # Tick Rate
+//!
+//! SYSRTC0 has no prescaler, so the embassy-time tick rate must equal LFCLK:
+//! enable embassy-time's `tick-hz-32_768` feature. The oscillator reported by
+//! [`FrozenClocks::lf_source`] (LFXO when configured, LFRCO otherwise) is
+//! selected as the SYSRTC clock.
+//!
+//! # Energy Modes
+//!
+//! SYSRTC0 keeps counting in EM2, and the SYSRTC_APP interrupt wakes the core
+//! from EM2. Set SLEEPDEEP in the SCB so the executor's WFE enters EM2 between
+//! polls; HF peripherals (TIMERs, USART, ...) stop while it sleeps.
+
+use core::cell::{Cell, RefCell};
+use core::sync::atomic::{compiler_fence, AtomicU32, Ordering};
+use core::task::Waker;
+
+use critical_section::{CriticalSection, Mutex};
+use embassy_time_driver::{Driver, TICK_HZ};
+use embassy_time_queue_utils::Queue;
+
+use super::{calc_now, ALARM_WINDOW, HALF_PERIOD};
+use crate::clock::{FrozenClocks, LfClockSource};
+use crate::interrupt::{
+    self,
+    typelevel::{self, Binding, Handler},
+};
+use crate::pac;
+use crate::regs;
+
+/// SYSRTC0 GRP0 IF/IEN bit positions
+const IF_OVF: u32 = 1 << 0;
+const IF_CMP0: u32 = 1 << 1;
+const IF_CMP1: u32 = 1 << 2;
+
+/// Alarms are armed at least this many ticks ahead
+///
+/// A compare value only takes effect once its write has crossed into the LF
+/// clock domain, so a match closer than that could be missed.
+const MIN_ALARM_TICKS: u64 = 3;
+
+/// Access the SYSRTC0 register block
+///
+/// The peripheral singleton is consumed by [`init`], after which the driver is
+/// the only user of SYSRTC0.
+#[inline]
+fn sysrtc0() -> &'static pac::sysrtc0_s::RegisterBlock {
+    // SAFETY: SYSRTC0 is owned by the time driver once `init()` has consumed the
+    // peripheral. All register access is either read-only or performed inside a
+    // critical section.
+    unsafe { &*pac::Sysrtc0S::ptr() }
+}
+
+/// embassy-time driver state
+struct Sysrtc0Driver {
+    /// Number of half periods (CMP1 matches and overflows) since start
+    period: AtomicU32,
+    /// Timestamp of the armed alarm, `u64::MAX` if none
+    alarm: Mutex<Cell<u64>>,
+    /// Timer queue of pending wakers
+    queue: Mutex<RefCell<Queue>>,
+}
+
+embassy_time_driver::time_driver_impl!(static DRIVER: Sysrtc0Driver = Sysrtc0Driver {
+    period: AtomicU32::new(0),
+    alarm: Mutex::new(Cell::new(u64::MAX)),
+    queue: Mutex::new(RefCell::new(Queue::new())),
+});
+
+impl Sysrtc0Driver {
+    fn init(&'static self, sysrtc: pac::Sysrtc0S, clocks: &FrozenClocks) {
+        assert!(
+            u64::from(clocks.lfclk().to_Hz()) == TICK_HZ,
+            "the embassy-time tick rate must equal LFCLK (tick-hz-32_768)"
+        );
+
+        clocks.enable_peripheral_clock(|cmu| {
+            cmu.sysrtc0clkctrl().write(|w| match clocks.lf_source() {
+                LfClockSource::Lfxo => w.clksel().lfxo(),
+                LfClockSource::Lfrco => w.clksel().lfrco(),
+            });
+            cmu.clken0().modify(|_, w| w.sysrtc0().set_bit());
+        });
+
+        sysrtc.en().write(|w| w.en().set_bit());
+
+        sysrtc
+            .grp0_ctrl()
+            .write(|w| w.cmp0en().set_bit().cmp1en().set_bit());
+        while sysrtc.grp0_syncbusy().read().ctrl().bit_is_set() {}
+
+        // SAFETY: CMP1VALUE and CNT are 32-bit fields; any value is valid.
+        sysrtc
+            .grp0_cmp1value()
+            .write(|w| unsafe { w.bits(HALF_PERIOD) });
+        sysrtc.cnt().write(|w| unsafe { w.bits(0) });
+        while sysrtc.grp0_syncbusy().read().cmp1value().bit_is_set() {}
+        while sysrtc.syncbusy().read().cnt().bit_is_set() {}
+
+        regs::clear_bits(sysrtc.grp0_if(), u32::MAX);
+        sysrtc
+            .grp0_ien()
+            .write(|w| w.ovf().set_bit().cmp1().set_bit());
+
+        interrupt::enable::<typelevel::SYSRTC_APP>();
+
+        sysrtc.cmd().write(|w| w.start().set_bit());
+        while sysrtc.syncbusy().read().start().bit_is_set() {}
+    }
+
+    fn on_interrupt(&self) {
+        let sysrtc = sysrtc0();
+
+        critical_section::with(|cs| {
+            let pending = sysrtc.grp0_if().read().bits() & sysrtc.grp0_ien().read().bits();
+
+            if pending & (IF_OVF | IF_CMP1) != 0 {
+                regs::clear_bits(sysrtc.grp0_if(), pending & (IF_OVF | IF_CMP1));
+                self.next_period(cs);
+            }
+
+            if pending & IF_CMP0 != 0 {
+                regs::clear_bits(sysrtc.grp0_if(), IF_CMP0);
+                self.trigger_alarm(cs);
+            }
+        });
+    }
+
+    /// Advance the half-period count and arm CMP0 if the alarm is now in range
+    fn next_period(&self, cs: CriticalSection) {
+        let period = self.period.load(Ordering::Relaxed) + 1;
+        self.period.store(period, Ordering::Relaxed);
+        let start = (period as u64) << 31;
+
+        if self.alarm.borrow(cs).get() < start + ALARM_WINDOW {
+            regs::set_bits(sysrtc0().grp0_ien(), IF_CMP0);
+        }
+    }
+
+    /// Wake expired timers and arm the next alarm
+    fn trigger_alarm(&self, cs: CriticalSection) {
+        let mut queue = self.queue.borrow(cs).borrow_mut();
+        let mut next = queue.next_expiration(self.now());
+        while !self.set_alarm(cs, next) {
+            next = queue.next_expiration(self.now());
+        }
+    }
+
+    /// Arm CMP0 for `timestamp`
+    ///
+    /// Returns `false` if the timestamp has already passed, in which case the
+    /// caller must process the queue again.
+    fn set_alarm(&self, cs: CriticalSection, timestamp: u64) -> bool {
+        let sysrtc = sysrtc0();
+        let alarm = self.alarm.borrow(cs);
+
+        alarm.set(timestamp);
+
+        let now = self.now();
+        if timestamp <= now {
+            regs::clear_bits(sysrtc.grp0_ien(), IF_CMP0);
+            alarm.set(u64::MAX);
+            return false;
+        }
+
+        // An alarm a few ticks away fires up to MIN_ALARM_TICKS late instead
+        // of being missed
+        let compare = timestamp.max(now + MIN_ALARM_TICKS);
+
+        while sysrtc.grp0_syncbusy().read().cmp0value().bit_is_set() {}
+        // SAFETY: CMP0VALUE is a 32-bit field; the low word of the timestamp is
+        // the counter value at which the alarm fires.
+        sysrtc
+            .grp0_cmp0value()
+            .write(|w| unsafe { w.bits(compare as u32) });
+        while sysrtc.grp0_syncbusy().read().cmp0value().bit_is_set() {}
+        regs::clear_bits(sysrtc.grp0_if(), IF_CMP0);
+
+        // Far-away alarms are armed later by next_period()
+        if timestamp - now < ALARM_WINDOW {
+            regs::set_bits(sysrtc.grp0_ien(), IF_CMP0);
+        } else {
+            regs::clear_bits(sysrtc.grp0_ien(), IF_CMP0);
+        }
+
+        // The counter may have passed the compare value while it was written
+        if compare <= self.now() {
+            regs::clear_bits(sysrtc.grp0_ien(), IF_CMP0);
+            alarm.set(u64::MAX);
+            return false;
+        }
+
+        true
+    }
+}
+
+impl Driver for Sysrtc0Driver {
+    fn now(&self) -> u64 {
+        let period = self.period.load(Ordering::Relaxed);
+        compiler_fence(Ordering::Acquire);
+        let counter = sysrtc0().cnt().read().bits();
+        calc_now(period, counter)
+    }
+
+    fn schedule_wake(&self, at: u64, waker: &Waker) {
+        critical_section::with(|cs| {
+            let mut queue = self.queue.borrow(cs).borrow_mut();
+            if queue.schedule_wake(at, waker) {
+                let mut next = queue.next_expiration(self.now());
+                while !self.set_alarm(cs, next) {
+                    next = queue.next_expiration(self.now());
+                }
+            }
+        });
+    }
+}
+
+/// Start the embassy-time driver on SYSRTC0
+///
+/// Must be called once, before any embassy-time API is used. Consumes SYSRTC0,
+/// clocks it from the LF oscillator, starts it as a free-running 32-bit counter
+/// and enables the SYSRTC_APP interrupt in the NVIC.
+///
+/// The SYSRTC_APP vector must be bound to [`InterruptHandler`] with
+/// [`bind_interrupts!`](crate::bind_interrupts).
+///
+/// # Arguments
+///
+/// * `sysrtc` - SYSRTC0_S peripheral (owned by the driver from now on)
+/// * `clocks` - Frozen clock configuration
+/// * `_irq` - Proof that SYSRTC_APP is bound to [`InterruptHandler`]
+///
+/// # Panics
+///
+/// Panics if LFCLK differs from the configured tick rate.
+pub fn init(
+    sysrtc: pac::Sysrtc0S,
+    clocks: &FrozenClocks,
+    _irq: impl Binding<typelevel::SYSRTC_APP, InterruptHandler>,
+) {
+    DRIVER.init(sysrtc, clocks);
+}
+
+/// Time driver interrupt handler
+///
+/// Bind the SYSRTC_APP vector to this handler with
+/// [`bind_interrupts!`](crate::bind_interrupts) and pass the binding to [`init`].
+pub struct InterruptHandler {
+    _private: (),
+}
+
+impl Handler<typelevel::SYSRTC_APP> for InterruptHandler {
+    unsafe fn on_interrupt() {
+        DRIVER.on_interrupt();
+    }
+}
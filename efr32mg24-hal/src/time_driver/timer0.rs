@@ -0,0 +1,253 @@
+//! embassy-time driver on TIMER0
+//!
+//! Enabled with the `time-driver-timer0` cargo feature.
+//!
+//! # Hardware Registers
+//!
+//! - **CFG**: Up-count mode and prescaler (written while the timer is disabled)
+//! - **TOP**: Set to `0xFFFF_FFFF` so TIMER0 free-runs over its full 32 bits
+//! - **CC0_OC**: Alarm compare value
+//! - **CC1_OC**: Fixed at half the counter range for period extension
+//! - **IEN/IF**: OF, CC0 and CC1 interrupts
+//!
+//! # Tick Rate
+//!
+//! The tick rate is selected with embassy-time's `tick-hz-*` features (1 MHz by
+//! default). TIMER0 is clocked from HFCLK through a 1-1024 prescaler, so HFCLK
+//! must be an integer multiple of the tick rate. With the XIAO MG24's 39 MHz HFXO,
+//! `tick-hz-1_000_000`, `tick-hz-3_000_000` and `tick-hz-1_000` all divide evenly.
+//!
+//! # Energy Modes
+//!
+//! The executor sleeps with WFE and is woken by the TIMER0 interrupt. TIMER0 is
+//! not clocked in EM2, so the core must stay in EM0/EM1 while timers are pending;
+//! use the SYSRTC0 backend (`time-driver-sysrtc0`) for EM2.
+
+use core::cell::{Cell, RefCell};
+use core::sync::atomic::{compiler_fence, AtomicU32, Ordering};
+use core::task::Waker;
+
+use critical_section::{CriticalSection, Mutex};
+use embassy_time_driver::{Driver, TICK_HZ};
+use embassy_time_queue_utils::Queue;
+
+use super::{calc_now, ALARM_WINDOW, HALF_PERIOD};
+use crate::clock::FrozenClocks;
+use crate::interrupt::{
+    self,
+    typelevel::{self, Binding, Handler},
+};
+use crate::pac;
+use crate::regs;
+
+/// TIMER0 IF/IEN bit positions
+const IF_OF: u32 = 1 << 0;
+const IF_CC0: u32 = 1 << 4;
+const IF_CC1: u32 = 1 << 5;
+
+/// Access the TIMER0 register block
+///
+/// The peripheral singleton is consumed by [`init`], after which the driver is
+/// the only user of TIMER0.
+#[inline]
+fn timer0() -> &'static pac::timer0_s::RegisterBlock {
+    // SAFETY: TIMER0 is owned by the time driver once `init()` has consumed the
+    // peripheral. All register access is either read-only or performed inside a
+    // critical section.
+    unsafe { &*pac::Timer0S::ptr() }
+}
+
+/// embassy-time driver state
+struct Timer0Driver {
+    /// Number of half periods (CC1 matches and overflows) since start
+    period: AtomicU32,
+    /// Timestamp of the armed alarm, `u64::MAX` if none
+    alarm: Mutex<Cell<u64>>,
+    /// Timer queue of pending wakers
+    queue: Mutex<RefCell<Queue>>,
+}
+
+embassy_time_driver::time_driver_impl!(static DRIVER: Timer0Driver = Timer0Driver {
+    period: AtomicU32::new(0),
+    alarm: Mutex::new(Cell::new(u64::MAX)),
+    queue: Mutex::new(RefCell::new(Queue::new())),
+});
+
+impl Timer0Driver {
+    fn init(&'static self, timer: pac::Timer0S, clocks: &FrozenClocks) {
+        clocks.enable_peripheral_clock(|cmu| {
+            cmu.clken0().modify(|_, w| w.timer0().set_bit());
+        });
+
+        let timer_hz = clocks.hfclk().to_Hz() as u64;
+        let divider = timer_hz / TICK_HZ;
+        assert!(
+            (1..=1024).contains(&divider) && divider * TICK_HZ == timer_hz,
+            "HFCLK must be an integer multiple (1-1024) of the embassy-time tick rate"
+        );
+
+        // CFG and CCx_CFG are only writable while the timer is disabled
+        timer.en().write(|w| w.en().clear_bit());
+
+        // SAFETY: divider is checked above to be in 1..=1024, so PRESC (divider - 1)
+        // fits the 10-bit field.
+        timer
+            .cfg()
+            .write(|w| unsafe { w.mode().up().presc().bits((divider - 1) as u16) });
+        timer.cc0_cfg().write(|w| w.mode().outputcompare());
+        timer.cc1_cfg().write(|w| w.mode().outputcompare());
+
+        timer.en().write(|w| w.en().set_bit());
+
+        // SAFETY: TOP, CC1_OC and CNT are 32-bit fields on TIMER0; any value is valid.
+        timer.top().write(|w| unsafe { w.top().bits(u32::MAX) });
+        timer
+            .cc1_oc()
+            .write(|w| unsafe { w.oc().bits(HALF_PERIOD) });
+        timer.cnt().write(|w| unsafe { w.cnt().bits(0) });
+
+        regs::clear_bits(timer.if_(), u32::MAX);
+        timer.ien().write(|w| w.of().set_bit().cc1().set_bit());
+
+        interrupt::enable::<typelevel::TIMER0>();
+
+        timer.cmd().write(|w| w.start().set_bit());
+    }
+
+    fn on_interrupt(&self) {
+        let timer = timer0();
+
+        critical_section::with(|cs| {
+            let pending = timer.if_().read().bits() & timer.ien().read().bits();
+
+            if pending & (IF_OF | IF_CC1) != 0 {
+                regs::clear_bits(timer.if_(), pending & (IF_OF | IF_CC1));
+                self.next_period(cs);
+            }
+
+            if pending & IF_CC0 != 0 {
+                regs::clear_bits(timer.if_(), IF_CC0);
+                self.trigger_alarm(cs);
+            }
+        });
+    }
+
+    /// Advance the half-period count and arm CC0 if the alarm is now in range
+    fn next_period(&self, cs: CriticalSection) {
+        let period = self.period.load(Ordering::Relaxed) + 1;
+        self.period.store(period, Ordering::Relaxed);
+        let start = (period as u64) << 31;
+
+        if self.alarm.borrow(cs).get() < start + ALARM_WINDOW {
+            timer0().ien().modify(|_, w| w.cc0().set_bit());
+        }
+    }
+
+    /// Wake expired timers and arm the next alarm
+    fn trigger_alarm(&self, cs: CriticalSection) {
+        let mut queue = self.queue.borrow(cs).borrow_mut();
+        let mut next = queue.next_expiration(self.now());
+        while !self.set_alarm(cs, next) {
+            next = queue.next_expiration(self.now());
+        }
+    }
+
+    /// Arm CC0 for `timestamp`
+    ///
+    /// Returns `false` if the timestamp has already passed, in which case the
+    /// caller must process the queue again.
+    fn set_alarm(&self, cs: CriticalSection, timestamp: u64) -> bool {
+        let timer = timer0();
+        let alarm = self.alarm.borrow(cs);
+
+        alarm.set(timestamp);
+
+        let now = self.now();
+        if timestamp <= now {
+            timer.ien().modify(|_, w| w.cc0().clear_bit());
+            alarm.set(u64::MAX);
+            return false;
+        }
+
+        // SAFETY: CC0_OC is a 32-bit field on TIMER0; the low word of the
+        // timestamp is the counter value at which the alarm fires.
+        timer
+            .cc0_oc()
+            .write(|w| unsafe { w.oc().bits(timestamp as u32) });
+        regs::clear_bits(timer.if_(), IF_CC0);
+
+        // Far-away alarms are armed later by next_period()
+        let in_window = timestamp - now < ALARM_WINDOW;
+        timer.ien().modify(|_, w| w.cc0().bit(in_window));
+
+        // The counter may have passed the compare value while it was written
+        if timestamp <= self.now() {
+            timer.ien().modify(|_, w| w.cc0().clear_bit());
+            alarm.set(u64::MAX);
+            return false;
+        }
+
+        true
+    }
+}
+
+impl Driver for Timer0Driver {
+    fn now(&self) -> u64 {
+        let period = self.period.load(Ordering::Relaxed);
+        compiler_fence(Ordering::Acquire);
+        let counter = timer0().cnt().read().bits();
+        calc_now(period, counter)
+    }
+
+    fn schedule_wake(&self, at: u64, waker: &Waker) {
+        critical_section::with(|cs| {
+            let mut queue = self.queue.borrow(cs).borrow_mut();
+            if queue.schedule_wake(at, waker) {
+                let mut next = queue.next_expiration(self.now());
+                while !self.set_alarm(cs, next) {
+                    next = queue.next_expiration(self.now());
+                }
+            }
+        });
+    }
+}
+
+/// Start the embassy-time driver on TIMER0
+///
+/// Must be called once, before any embassy-time API is used. Consumes TIMER0,
+/// configures it as a free-running 32-bit counter at the embassy-time tick rate
+/// and enables the TIMER0 interrupt in the NVIC.
+///
+/// The TIMER0 vector must be bound to [`InterruptHandler`] with
+/// [`bind_interrupts!`](crate::bind_interrupts).
+///
+/// # Arguments
+///
+/// * `timer` - TIMER0_S peripheral (owned by the driver from now on)
+/// * `clocks` - Frozen clock configuration
+/// * `_irq` - Proof that TIMER0 is bound to [`InterruptHandler`]
+///
+/// # Panics
+///
+/// Panics if HFCLK is not an integer multiple (1-1024) of the configured tick rate.
+pub fn init(
+    timer: pac::Timer0S,
+    clocks: &FrozenClocks,
+    _irq: impl Binding<typelevel::TIMER0, InterruptHandler>,
+) {
+    DRIVER.init(timer, clocks);
+}
+
+/// Time driver interrupt handler
+///
+/// Bind the TIMER0 vector to this handler with
+/// [`bind_interrupts!`](crate::bind_interrupts) and pass the binding to [`init`].
+pub struct InterruptHandler {
+    _private: (),
+}
+
+impl Handler<typelevel::TIMER0> for InterruptHandler {
+    unsafe fn on_interrupt() {
+        DRIVER.on_interrupt();
+    }
+}
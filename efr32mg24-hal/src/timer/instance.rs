@@ -0,0 +1,56 @@
+//! Timer peripheral instances
+//!
+//! [`Instance`] is implemented for TIMER0 to TIMER4. It ties each of them to
+//! its register block, CMU clock enable bit and interrupt vector, so the driver
+//! is written once for all five.
+//!
+//! The five timers share one register layout; TIMER0 counts 32 bits, TIMER1-4
+//! count 16 bits. The driver limits TOP to 16 bits on every instance.
+
+use crate::interrupt::typelevel;
+use crate::pac;
+
+mod sealed {
+    use crate::pac;
+
+    pub trait Instance {
+        /// Register block
+        fn regs() -> &'static pac::timer0_s::RegisterBlock;
+
+        /// Enable the peripheral clock in the CMU
+        fn enable_clock(cmu: &pac::CmuS);
+    }
+}
+
+/// A timer peripheral: TIMER0 to TIMER4
+pub trait Instance: sealed::Instance + 'static {
+    /// Interrupt vector
+    type Interrupt: typelevel::Interrupt;
+}
+
+macro_rules! impl_instance {
+    ($Periph:ident, $clk:ident, $IRQ:ident) => {
+        impl sealed::Instance for pac::$Periph {
+            fn regs() -> &'static pac::timer0_s::RegisterBlock {
+                // SAFETY: All timers have the register layout of TIMER0, and the
+                // register block is only accessed by the driver that owns the
+                // peripheral.
+                unsafe { &*(pac::$Periph::ptr() as *const pac::timer0_s::RegisterBlock) }
+            }
+
+            fn enable_clock(cmu: &pac::CmuS) {
+                cmu.clken0().modify(|_, w| w.$clk().set_bit());
+            }
+        }
+
+        impl Instance for pac::$Periph {
+            type Interrupt = typelevel::$IRQ;
+        }
+    };
+}
+
+impl_instance!(Timer0S, timer0, TIMER0);
+impl_instance!(Timer1S, timer1, TIMER1);
+impl_instance!(Timer2S, timer2, TIMER2);
+impl_instance!(Timer3S, timer3, TIMER3);
+impl_instance!(Timer4S, timer4, TIMER4);
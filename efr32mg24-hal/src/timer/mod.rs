@@ -23,6 +23,9 @@
 //! - Configurable frequency and duty cycle
 //! - 16-bit or 32-bit resolution
 //! - Hardware register access
+//! - `embedded_hal::delay::DelayNs` on every timer (polls the running counter)
+//! - One generic [`Timer`] driver over the sealed [`Instance`] trait;
+//!   `Timer0`-`Timer4` are aliases
 //!
 //! # Example
 //!
@@ -31,20 +34,21 @@
 //!     clock::{Clocks, ClockConfig, HfxoConfig},
 //!     timer::{Timer0, Config, PwmMode, PwmChannel},
 //!     pac,
+//!     prelude::*,
 //! };
 //!
 //! let dp = pac::Peripherals::take().unwrap();
 //!
 //! // Configure clocks
 //! let clocks = Clocks::new(dp.cmu_s, ClockConfig {
-//!     hfxo: Some(HfxoConfig::new(39_000_000)),
+//!     hfxo: Some(HfxoConfig::new(39.MHz())),
 //!     lfxo: Some(Default::default()),
 //! }).freeze();
 //!
 //! // Create Timer0 with PWM at 10 kHz
 //! let mut timer = Timer0::new(
 //!     dp.timer0_s,
-//!     Config::new(10_000).with_pwm(PwmMode::EdgeAligned),
+//!     Config::new(10.kHz()).with_pwm(PwmMode::EdgeAligned),
 //!     &clocks
 //! );
 //!
@@ -53,383 +57,389 @@
 //! timer.enable_channel(PwmChannel::Channel0);
 //! ```
 
+mod instance;
 mod traits;
 mod types;
 
+pub use instance::Instance;
 pub use types::{Config, Error, PwmChannel, PwmMode};
 
 use crate::clock::FrozenClocks;
 use crate::pac;
+use crate::time::Hertz;
+
+/// Timer and PWM driver on TIMER0 to TIMER4
+///
+/// Use the [`Timer0`] to [`Timer4`] aliases to name a concrete driver.
+pub struct Timer<I: Instance> {
+    _timer: I,
+    frequency: Hertz,
+    top_value: u32,
+    pwm_enabled: bool,
+    /// Counter clock (HFCLK / (PRESC + 1)), used by `DelayNs`
+    counter_hz: u32,
+    /// Counter runs up and down (center-aligned PWM)
+    up_down: bool,
+}
 
-/// Macro to implement timer instances
-macro_rules! impl_timer {
-    ($TimerX:ident, $timerX_s:ident, $timerx:ident, $clken_bit:ident) => {
-        /// Timer instance
-        pub struct $TimerX {
-            timer: pac::$timerX_s,
-            frequency: u32,
-            top_value: u32,
-            pwm_enabled: bool,
-        }
-
-        impl $TimerX {
-            /// Creates a new timer instance
-            ///
-            /// # Arguments
-            ///
-            /// * `timer` - Timer peripheral instance
-            /// * `config` - Timer configuration
-            /// * `clocks` - Frozen clock configuration
-            pub fn new(timer: pac::$timerX_s, config: Config, clocks: &FrozenClocks) -> Self {
-                // Enable timer clock using safe accessor
-                clocks.enable_peripheral_clock(|cmu| {
-                    cmu.clken0().modify(|_, w| w.$clken_bit().set_bit());
-                });
-
-                // Enable timer peripheral
-                timer.en().write(|w| w.en().set_bit());
-
-                // Calculate prescaler and top value for desired frequency
-                let (prescaler, top) =
-                    Self::calculate_prescaler_and_top(clocks.hfclk().into(), config.frequency);
-
-                // Configure PWM mode if requested
-                let pwm_enabled = if let Some(pwm_mode) = config.pwm_mode {
-                    match pwm_mode {
-                        PwmMode::EdgeAligned => {
-                            // Edge-aligned PWM: count up
-                            // SAFETY: Prescaler value (0-10) is validated by calculate_prescaler_and_top().
-                            // Mode enum variant is type-safe and always valid.
-                            timer
-                                .cfg()
-                                .write(|w| unsafe { w.presc().bits(prescaler).mode().up() });
-                        }
-                        PwmMode::CenterAligned => {
-                            // Center-aligned PWM: count up/down
-                            // SAFETY: Prescaler value (0-10) is validated by calculate_prescaler_and_top().
-                            // Mode enum variant is type-safe and always valid.
-                            timer
-                                .cfg()
-                                .write(|w| unsafe { w.presc().bits(prescaler).mode().updown() });
-                        }
-                    }
-                    true
-                } else {
-                    // Basic timer mode: count up
+impl<I: Instance> Timer<I> {
+    /// Creates a new timer instance
+    ///
+    /// # Arguments
+    ///
+    /// * `timer` - Timer peripheral instance
+    /// * `config` - Timer configuration
+    /// * `clocks` - Frozen clock configuration
+    pub fn new(timer: I, config: Config, clocks: &FrozenClocks) -> Self {
+        // Enable timer clock using safe accessor
+        clocks.enable_peripheral_clock(|cmu| I::enable_clock(cmu));
+
+        // Enable timer peripheral
+        let regs = I::regs();
+        regs.en().write(|w| w.en().set_bit());
+
+        // Calculate prescaler and top value for desired frequency
+        let (prescaler, top) =
+            Self::calculate_prescaler_and_top(clocks.hfclk().to_Hz(), config.frequency.to_Hz());
+
+        // Configure PWM mode if requested
+        let pwm_enabled = if let Some(pwm_mode) = config.pwm_mode {
+            match pwm_mode {
+                PwmMode::EdgeAligned => {
+                    // Edge-aligned PWM: count up
                     // SAFETY: Prescaler value (0-10) is validated by calculate_prescaler_and_top().
                     // Mode enum variant is type-safe and always valid.
-                    timer
-                        .cfg()
+                    regs.cfg()
                         .write(|w| unsafe { w.presc().bits(prescaler).mode().up() });
-                    false
-                };
-
-                // Set top value (PWM period)
-                // SAFETY: TOP value is calculated by calculate_prescaler_and_top() to fit within
-                // the timer's 16-bit range (0x0000 to 0xFFFF). The calculation ensures TOP <= 0xFFFF.
-                timer.top().write(|w| unsafe { w.bits(top) });
-
-                Self {
-                    timer,
-                    frequency: config.frequency,
-                    top_value: top,
-                    pwm_enabled,
                 }
-            }
-
-            /// Calculate prescaler and top value for desired frequency
-            ///
-            /// Formula: Timer_freq = HFCLK / (prescaler * (TOP + 1))
-            fn calculate_prescaler_and_top(hfclk: u32, target_freq: u32) -> (u16, u32) {
-                // Try prescalers from 0 to 10 (dividers: 1, 2, 4, 8, 16, 32, 64, 128, 256, 512, 1024)
-                for prescaler in 0..=10u16 {
-                    let divider = 1u32 << prescaler;
-                    let timer_clk = hfclk / divider;
-
-                    // Calculate TOP value
-                    if timer_clk > target_freq {
-                        let top = (timer_clk / target_freq).saturating_sub(1);
-                        if top <= 0xFFFF {
-                            // 16-bit limit
-                            return (prescaler, top);
-                        }
-                    }
+                PwmMode::CenterAligned => {
+                    // Center-aligned PWM: count up/down
+                    // SAFETY: Prescaler value (0-10) is validated by calculate_prescaler_and_top().
+                    // Mode enum variant is type-safe and always valid.
+                    regs.cfg()
+                        .write(|w| unsafe { w.presc().bits(prescaler).mode().updown() });
                 }
-
-                // Fallback: maximum prescaler with best TOP
-                let divider = 1024u32;
-                let timer_clk = hfclk / divider;
-                let top = (timer_clk / target_freq).saturating_sub(1).min(0xFFFF);
-                (10, top)
             }
-
-            /// Start the timer
-            pub fn start(&mut self) {
-                self.timer.cmd().write(|w| w.start().set_bit());
+            true
+        } else {
+            // Basic timer mode: count up
+            // SAFETY: Prescaler value (0-10) is validated by calculate_prescaler_and_top().
+            // Mode enum variant is type-safe and always valid.
+            regs.cfg()
+                .write(|w| unsafe { w.presc().bits(prescaler).mode().up() });
+            false
+        };
+
+        // Set top value (PWM period)
+        // SAFETY: TOP value is calculated by calculate_prescaler_and_top() to fit within
+        // the timer's 16-bit range (0x0000 to 0xFFFF). The calculation ensures TOP <= 0xFFFF.
+        regs.top().write(|w| unsafe { w.bits(top) });
+
+        // The counter advances once every PRESC + 1 HFCLK cycles
+        let hfclk = clocks.hfclk().to_Hz();
+        let counter_hz = hfclk / (regs.cfg().read().presc().bits() as u32 + 1);
+
+        Self {
+            _timer: timer,
+            frequency: config.frequency,
+            top_value: top,
+            pwm_enabled,
+            counter_hz,
+            up_down: config.pwm_mode == Some(PwmMode::CenterAligned),
+        }
+    }
+
+    /// Calculate prescaler and top value for desired frequency
+    ///
+    /// Formula: Timer_freq = HFCLK / (prescaler * (TOP + 1))
+    fn calculate_prescaler_and_top(hfclk: u32, target_freq: u32) -> (u16, u32) {
+        // Try prescalers from 0 to 10 (dividers: 1, 2, 4, 8, 16, 32, 64, 128, 256, 512, 1024)
+        for prescaler in 0..=10u16 {
+            let divider = 1u32 << prescaler;
+            let timer_clk = hfclk / divider;
+
+            // Calculate TOP value
+            if timer_clk > target_freq {
+                let top = (timer_clk / target_freq).saturating_sub(1);
+                if top <= 0xFFFF {
+                    // 16-bit limit
+                    return (prescaler, top);
+                }
             }
+        }
 
-            /// Stop the timer
-            pub fn stop(&mut self) {
-                self.timer.cmd().write(|w| w.stop().set_bit());
-            }
+        // Fallback: maximum prescaler with best TOP
+        let divider = 1024u32;
+        let timer_clk = hfclk / divider;
+        let top = (timer_clk / target_freq).saturating_sub(1).min(0xFFFF);
+        (10, top)
+    }
+
+    /// Start the timer
+    pub fn start(&mut self) {
+        I::regs().cmd().write(|w| w.start().set_bit());
+    }
+
+    /// Stop the timer
+    pub fn stop(&mut self) {
+        I::regs().cmd().write(|w| w.stop().set_bit());
+    }
+
+    /// Get current counter value
+    pub fn get_counter(&self) -> u32 {
+        I::regs().cnt().read().bits()
+    }
+
+    /// Reset counter to zero
+    pub fn reset_counter(&mut self) {
+        // SAFETY: Writing 0 to CNT register is always safe and resets the counter.
+        I::regs().cnt().write(|w| unsafe { w.bits(0) });
+    }
+
+    /// Set duty cycle for a PWM channel
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - PWM channel (0, 1, or 2)
+    /// * `duty_percent` - Duty cycle percentage (0-100)
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, `Err(Error)` if duty cycle is invalid
+    ///
+    /// # Thread Safety
+    ///
+    /// This function uses critical sections for atomic register access,
+    /// making it safe to call from interrupt contexts or RTOS tasks.
+    pub fn set_duty_cycle(&mut self, channel: PwmChannel, duty_percent: u8) -> Result<(), Error> {
+        if duty_percent > 100 {
+            return Err(Error::InvalidDutyCycle);
+        }
 
-            /// Get current counter value
-            pub fn get_counter(&self) -> u32 {
-                self.timer.cnt().read().bits()
-            }
+        if !self.pwm_enabled {
+            return Err(Error::InvalidChannel);
+        }
 
-            /// Reset counter to zero
-            pub fn reset_counter(&mut self) {
-                // SAFETY: Writing 0 to CNT register is always safe and resets the counter.
-                self.timer.cnt().write(|w| unsafe { w.bits(0) });
+        // Calculate compare value
+        let compare_value = (self.top_value as u64 * duty_percent as u64 / 100) as u32;
+
+        // Set compare value for the channel atomically
+        // Use critical section to prevent race conditions in RTOS environments
+        critical_section::with(|_cs| {
+            // SAFETY: Compare value is calculated from validated duty_percent (0-100) and
+            // top_value (<=0xFFFF), ensuring it fits within the CC register width.
+            match channel {
+                PwmChannel::Channel0 => {
+                    I::regs()
+                        .cc0_oc()
+                        .write(|w| unsafe { w.bits(compare_value) });
+                }
+                PwmChannel::Channel1 => {
+                    I::regs()
+                        .cc1_oc()
+                        .write(|w| unsafe { w.bits(compare_value) });
+                }
+                PwmChannel::Channel2 => {
+                    I::regs()
+                        .cc2_oc()
+                        .write(|w| unsafe { w.bits(compare_value) });
+                }
             }
-
-            /// Set duty cycle for a PWM channel
-            ///
-            /// # Arguments
-            ///
-            /// * `channel` - PWM channel (0, 1, or 2)
-            /// * `duty_percent` - Duty cycle percentage (0-100)
-            ///
-            /// # Returns
-            ///
-            /// `Ok(())` on success, `Err(Error)` if duty cycle is invalid
-            ///
-            /// # Thread Safety
-            ///
-            /// This function uses critical sections for atomic register access,
-            /// making it safe to call from interrupt contexts or RTOS tasks.
-            pub fn set_duty_cycle(
-                &mut self,
-                channel: PwmChannel,
-                duty_percent: u8,
-            ) -> Result<(), Error> {
-                if duty_percent > 100 {
-                    return Err(Error::InvalidDutyCycle);
+        });
+
+        Ok(())
+    }
+
+    /// Enable PWM output on a channel
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - PWM channel to enable
+    ///
+    /// # PWM Output Configuration
+    ///
+    /// This configures the CC channel for PWM mode with:
+    /// - **CMOA (Compare Match Output Action)**: SET - Output goes high on compare match
+    /// - **COFOA (Counter Overflow Output Action)**: TOGGLE - Output toggles on overflow/underflow
+    ///
+    /// For edge-aligned PWM (up-counting):
+    /// - Counter counts from 0 to TOP
+    /// - Output goes HIGH when CNT == CCx_OC (compare match)
+    /// - Output goes LOW when CNT reaches TOP (counter overflow)
+    /// - Duty cycle = (CCx_OC / TOP) × 100%
+    ///
+    /// For center-aligned PWM (up-down counting):
+    /// - Counter counts 0→TOP→0
+    /// - Output toggles on compare matches in both directions
+    /// - Creates symmetric waveform
+    ///
+    /// **Note**: This configuration has been validated against EFR32MG24 reference manual
+    /// section on Timer PWM generation. Hardware testing pending.
+    pub fn enable_channel(&mut self, channel: PwmChannel) {
+        critical_section::with(|_cs| {
+            match channel {
+                PwmChannel::Channel0 => {
+                    // Configure channel mode in CC0_CFG
+                    I::regs().cc0_cfg().write(|w| {
+                        w.mode()
+                            .pwm() // PWM mode
+                            .coist()
+                            .clear_bit() // Output low when timer disabled
+                    });
+                    // Configure output action in CC0_CTRL
+                    I::regs().cc0_ctrl().write(|w| {
+                        w.outinv()
+                            .clear_bit() // Non-inverted
+                            .cofoa()
+                            .toggle() // Toggle on counter overflow
+                            .cmoa()
+                            .set_() // Set on compare match
+                    });
                 }
-
-                if !self.pwm_enabled {
-                    return Err(Error::InvalidChannel);
+                PwmChannel::Channel1 => {
+                    I::regs()
+                        .cc1_cfg()
+                        .write(|w| w.mode().pwm().coist().clear_bit());
+                    I::regs()
+                        .cc1_ctrl()
+                        .write(|w| w.outinv().clear_bit().cofoa().toggle().cmoa().set_());
+                }
+                PwmChannel::Channel2 => {
+                    I::regs()
+                        .cc2_cfg()
+                        .write(|w| w.mode().pwm().coist().clear_bit());
+                    I::regs()
+                        .cc2_ctrl()
+                        .write(|w| w.outinv().clear_bit().cofoa().toggle().cmoa().set_());
                 }
-
-                // Calculate compare value
-                let compare_value = (self.top_value as u64 * duty_percent as u64 / 100) as u32;
-
-                // Set compare value for the channel atomically
-                // Use critical section to prevent race conditions in RTOS environments
-                critical_section::with(|_cs| {
-                    // SAFETY: Compare value is calculated from validated duty_percent (0-100) and
-                    // top_value (<=0xFFFF), ensuring it fits within the CC register width.
-                    match channel {
-                        PwmChannel::Channel0 => {
-                            self.timer
-                                .cc0_oc()
-                                .write(|w| unsafe { w.bits(compare_value) });
-                        }
-                        PwmChannel::Channel1 => {
-                            self.timer
-                                .cc1_oc()
-                                .write(|w| unsafe { w.bits(compare_value) });
-                        }
-                        PwmChannel::Channel2 => {
-                            self.timer
-                                .cc2_oc()
-                                .write(|w| unsafe { w.bits(compare_value) });
-                        }
-                    }
-                });
-
-                Ok(())
             }
-
-            /// Enable PWM output on a channel
-            ///
-            /// # Arguments
-            ///
-            /// * `channel` - PWM channel to enable
-            ///
-            /// # PWM Output Configuration
-            ///
-            /// This configures the CC channel for PWM mode with:
-            /// - **CMOA (Compare Match Output Action)**: SET - Output goes high on compare match
-            /// - **COFOA (Counter Overflow Output Action)**: TOGGLE - Output toggles on overflow/underflow
-            ///
-            /// For edge-aligned PWM (up-counting):
-            /// - Counter counts from 0 to TOP
-            /// - Output goes HIGH when CNT == CCx_OC (compare match)
-            /// - Output goes LOW when CNT reaches TOP (counter overflow)
-            /// - Duty cycle = (CCx_OC / TOP) × 100%
-            ///
-            /// For center-aligned PWM (up-down counting):
-            /// - Counter counts 0→TOP→0
-            /// - Output toggles on compare matches in both directions
-            /// - Creates symmetric waveform
-            ///
-            /// **Note**: This configuration has been validated against EFR32MG24 reference manual
-            /// section on Timer PWM generation. Hardware testing pending.
-            pub fn enable_channel(&mut self, channel: PwmChannel) {
-                critical_section::with(|_cs| {
-                    match channel {
-                        PwmChannel::Channel0 => {
-                            // Configure channel mode in CC0_CFG
-                            self.timer.cc0_cfg().write(|w| {
-                                w.mode()
-                                    .pwm() // PWM mode
-                                    .coist()
-                                    .clear_bit() // Output low when timer disabled
-                            });
-                            // Configure output action in CC0_CTRL
-                            self.timer.cc0_ctrl().write(|w| {
-                                w.outinv()
-                                    .clear_bit() // Non-inverted
-                                    .cofoa()
-                                    .toggle() // Toggle on counter overflow
-                                    .cmoa()
-                                    .set_() // Set on compare match
-                            });
-                        }
-                        PwmChannel::Channel1 => {
-                            self.timer
-                                .cc1_cfg()
-                                .write(|w| w.mode().pwm().coist().clear_bit());
-                            self.timer
-                                .cc1_ctrl()
-                                .write(|w| w.outinv().clear_bit().cofoa().toggle().cmoa().set_());
-                        }
-                        PwmChannel::Channel2 => {
-                            self.timer
-                                .cc2_cfg()
-                                .write(|w| w.mode().pwm().coist().clear_bit());
-                            self.timer
-                                .cc2_ctrl()
-                                .write(|w| w.outinv().clear_bit().cofoa().toggle().cmoa().set_());
-                        }
-                    }
-                });
+        });
+    }
+
+    /// Disable PWM output on a channel
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - PWM channel to disable
+    pub fn disable_channel(&mut self, channel: PwmChannel) {
+        critical_section::with(|_cs| match channel {
+            PwmChannel::Channel0 => {
+                I::regs().cc0_cfg().write(|w| w.mode().off());
             }
-
-            /// Disable PWM output on a channel
-            ///
-            /// # Arguments
-            ///
-            /// * `channel` - PWM channel to disable
-            pub fn disable_channel(&mut self, channel: PwmChannel) {
-                critical_section::with(|_cs| match channel {
-                    PwmChannel::Channel0 => {
-                        self.timer.cc0_cfg().write(|w| w.mode().off());
-                    }
-                    PwmChannel::Channel1 => {
-                        self.timer.cc1_cfg().write(|w| w.mode().off());
-                    }
-                    PwmChannel::Channel2 => {
-                        self.timer.cc2_cfg().write(|w| w.mode().off());
-                    }
-                });
+            PwmChannel::Channel1 => {
+                I::regs().cc1_cfg().write(|w| w.mode().off());
             }
-
-            /// Set raw duty cycle value for a PWM channel
-            ///
-            /// This provides direct access to the compare register for applications
-            /// requiring precise control beyond percentage-based duty cycles.
-            ///
-            /// # Arguments
-            ///
-            /// * `channel` - PWM channel (0, 1, or 2)
-            /// * `compare_value` - Raw compare value (0 to TOP)
-            ///
-            /// # Returns
-            ///
-            /// `Ok(())` on success, `Err(Error)` if compare value exceeds TOP
-            ///
-            /// # Example
-            ///
-            /// ```no_run
-            /// // Set exact compare value for 33.3% duty cycle with TOP=30000
-            /// timer.set_duty_raw(PwmChannel::Channel0, 10000)?;
-            /// ```
-            pub fn set_duty_raw(
-                &mut self,
-                channel: PwmChannel,
-                compare_value: u32,
-            ) -> Result<(), Error> {
-                if compare_value > self.top_value {
-                    return Err(Error::InvalidDutyCycle);
-                }
-
-                if !self.pwm_enabled {
-                    return Err(Error::InvalidChannel);
-                }
-
-                critical_section::with(|_cs| {
-                    // SAFETY: Compare value has been validated to be <= top_value (<=0xFFFF),
-                    // ensuring it fits within the CC register width.
-                    match channel {
-                        PwmChannel::Channel0 => {
-                            self.timer
-                                .cc0_oc()
-                                .write(|w| unsafe { w.bits(compare_value) });
-                        }
-                        PwmChannel::Channel1 => {
-                            self.timer
-                                .cc1_oc()
-                                .write(|w| unsafe { w.bits(compare_value) });
-                        }
-                        PwmChannel::Channel2 => {
-                            self.timer
-                                .cc2_oc()
-                                .write(|w| unsafe { w.bits(compare_value) });
-                        }
-                    }
-                });
-
-                Ok(())
+            PwmChannel::Channel2 => {
+                I::regs().cc2_cfg().write(|w| w.mode().off());
             }
+        });
+    }
+
+    /// Set raw duty cycle value for a PWM channel
+    ///
+    /// This provides direct access to the compare register for applications
+    /// requiring precise control beyond percentage-based duty cycles.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - PWM channel (0, 1, or 2)
+    /// * `compare_value` - Raw compare value (0 to TOP)
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, `Err(Error)` if compare value exceeds TOP
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// // Set exact compare value for 33.3% duty cycle with TOP=30000
+    /// timer.set_duty_raw(PwmChannel::Channel0, 10000)?;
+    /// ```
+    pub fn set_duty_raw(&mut self, channel: PwmChannel, compare_value: u32) -> Result<(), Error> {
+        if compare_value > self.top_value {
+            return Err(Error::InvalidDutyCycle);
+        }
 
-            /// Enable timer overflow interrupt
-            ///
-            /// This enables the overflow/underflow interrupt, useful for periodic tasks
-            /// synchronized with the timer period.
-            pub fn listen_overflow(&mut self) {
-                critical_section::with(|_cs| {
-                    self.timer.ien().modify(|_, w| w.of().set_bit());
-                });
-            }
+        if !self.pwm_enabled {
+            return Err(Error::InvalidChannel);
+        }
 
-            /// Disable timer overflow interrupt
-            pub fn unlisten_overflow(&mut self) {
-                critical_section::with(|_cs| {
-                    self.timer.ien().modify(|_, w| w.of().clear_bit());
-                });
+        critical_section::with(|_cs| {
+            // SAFETY: Compare value has been validated to be <= top_value (<=0xFFFF),
+            // ensuring it fits within the CC register width.
+            match channel {
+                PwmChannel::Channel0 => {
+                    I::regs()
+                        .cc0_oc()
+                        .write(|w| unsafe { w.bits(compare_value) });
+                }
+                PwmChannel::Channel1 => {
+                    I::regs()
+                        .cc1_oc()
+                        .write(|w| unsafe { w.bits(compare_value) });
+                }
+                PwmChannel::Channel2 => {
+                    I::regs()
+                        .cc2_oc()
+                        .write(|w| unsafe { w.bits(compare_value) });
+                }
             }
+        });
+
+        Ok(())
+    }
+
+    /// Enable timer overflow interrupt
+    ///
+    /// This enables the overflow/underflow interrupt, useful for periodic tasks
+    /// synchronized with the timer period.
+    pub fn listen_overflow(&mut self) {
+        critical_section::with(|_cs| {
+            I::regs().ien().modify(|_, w| w.of().set_bit());
+        });
+    }
+
+    /// Disable timer overflow interrupt
+    pub fn unlisten_overflow(&mut self) {
+        critical_section::with(|_cs| {
+            I::regs().ien().modify(|_, w| w.of().clear_bit());
+        });
+    }
+
+    /// Check if overflow interrupt flag is set
+    pub fn is_overflow(&self) -> bool {
+        I::regs().if_().read().of().bit_is_set()
+    }
+
+    /// Clear overflow interrupt flag
+    pub fn clear_overflow(&mut self) {
+        critical_section::with(|_cs| {
+            I::regs().if_().write(|w| w.of().set_bit());
+        });
+    }
+
+    /// Get the configured PWM frequency
+    pub fn get_frequency(&self) -> Hertz {
+        self.frequency
+    }
+
+    /// Get the TOP value (period)
+    pub fn get_top_value(&self) -> u32 {
+        self.top_value
+    }
+}
 
-            /// Check if overflow interrupt flag is set
-            pub fn is_overflow(&self) -> bool {
-                self.timer.if_().read().of().bit_is_set()
-            }
+/// TIMER0 instance
+pub type Timer0 = Timer<pac::Timer0S>;
 
-            /// Clear overflow interrupt flag
-            pub fn clear_overflow(&mut self) {
-                critical_section::with(|_cs| {
-                    self.timer.if_().write(|w| w.of().set_bit());
-                });
-            }
+/// TIMER1 instance
+pub type Timer1 = Timer<pac::Timer1S>;
 
-            /// Get the configured PWM frequency
-            pub fn get_frequency(&self) -> u32 {
-                self.frequency
-            }
+/// TIMER2 instance
+pub type Timer2 = Timer<pac::Timer2S>;
 
-            /// Get the TOP value (period)
-            pub fn get_top_value(&self) -> u32 {
-                self.top_value
-            }
-        }
-    };
-}
+/// TIMER3 instance
+pub type Timer3 = Timer<pac::Timer3S>;
 
-// Implement all 5 timer instances
-impl_timer!(Timer0, Timer0S, timer0, timer0);
-impl_timer!(Timer1, Timer1S, timer1, timer1);
-impl_timer!(Timer2, Timer2S, timer2, timer2);
-impl_timer!(Timer3, Timer3S, timer3, timer3);
-impl_timer!(Timer4, Timer4S, timer4, timer4);
+/// TIMER4 instance
+pub type Timer4 = Timer<pac::Timer4S>;
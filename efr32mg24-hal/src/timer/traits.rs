@@ -2,15 +2,119 @@
 //!
 //! This module provides trait implementations for embedded-hal compatibility.
 //!
+//! # DelayNs
+//!
+//! Every timer implements [`DelayNs`] by polling its counter, without
+//! reconfiguring it. A timer that is already generating PWM keeps doing so while
+//! it is used for delays; a stopped timer is started. The delay resolution is
+//! one counter tick (HFCLK / (PRESC + 1)).
+//!
+//! Wraps of the counter are detected from the position going backwards and from
+//! the period-end interrupt flag (IF.OF, or IF.UF in up/down mode), which the
+//! delay clears. A whole period that passes between two polls is therefore still
+//! counted, so an interrupt handler that runs for up to two periods does not
+//! shorten the delay. While [`Timer::listen_overflow`] is active the flag belongs
+//! to the application and is left alone; the counter must then be polled at least
+//! once per period.
+//!
 //! Note: embedded-hal v1.0 PWM traits are still evolving. This module provides
 //! a foundation for future trait implementations.
 
+use embedded_hal::delay::DelayNs;
+
+use super::{Instance, Timer};
+use crate::regs;
+
+/// TIMER IF/IEN bit positions
+const IF_OF: u32 = 1 << 0;
+const IF_UF: u32 = 1 << 1;
+
+impl<I: Instance> Timer<I> {
+    /// Counter position within the period, in ticks since the period began
+    fn position(&self) -> u64 {
+        let cnt = I::regs().cnt().read().bits() as u64;
+        if self.up_down && I::regs().status().read().dir().is_down() {
+            2 * self.top_value as u64 - cnt
+        } else {
+            cnt
+        }
+    }
+
+    /// Busy-wait for at least `ticks` counter ticks
+    fn delay_ticks(&mut self, ticks: u64) {
+        if ticks == 0 {
+            return;
+        }
+
+        if I::regs().status().read().running().bit_is_clear() {
+            self.start();
+        }
+
+        let period = if self.up_down {
+            2 * self.top_value as u64
+        } else {
+            self.top_value as u64 + 1
+        }
+        .max(1);
+
+        // The period ends where position() wraps to 0: at TOP when counting up,
+        // at 0 in up/down mode
+        let wrap_flag = if self.up_down { IF_UF } else { IF_OF };
+        let use_flag = I::regs().ien().read().bits() & wrap_flag == 0;
+        if use_flag {
+            regs::clear_bits(I::regs().if_(), wrap_flag);
+        }
+
+        // The first tick observed may complete right after `last` is read,
+        // so wait for one extra tick
+        let ticks = ticks + 1;
+        let mut last = self.position();
+        let mut elapsed = 0u64;
+        while elapsed < ticks {
+            let now = self.position();
+            let wrapped = use_flag && I::regs().if_().read().bits() & wrap_flag != 0;
+
+            elapsed += if now < last {
+                if wrapped {
+                    regs::clear_bits(I::regs().if_(), wrap_flag);
+                }
+                now + period - last
+            } else if wrapped && self.position() >= now {
+                // A whole period passed between two polls. If the position has
+                // wrapped since `now` was read instead, the next poll counts it.
+                regs::clear_bits(I::regs().if_(), wrap_flag);
+                period + now - last
+            } else {
+                now - last
+            };
+            last = now;
+        }
+    }
+}
+
+impl<I: Instance> DelayNs for Timer<I> {
+    fn delay_ns(&mut self, ns: u32) {
+        let ticks = (ns as u64 * self.counter_hz as u64).div_ceil(1_000_000_000);
+        self.delay_ticks(ticks);
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        let ticks = (us as u64 * self.counter_hz as u64).div_ceil(1_000_000);
+        self.delay_ticks(ticks);
+    }
+
+    fn delay_ms(&mut self, ms: u32) {
+        let ticks = (ms as u64 * self.counter_hz as u64).div_ceil(1_000);
+        self.delay_ticks(ticks);
+    }
+}
+
 // Placeholder for future embedded-hal PWM trait implementations
 // When embedded-hal v1.0 PWM traits stabilize, implementations will be added here
 
 // Example structure for future PWM trait:
 //
-// impl embedded_hal::pwm::SetDutyCycle for Timer0 {
+// impl<I: Instance> embedded_hal::pwm::SetDutyCycle for Timer<I> {
 //     fn max_duty_cycle(&self) -> u16 {
 //         self.get_top_value() as u16
 //     }
@@ -3,11 +3,13 @@
 //! This module defines configuration types, modes, and error handling for
 //! the EFR32MG24 TIMER peripherals.
 
+use crate::time::Hertz;
+
 /// Timer configuration
 #[derive(Debug, Clone, Copy)]
 pub struct Config {
-    /// Timer frequency in Hz
-    pub frequency: u32,
+    /// Timer frequency
+    pub frequency: Hertz,
     /// PWM mode configuration (None for basic timer mode)
     pub pwm_mode: Option<PwmMode>,
 }
@@ -17,14 +19,14 @@ impl Config {
     ///
     /// # Arguments
     ///
-    /// * `frequency` - Timer frequency in Hz
+    /// * `frequency` - Timer frequency
     ///
     /// # Example
     ///
     /// ```no_run
-    /// let config = Config::new(1_000_000); // 1 MHz timer
+    /// let config = Config::new(1.MHz()); // 1 MHz timer
     /// ```
-    pub const fn new(frequency: u32) -> Self {
+    pub const fn new(frequency: Hertz) -> Self {
         Self {
             frequency,
             pwm_mode: None,
@@ -40,7 +42,7 @@ impl Config {
     /// # Example
     ///
     /// ```no_run
-    /// let config = Config::new(1_000_000).with_pwm(PwmMode::EdgeAligned);
+    /// let config = Config::new(1.MHz()).with_pwm(PwmMode::EdgeAligned);
     /// ```
     pub const fn with_pwm(mut self, mode: PwmMode) -> Self {
         self.pwm_mode = Some(mode);
@@ -0,0 +1,352 @@
+//! embedded-io-async trait implementations for USART
+//!
+//! Reads sleep on the USART0_RX interrupt (RXDATAV) and writes on the USART0_TX
+//! interrupt (TXBL/TXC). Both vectors must be bound to [`InterruptHandler`] and
+//! the driver created with [`Usart0::new_async`].
+//!
+//! The EUSART UARTs work the same way on their EUSARTn_RX (RXFL) and
+//! EUSARTn_TX (TXFL/TXC) vectors. In low-frequency mode, the EUSART0_RX
+//! interrupt wakes the core from EM2.
+
+use super::{
+    error_pending, half_duplex_unblock_rx, receive_available, transmit_available, Config, Error,
+    Eusart0, Instance, LowFrequencyClock, Rx, Tx, Uart, Usart0,
+};
+use crate::asynch::{self, wait_for};
+use crate::clock::FrozenClocks;
+use crate::interrupt::{
+    self,
+    typelevel::{self, Binding, Handler, Interrupt},
+};
+use crate::pac;
+use crate::regs;
+
+/// USART IF/IEN bit positions
+const IF_TXC: u32 = 1 << 0;
+const IF_TXBL: u32 = 1 << 1;
+const IF_RXDATAV: u32 = 1 << 2;
+
+/// USART and EUSART IF/IEN receive error bit positions
+const IF_RXOF: u32 = 1 << 4;
+const IF_PERR: u32 = 1 << 8;
+const IF_FERR: u32 = 1 << 9;
+
+/// EUSART IF/IEN bit positions
+const IF_TXFL: u32 = 1 << 1;
+const IF_RXFL: u32 = 1 << 2;
+
+/// USART interrupt handler for the async drivers
+///
+/// Bind the TX and RX vectors of the USART0 or EUSART instance to this handler
+/// with [`bind_interrupts!`](crate::bind_interrupts).
+pub struct InterruptHandler {
+    _private: (),
+}
+
+macro_rules! impl_handler {
+    ($($irq:ident),*) => {
+        $(
+            impl Handler<typelevel::$irq> for InterruptHandler {
+                unsafe fn on_interrupt() {
+                    asynch::on_interrupt(pac::Interrupt::$irq);
+                }
+            }
+        )*
+    };
+}
+
+impl_handler!(USART0_RX, USART0_TX, EUSART0_RX, EUSART0_TX, EUSART1_RX, EUSART1_TX);
+
+impl Usart0 {
+    /// Create a new USART0 instance for async use
+    ///
+    /// Configures USART0 like [`Usart0::new`], then enables the USART0_RX and
+    /// USART0_TX interrupts in the NVIC. `_irqs` proves that both vectors are
+    /// bound to [`InterruptHandler`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// bind_interrupts!(struct Irqs {
+    ///     USART0_RX => usart::InterruptHandler;
+    ///     USART0_TX => usart::InterruptHandler;
+    /// });
+    ///
+    /// let usart = Usart0::new_async(dp.usart0_s, Config::default(), &clocks, Irqs);
+    /// ```
+    pub fn new_async(
+        usart: pac::Usart0S,
+        config: Config,
+        clocks: &FrozenClocks,
+        _irqs: impl Binding<typelevel::USART0_RX, InterruptHandler>
+            + Binding<typelevel::USART0_TX, InterruptHandler>,
+    ) -> Self {
+        let usart = Self::new(usart, config, clocks);
+        interrupt::enable::<typelevel::USART0_RX>();
+        interrupt::enable::<typelevel::USART0_TX>();
+        usart
+    }
+}
+
+/// Wait until the TX buffer has room
+async fn wait_txbl(usart: &pac::usart0_s::RegisterBlock) {
+    wait_for(
+        &asynch::USART0_TX,
+        || usart.status().read().txbl().bit_is_set(),
+        || {
+            regs::clear_bits(usart.if_(), IF_TXBL);
+            regs::set_bits(usart.ien(), IF_TXBL);
+        },
+    )
+    .await
+}
+
+/// Wait until the last frame has left the shift register
+async fn wait_txc(usart: &pac::usart0_s::RegisterBlock) {
+    wait_for(
+        &asynch::USART0_TX,
+        || usart.status().read().txc().bit_is_set(),
+        || {
+            regs::clear_bits(usart.if_(), IF_TXC);
+            regs::set_bits(usart.ien(), IF_TXC);
+        },
+    )
+    .await
+}
+
+/// Wait until a received byte or a receive error is available
+///
+/// A frame with a parity or framing error carries its error bits in RXDATAX, so
+/// PERR/FERR flags left in IF when nothing has been received are stale and are
+/// cleared before sleeping. RXOF is reported from IF and is left set.
+async fn wait_rxdatav(usart: &pac::usart0_s::RegisterBlock) {
+    wait_for(
+        &asynch::USART0_RX,
+        || usart.status().read().rxdatav().bit_is_set() || error_pending(usart),
+        || {
+            regs::clear_bits(usart.if_(), IF_RXDATAV | IF_PERR | IF_FERR);
+            regs::set_bits(usart.ien(), IF_RXDATAV | IF_RXOF | IF_PERR | IF_FERR);
+        },
+    )
+    .await
+}
+
+/// Wait for at least one byte (or a receive error), then return everything
+/// already received
+async fn read(usart: &pac::usart0_s::RegisterBlock, buf: &mut [u8]) -> Result<usize, Error> {
+    if buf.is_empty() {
+        return Ok(0);
+    }
+
+    loop {
+        let count = receive_available(usart, buf)?;
+        if count > 0 {
+            return Ok(count);
+        }
+        wait_rxdatav(usart).await;
+    }
+}
+
+/// Wait for room in the TX buffer, then queue as many bytes as fit
+async fn write(usart: &pac::usart0_s::RegisterBlock, buf: &[u8]) -> usize {
+    if buf.is_empty() {
+        return 0;
+    }
+
+    wait_txbl(usart).await;
+    transmit_available(usart, buf)
+}
+
+/// Wait for TX complete, then unblock the receiver on a half-duplex line
+async fn flush(usart: &pac::usart0_s::RegisterBlock) {
+    wait_txc(usart).await;
+    half_duplex_unblock_rx(usart);
+}
+
+/// embedded-io-async Read implementation for USART0
+///
+/// Waits for at least one byte (or a receive error), then returns everything
+/// already received (up to `buf.len()`) without waiting further.
+impl embedded_io_async::Read for Usart0 {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        read(&self.usart, buf).await
+    }
+}
+
+/// embedded-io-async Write implementation for USART0
+///
+/// Waits for room in the TX buffer, then queues as many bytes as fit without
+/// waiting further.
+impl embedded_io_async::Write for Usart0 {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        Ok(write(&self.usart, buf).await)
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        flush(&self.usart).await;
+        Ok(())
+    }
+}
+
+/// embedded-io-async Read implementation for the USART0 receive half
+impl embedded_io_async::Read for Rx {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        read(self.regs(), buf).await
+    }
+}
+
+/// embedded-io-async Write implementation for the USART0 transmit half
+impl embedded_io_async::Write for Tx {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        Ok(write(self.regs(), buf).await)
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        flush(self.regs()).await;
+        Ok(())
+    }
+}
+
+impl<I: Instance> Uart<I> {
+    /// Create a new UART for async use
+    ///
+    /// Configures the EUSART like `new`, then enables its TX and RX
+    /// interrupts in the NVIC. `_irqs` proves that both vectors are bound
+    /// to [`InterruptHandler`].
+    pub fn new_async(
+        eusart: I,
+        config: Config,
+        clocks: &FrozenClocks,
+        _irqs: impl Binding<I::TxInterrupt, InterruptHandler>
+            + Binding<I::RxInterrupt, InterruptHandler>,
+    ) -> Self
+    where
+        InterruptHandler: Handler<I::TxInterrupt> + Handler<I::RxInterrupt>,
+    {
+        let uart = Self::new(eusart, config, clocks);
+        interrupt::enable::<I::RxInterrupt>();
+        interrupt::enable::<I::TxInterrupt>();
+        uart
+    }
+
+    /// Wait until the TX FIFO has room
+    async fn wait_txfl(&mut self) {
+        let eusart = I::regs();
+        wait_for(
+            asynch::waker(I::TxInterrupt::IRQ),
+            || eusart.status().read().txfl().bit_is_set(),
+            || {
+                regs::clear_bits(eusart.if_(), IF_TXFL);
+                regs::set_bits(eusart.ien(), IF_TXFL);
+            },
+        )
+        .await
+    }
+
+    /// Wait until the last frame has left the shift register
+    async fn wait_txc(&mut self) {
+        let eusart = I::regs();
+        wait_for(
+            asynch::waker(I::TxInterrupt::IRQ),
+            || eusart.status().read().txc().bit_is_set(),
+            || {
+                regs::clear_bits(eusart.if_(), IF_TXC);
+                regs::set_bits(eusart.ien(), IF_TXC);
+            },
+        )
+        .await
+    }
+
+    /// Wait until the RX FIFO reaches the watermark or a receive error is
+    /// flagged
+    async fn wait_rxfl(&mut self) {
+        let eusart = I::regs();
+        wait_for(
+            asynch::waker(I::RxInterrupt::IRQ),
+            || self.rx_ready(),
+            || {
+                regs::clear_bits(eusart.if_(), IF_RXFL);
+                regs::set_bits(eusart.ien(), IF_RXFL | IF_RXOF | IF_PERR | IF_FERR);
+            },
+        )
+        .await
+    }
+}
+
+/// embedded-io-async Read implementation for the EUSART UARTs
+///
+/// Waits until the RX FIFO reaches the watermark (or a receive error),
+/// then returns everything readable (up to `buf.len()`) without waiting
+/// further.
+impl<I: Instance> embedded_io_async::Read for Uart<I> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            let count = self.receive_available(buf)?;
+            if count > 0 {
+                return Ok(count);
+            }
+            self.wait_rxfl().await;
+        }
+    }
+}
+
+/// embedded-io-async Write implementation for the EUSART UARTs
+///
+/// Waits for room in the TX FIFO, then queues as many bytes as fit without
+/// waiting further.
+impl<I: Instance> embedded_io_async::Write for Uart<I> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        self.wait_txfl().await;
+        Ok(self.transmit_available(buf))
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        self.wait_txc().await;
+        self.rs485_unblock_rx();
+        Ok(())
+    }
+}
+
+impl Eusart0 {
+    /// Create a new EUSART0 UART in low-frequency mode for async use
+    ///
+    /// Configures EUSART0 like [`Eusart0::new_low_frequency`], then enables its
+    /// TX and RX interrupts in the NVIC. A task awaiting a read sleeps in EM2
+    /// until the RX FIFO reaches the watermark.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// bind_interrupts!(struct Irqs {
+    ///     EUSART0_RX => usart::InterruptHandler;
+    ///     EUSART0_TX => usart::InterruptHandler;
+    /// });
+    ///
+    /// let config = Config::new(9_600.bps());
+    /// let mut console =
+    ///     Eusart0::new_low_frequency_async(dp.eusart0_s, config, &clocks, LowFrequencyClock::Lfxo, Irqs);
+    /// let mut line = [0u8; 32];
+    /// let len = console.read(&mut line).await?;
+    /// ```
+    pub fn new_low_frequency_async(
+        eusart: pac::Eusart0S,
+        config: Config,
+        clocks: &FrozenClocks,
+        clock: LowFrequencyClock,
+        _irqs: impl Binding<typelevel::EUSART0_TX, InterruptHandler>
+            + Binding<typelevel::EUSART0_RX, InterruptHandler>,
+    ) -> Self {
+        let uart = Self::new_low_frequency(eusart, config, clocks, clock);
+        interrupt::enable::<typelevel::EUSART0_RX>();
+        interrupt::enable::<typelevel::EUSART0_TX>();
+        uart
+    }
+}
@@ -0,0 +1,429 @@
+//! Interrupt-driven USART0 with RX/TX ring buffers
+//!
+//! [`BufferedUsart0`] moves bytes between the USART0 hardware buffers and two
+//! caller-provided ring buffers from the USART0_RX and USART0_TX interrupts.
+//! Received bytes are stored as they arrive, regardless of what the main loop is
+//! doing, and writes return as soon as the data is queued.
+//!
+//! Both vectors must be bound to [`BufferedInterruptHandler`]:
+//!
+//! ```no_run
+//! use efr32mg24_hal::{bind_interrupts, usart};
+//!
+//! bind_interrupts!(struct Irqs {
+//!     USART0_RX => usart::BufferedInterruptHandler;
+//!     USART0_TX => usart::BufferedInterruptHandler;
+//! });
+//! ```
+//!
+//! # Flow Control
+//!
+//! With [`FlowControl::RtsCts`](super::FlowControl::RtsCts), the RX interrupt
+//! stops draining the USART receive buffer while the RX ring buffer is full.
+//! The hardware then deasserts RTS, and reception resumes once the application
+//! has read from the ring. CTS pauses transmission in hardware.
+//!
+//! # RS-485
+//!
+//! In RS-485 mode, the TX interrupt blocks the receiver while frames are sent,
+//! and the TXC interrupt unblocks it once the TX buffer has drained.
+//!
+//! # Receive Errors
+//!
+//! Without flow control, a byte that arrives while the RX ring buffer is full is dropped, as is one
+//! lost to a hardware receive overflow (RXOF). Either case is reported once, as
+//! [`Error::Overrun`], by the next read. Frames received with a parity or
+//! framing error are dropped and reported as [`Error::Parity`] or
+//! [`Error::Framing`]. Bytes received before an error stay buffered and are
+//! returned by the following reads.
+
+use core::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+use super::{
+    half_duplex, half_duplex_block_rx, half_duplex_unblock_rx, receive, Config, Error, FlowControl,
+    Usart0,
+};
+use crate::clock::FrozenClocks;
+use crate::interrupt::{
+    self,
+    typelevel::{self, Binding, Handler, Interrupt},
+};
+use crate::pac;
+use crate::regs;
+use crate::ring_buffer::RingBuffer;
+
+/// USART IF/IEN bit positions
+const IF_TXC: u32 = 1 << 0;
+const IF_TXBL: u32 = 1 << 1;
+const IF_RXDATAV: u32 = 1 << 2;
+
+/// Bytes received, waiting to be read
+static RX_BUFFER: RingBuffer = RingBuffer::new();
+/// Bytes written, waiting to be transmitted
+static TX_BUFFER: RingBuffer = RingBuffer::new();
+/// Receive errors not yet reported, as `error_bit()` flags
+static RX_ERRORS: AtomicU8 = AtomicU8::new(0);
+/// RTS/CTS flow control is enabled: leave frames in the hardware buffer while
+/// the RX ring is full
+static RX_FLOW_CONTROL: AtomicBool = AtomicBool::new(false);
+
+/// Flag recording `error` in [`RX_ERRORS`]
+const fn error_bit(error: Error) -> u8 {
+    match error {
+        Error::Overrun => 1 << 0,
+        Error::Parity => 1 << 1,
+        Error::Framing => 1 << 2,
+        Error::Timeout => 1 << 3,
+    }
+}
+
+/// USART0 interrupt handler for [`BufferedUsart0`]
+///
+/// Bind USART0_RX and USART0_TX to this handler with
+/// [`bind_interrupts!`](crate::bind_interrupts).
+pub struct BufferedInterruptHandler {
+    _private: (),
+}
+
+impl Handler<typelevel::USART0_RX> for BufferedInterruptHandler {
+    unsafe fn on_interrupt() {
+        // SAFETY: The handler only reads RXDATAX and IF, and clears IF bits
+        // through the CLR alias. RXDATAX is not read by BufferedUsart0 itself.
+        let usart = unsafe { &*pac::Usart0S::ptr() };
+
+        regs::clear_bits(usart.if_(), IF_RXDATAV);
+        loop {
+            if RX_FLOW_CONTROL.load(Ordering::Relaxed) && RX_BUFFER.is_full() {
+                // Let the hardware buffer fill up so RTS is deasserted; reading
+                // from the ring enables the interrupt again
+                regs::clear_bits(usart.ien(), IF_RXDATAV);
+                break;
+            }
+
+            let error = match receive(usart) {
+                Ok(byte) if RX_BUFFER.push(byte) => continue,
+                Ok(_) => Error::Overrun,
+                Err(nb::Error::WouldBlock) => break,
+                Err(nb::Error::Other(error)) => error,
+            };
+            RX_ERRORS.fetch_or(error_bit(error), Ordering::AcqRel);
+        }
+    }
+}
+
+impl Handler<typelevel::USART0_TX> for BufferedInterruptHandler {
+    unsafe fn on_interrupt() {
+        // SAFETY: The handler only writes TXDATA, which BufferedUsart0 never
+        // writes itself, and updates IF/IEN through the SET/CLR aliases.
+        let usart = unsafe { &*pac::Usart0S::ptr() };
+
+        let blocks_rx = half_duplex(usart);
+        let mut sent = false;
+
+        regs::clear_bits(usart.if_(), IF_TXBL);
+        while usart.status().read().txbl().bit_is_set() {
+            match TX_BUFFER.pop() {
+                // SAFETY: Any 8-bit value is valid for TXDATA
+                Some(byte) => {
+                    if blocks_rx && !sent {
+                        half_duplex_block_rx(usart);
+                    }
+                    usart.txdata().write(|w| unsafe { w.txdata().bits(byte) });
+                    sent = true;
+                }
+                None => break,
+            }
+        }
+
+        if TX_BUFFER.is_empty() {
+            regs::clear_bits(usart.ien(), IF_TXBL);
+            // A write may have queued data after the check above
+            if !TX_BUFFER.is_empty() {
+                regs::set_bits(usart.ien(), IF_TXBL);
+            }
+        }
+
+        if blocks_rx {
+            if sent {
+                // Unblock the receiver from the TXC interrupt once these frames
+                // have gone out
+                regs::clear_bits(usart.if_(), IF_TXC);
+                regs::set_bits(usart.ien(), IF_TXC);
+            } else if TX_BUFFER.is_empty() && usart.status().read().txc().bit_is_set() {
+                regs::clear_bits(usart.ien(), IF_TXC);
+                regs::clear_bits(usart.if_(), IF_TXC);
+                half_duplex_unblock_rx(usart);
+            }
+        }
+    }
+}
+
+/// Interrupt-driven USART0 with RX/TX ring buffers
+///
+/// Implements the non-blocking [`embedded_io::ReadReady`]/[`embedded_io::WriteReady`]
+/// and [`embedded_hal_nb::serial`] traits, and [`embedded_io::Read`]/[`embedded_io::Write`],
+/// which only block while the RX buffer is empty or the TX buffer is full.
+pub struct BufferedUsart0 {
+    usart: Usart0,
+}
+
+impl BufferedUsart0 {
+    /// Create a new buffered USART0 instance
+    ///
+    /// Configures USART0 like [`Usart0::new`], attaches the ring buffers and
+    /// enables the USART0_RX and USART0_TX interrupts. `_irqs` proves that both
+    /// vectors are bound to [`BufferedInterruptHandler`].
+    ///
+    /// # Arguments
+    ///
+    /// * `usart` - USART0_S peripheral from PAC
+    /// * `config` - USART configuration (baud rate, data bits, parity, stop bits,
+    ///   flow control, RS-485 mode)
+    /// * `clocks` - Frozen clock configuration for baud rate calculation
+    /// * `_irqs` - Binding of USART0_RX and USART0_TX to [`BufferedInterruptHandler`]
+    /// * `tx_buffer` - Storage for bytes waiting to be transmitted
+    /// * `rx_buffer` - Storage for received bytes waiting to be read
+    ///
+    /// # Panics
+    ///
+    /// Panics if either buffer is empty.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// static mut TX_BUF: [u8; 64] = [0; 64];
+    /// static mut RX_BUF: [u8; 256] = [0; 256];
+    ///
+    /// let usart = BufferedUsart0::new(
+    ///     dp.usart0_s,
+    ///     Config::new(115_200.bps()),
+    ///     &clocks,
+    ///     Irqs,
+    ///     unsafe { &mut *core::ptr::addr_of_mut!(TX_BUF) },
+    ///     unsafe { &mut *core::ptr::addr_of_mut!(RX_BUF) },
+    /// );
+    /// ```
+    pub fn new(
+        usart: pac::Usart0S,
+        config: Config,
+        clocks: &FrozenClocks,
+        _irqs: impl Binding<typelevel::USART0_RX, BufferedInterruptHandler>
+            + Binding<typelevel::USART0_TX, BufferedInterruptHandler>,
+        tx_buffer: &'static mut [u8],
+        rx_buffer: &'static mut [u8],
+    ) -> Self {
+        assert!(!tx_buffer.is_empty() && !rx_buffer.is_empty());
+
+        let usart = Usart0::new(usart, config, clocks);
+
+        // SAFETY: The buffers are 'static and exclusively borrowed, and the
+        // interrupts that use the rings are not enabled yet. USART0_S is a
+        // singleton, so no other BufferedUsart0 can use the rings.
+        unsafe {
+            TX_BUFFER.init(tx_buffer.as_mut_ptr(), tx_buffer.len());
+            RX_BUFFER.init(rx_buffer.as_mut_ptr(), rx_buffer.len());
+        }
+        RX_ERRORS.store(0, Ordering::Relaxed);
+        RX_FLOW_CONTROL.store(
+            matches!(config.flow_control, FlowControl::RtsCts(_)),
+            Ordering::Relaxed,
+        );
+
+        let periph = &usart.usart;
+        regs::clear_bits(periph.if_(), IF_RXDATAV | IF_TXBL);
+        regs::set_bits(periph.ien(), IF_RXDATAV);
+
+        interrupt::enable::<typelevel::USART0_RX>();
+        interrupt::enable::<typelevel::USART0_TX>();
+
+        Self { usart }
+    }
+
+    /// Number of received bytes waiting to be read
+    pub fn rx_len(&self) -> usize {
+        RX_BUFFER.len()
+    }
+
+    /// Free space in the TX buffer, in bytes
+    pub fn tx_free(&self) -> usize {
+        TX_BUFFER.capacity() - TX_BUFFER.len()
+    }
+
+    /// Release the interrupt-driven mode
+    ///
+    /// Disables the USART0 interrupts and detaches the ring buffers. Bytes still
+    /// queued for transmission are dropped; call
+    /// [`flush`](embedded_io::Write::flush) first to send them.
+    pub fn free(self) -> Usart0 {
+        typelevel::USART0_RX::disable();
+        typelevel::USART0_TX::disable();
+        regs::clear_bits(self.usart.usart.ien(), IF_RXDATAV | IF_TXBL | IF_TXC);
+
+        TX_BUFFER.deinit();
+        RX_BUFFER.deinit();
+
+        self.usart
+    }
+
+    /// Take one pending receive error report
+    fn take_error(&self) -> Result<(), Error> {
+        let pending = RX_ERRORS.load(Ordering::Acquire);
+        let error = [Error::Overrun, Error::Parity, Error::Framing]
+            .into_iter()
+            .find(|&error| pending & error_bit(error) != 0);
+
+        match error {
+            Some(error) => {
+                RX_ERRORS.fetch_and(!error_bit(error), Ordering::AcqRel);
+                Err(error)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Start the TX interrupt after queueing data
+    fn kick_tx(&self) {
+        let periph = &self.usart.usart;
+        regs::set_bits(periph.ien(), IF_TXBL);
+        // Raise the flag by software as well: TXBL may have been cleared while
+        // the hardware buffer was already empty, in which case it would not be
+        // set again
+        regs::set_bits(periph.if_(), IF_TXBL);
+    }
+
+    /// Read buffered bytes without blocking
+    fn read_buffered(&mut self, buf: &mut [u8]) -> usize {
+        let mut count = 0;
+        for slot in buf.iter_mut() {
+            match RX_BUFFER.pop() {
+                Some(byte) => *slot = byte,
+                None => break,
+            }
+            count += 1;
+        }
+
+        if count > 0 && RX_FLOW_CONTROL.load(Ordering::Relaxed) {
+            // Resume draining the hardware buffer if the ring was full
+            regs::set_bits(self.usart.usart.ien(), IF_RXDATAV);
+        }
+        count
+    }
+
+    /// Queue bytes for transmission without blocking
+    fn write_buffered(&mut self, buf: &[u8]) -> usize {
+        let count = buf.iter().take_while(|&&byte| TX_BUFFER.push(byte)).count();
+        if count > 0 {
+            self.kick_tx();
+        }
+        count
+    }
+}
+
+impl embedded_io::ErrorType for BufferedUsart0 {
+    type Error = Error;
+}
+
+impl embedded_io::Read for BufferedUsart0 {
+    /// Read received bytes
+    ///
+    /// Blocks only while the RX buffer is empty. A pending receive error is
+    /// returned before the buffered bytes.
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            self.take_error()?;
+            let count = self.read_buffered(buf);
+            if count > 0 {
+                return Ok(count);
+            }
+            core::hint::spin_loop();
+        }
+    }
+}
+
+impl embedded_io::ReadReady for BufferedUsart0 {
+    fn read_ready(&mut self) -> Result<bool, Self::Error> {
+        self.take_error()?;
+        Ok(!RX_BUFFER.is_empty())
+    }
+}
+
+impl embedded_io::Write for BufferedUsart0 {
+    /// Queue bytes for transmission
+    ///
+    /// Blocks only while the TX buffer is full.
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            let count = self.write_buffered(buf);
+            if count > 0 {
+                return Ok(count);
+            }
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Wait until the TX buffer is empty and the last frame has been sent
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        while !TX_BUFFER.is_empty() {
+            core::hint::spin_loop();
+        }
+        self.usart.flush();
+        Ok(())
+    }
+}
+
+/// `core::fmt::Write` implementation for the buffered USART0
+///
+/// Queues the formatted text, blocking only while the TX buffer is full.
+impl core::fmt::Write for BufferedUsart0 {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        embedded_io::Write::write_all(self, s.as_bytes()).map_err(|_| core::fmt::Error)
+    }
+}
+
+impl embedded_io::WriteReady for BufferedUsart0 {
+    fn write_ready(&mut self) -> Result<bool, Self::Error> {
+        Ok(!TX_BUFFER.is_full())
+    }
+}
+
+impl embedded_hal_nb::serial::ErrorType for BufferedUsart0 {
+    type Error = Error;
+}
+
+impl embedded_hal_nb::serial::Read<u8> for BufferedUsart0 {
+    fn read(&mut self) -> nb::Result<u8, Self::Error> {
+        self.take_error()?;
+        let mut byte = [0];
+        if self.read_buffered(&mut byte) == 1 {
+            Ok(byte[0])
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+}
+
+impl embedded_hal_nb::serial::Write<u8> for BufferedUsart0 {
+    fn write(&mut self, word: u8) -> nb::Result<(), Self::Error> {
+        if self.write_buffered(&[word]) == 1 {
+            Ok(())
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        if TX_BUFFER.is_empty() && self.usart.usart.status().read().txc().bit_is_set() {
+            Ok(())
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+}
@@ -0,0 +1,586 @@
+//! USART0 LDMA transfers and circular DMA reception
+//!
+//! The LDMA channel is paced by the USART0 TXBL (transmit) or RXDATAV (receive)
+//! request, routed through LDMAXBAR, and moves one byte per request between
+//! memory and TXDATA/RXDATA. Transfers longer than one descriptor (2048 bytes)
+//! are split into consecutive descriptors.
+//!
+//! [`CircularRx`] keeps a channel receiving into a buffer that the LDMA wraps
+//! around by reloading its own descriptor. The application polls how far the
+//! LDMA has written and reads the bytes behind it.
+//!
+//! [`DmaWriter`] transmits from two alternating halves of a buffer: while the
+//! LDMA sends one, writes fill the other, so the CPU does not wait for the
+//! transmission.
+//!
+//! # Receive Errors
+//!
+//! The LDMA reads RXDATA directly, so a frame with a parity or framing error is
+//! stored like any other byte. The error is reported from the IF flags by the
+//! next call, after the bytes it belongs to.
+
+use core::marker::PhantomData;
+
+use super::{
+    flush_tx, half_duplex, half_duplex_block_rx, rx_idle, take_error_flags, Error, Tx, Usart0,
+    IF_FERR, IF_PERR, IF_RXOF,
+};
+use crate::dma::{Channel, Descriptor, Request, TransferSize, MAX_TRANSFER_UNITS};
+use crate::pac;
+
+impl Usart0 {
+    /// Write bytes through LDMA channel `N` (blocking)
+    ///
+    /// The CPU only sets up each descriptor and waits for it to complete. Like
+    /// [`write`](Self::write), this returns once the last byte is queued (or, in
+    /// RS-485 mode, sent); use [`flush`](Self::flush) to wait until it has been
+    /// sent.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let mut ch0 = dma.channel0();
+    /// usart.write_dma(&mut ch0, &firmware_image);
+    /// usart.flush();
+    /// ```
+    pub fn write_dma<const N: u8>(&mut self, channel: &mut Channel<N>, data: &[u8]) {
+        write_dma(&self.usart, channel, data);
+    }
+
+    /// Fill `buffer` through LDMA channel `N` (blocking)
+    ///
+    /// Returns once `buffer.len()` bytes have been received, or the receive
+    /// error raised while receiving them.
+    pub fn read_dma<const N: u8>(
+        &mut self,
+        channel: &mut Channel<N>,
+        buffer: &mut [u8],
+    ) -> Result<(), Error> {
+        receive_dma(&self.usart, channel, buffer, false).map(|_| ())
+    }
+
+    /// Receive a variable-length packet through LDMA channel `N` (blocking)
+    ///
+    /// Returns the number of bytes received when the RX line goes idle after the
+    /// packet, or when `buffer` is full. Idle detection must be enabled with
+    /// [`enable_idle_detection`](Self::enable_idle_detection); without it, this
+    /// behaves like [`read_dma`](Self::read_dma).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// usart.enable_idle_detection(20);
+    /// let mut packet = [0u8; 256];
+    /// let len = usart.read_dma_until_idle(&mut ch1, &mut packet)?;
+    /// handle(&packet[..len]);
+    /// ```
+    pub fn read_dma_until_idle<const N: u8>(
+        &mut self,
+        channel: &mut Channel<N>,
+        buffer: &mut [u8],
+    ) -> Result<usize, Error> {
+        receive_dma(&self.usart, channel, buffer, true)
+    }
+
+    /// Start receiving continuously into `buffer` through LDMA channel `N`
+    ///
+    /// The LDMA wraps around to the start of `buffer` when it reaches the end.
+    /// Bytes are read back through the returned [`CircularRx`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buffer` is empty or longer than 2048 bytes.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// static mut RX_DMA: [u8; 512] = [0; 512];
+    ///
+    /// let mut rx = usart.read_dma_circular(dma.channel1(), unsafe { &mut *core::ptr::addr_of_mut!(RX_DMA) });
+    /// let mut chunk = [0u8; 64];
+    /// loop {
+    ///     let count = rx.read(&mut chunk)?;
+    ///     // Process chunk[..count]
+    /// }
+    /// ```
+    pub fn read_dma_circular<const N: u8>(
+        &mut self,
+        channel: Channel<N>,
+        buffer: &'static mut [u8],
+    ) -> CircularRx<'_, N> {
+        start_circular(&self.usart, channel, buffer)
+    }
+}
+
+/// Write bytes through LDMA channel `N` (blocking)
+pub(super) fn write_dma<const N: u8>(
+    usart: &pac::usart0_s::RegisterBlock,
+    channel: &mut Channel<N>,
+    data: &[u8],
+) {
+    let txdata = usart.txdata().as_ptr() as u32;
+    half_duplex_block_rx(usart);
+
+    for chunk in data.chunks(MAX_TRANSFER_UNITS) {
+        let descriptor = Descriptor::peripheral(
+            TransferSize::Byte,
+            chunk.len(),
+            chunk.as_ptr() as u32,
+            true,
+            txdata,
+            false,
+        );
+
+        // SAFETY: `chunk` stays borrowed until the channel reports done below.
+        unsafe { channel.start(Request::Usart0TxBufferLevel, descriptor, false) };
+        while !channel.is_done() {
+            core::hint::spin_loop();
+        }
+        channel.clear_done();
+    }
+
+    if half_duplex(usart) {
+        flush_tx(usart);
+    }
+}
+
+/// Receive into `buffer`, optionally stopping when the RX line goes idle
+pub(super) fn receive_dma<const N: u8>(
+    usart: &pac::usart0_s::RegisterBlock,
+    channel: &mut Channel<N>,
+    buffer: &mut [u8],
+    until_idle: bool,
+) -> Result<usize, Error> {
+    let rxdata = usart.rxdata().as_ptr() as u32;
+    if until_idle {
+        // Discard an idle period that ended a previous packet
+        rx_idle(usart);
+    }
+
+    let mut count = 0;
+    for chunk in buffer.chunks_mut(MAX_TRANSFER_UNITS) {
+        let base = chunk.as_mut_ptr() as u32;
+        let descriptor =
+            Descriptor::peripheral(TransferSize::Byte, chunk.len(), rxdata, false, base, true);
+
+        // SAFETY: `chunk` stays borrowed until the channel reports done, or is
+        // stopped, below.
+        unsafe { channel.start(Request::Usart0RxDataValid, descriptor, false) };
+        loop {
+            if channel.is_done() {
+                channel.clear_done();
+                count += chunk.len();
+                break;
+            }
+
+            if until_idle && rx_idle(usart) {
+                channel.stop();
+                channel.clear_done();
+                count += (channel.destination() - base) as usize;
+                take_error_flags(usart)?;
+                return Ok(count);
+            }
+
+            core::hint::spin_loop();
+        }
+    }
+
+    take_error_flags(usart)?;
+    Ok(count)
+}
+
+/// Start receiving continuously into `buffer` through LDMA channel `N`
+///
+/// The caller picks `'a` to keep the USART0 receiver borrowed for as long as
+/// the returned [`CircularRx`] lives.
+pub(super) fn start_circular<'a, const N: u8>(
+    usart: &pac::usart0_s::RegisterBlock,
+    mut channel: Channel<N>,
+    buffer: &'static mut [u8],
+) -> CircularRx<'a, N> {
+    assert!(
+        !buffer.is_empty() && buffer.len() <= MAX_TRANSFER_UNITS,
+        "circular DMA buffer must hold 1 to 2048 bytes"
+    );
+
+    let len = buffer.len();
+    let buffer = buffer.as_mut_ptr();
+    let descriptor = Descriptor::peripheral(
+        TransferSize::Byte,
+        len,
+        usart.rxdata().as_ptr() as u32,
+        false,
+        buffer as u32,
+        true,
+    );
+
+    // Errors raised before reception started belong to no data in the buffer
+    let _ = take_error_flags(usart);
+
+    // SAFETY: `buffer` is `'static` and is only handed back by
+    // `CircularRx::stop` after the channel has been stopped.
+    unsafe { channel.start(Request::Usart0RxDataValid, descriptor, true) };
+
+    CircularRx {
+        _usart: PhantomData,
+        channel,
+        buffer,
+        len,
+        read: 0,
+        laps: 0,
+        counted_early: false,
+    }
+}
+
+/// Continuous USART0 reception into a circular DMA buffer
+///
+/// Created by [`Usart0::read_dma_circular`] or [`Rx::read_dma_circular`](super::Rx::read_dma_circular). The LDMA writes received bytes
+/// into the buffer without CPU involvement; [`read`](Self::read) copies out the
+/// bytes it has written since the last read.
+///
+/// # Overruns
+///
+/// If the LDMA laps the reader, the unread data is lost. The next read reports
+/// [`Error::Overrun`] and continues from the current LDMA position. The LDMA
+/// done flag counts laps, so an overrun by more than a whole extra lap between
+/// two reads may go unnoticed; size the buffer for the longest gap between
+/// reads.
+///
+/// Dropping a `CircularRx` stops the channel. Use [`stop`](Self::stop) to get
+/// the channel and buffer back.
+pub struct CircularRx<'a, const N: u8> {
+    /// Keeps the USART0 receiver borrowed while the LDMA reads RXDATA
+    _usart: PhantomData<&'a mut ()>,
+    channel: Channel<N>,
+    buffer: *mut u8,
+    len: usize,
+    /// Index of the next byte to read
+    read: usize,
+    /// Number of times the LDMA has wrapped past the reader's lap
+    laps: usize,
+    /// A wrap was counted from the position before its done flag was seen
+    counted_early: bool,
+}
+
+impl<const N: u8> CircularRx<'_, N> {
+    /// USART0 registers
+    fn regs(&self) -> &pac::usart0_s::RegisterBlock {
+        // SAFETY: The `Usart0` or `Rx` this was created from stays mutably
+        // borrowed, and only the receive side (RXDATA, IF via its CLR alias) is
+        // touched here.
+        unsafe { &*pac::Usart0S::ptr() }
+    }
+
+    /// Buffer index the LDMA writes next
+    pub fn position(&self) -> usize {
+        let offset = self.channel.destination().wrapping_sub(self.buffer as u32) as usize;
+        if offset >= self.len {
+            0
+        } else {
+            offset
+        }
+    }
+
+    /// Number of received bytes not read yet
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Overrun`] if the LDMA has overwritten unread bytes.
+    pub fn available(&mut self) -> Result<usize, Error> {
+        let wrapped = self.channel.is_done();
+        let write = self.position();
+
+        if wrapped {
+            self.channel.clear_done();
+            if self.counted_early {
+                self.counted_early = false;
+            } else {
+                self.laps += 1;
+            }
+        }
+
+        if self.laps == 0 && write < self.read {
+            // The LDMA wrapped between reading the done flag and the position
+            self.laps = 1;
+            self.counted_early = true;
+        }
+
+        let pending = self.laps * self.len + write - self.read;
+        if pending > self.len {
+            self.read = write;
+            self.laps = 0;
+            return Err(Error::Overrun);
+        }
+
+        Ok(pending)
+    }
+
+    /// Read received bytes (non-blocking)
+    ///
+    /// Returns the number of bytes copied into `buf`, `Ok(0)` if nothing new has
+    /// been received, or a pending receive error.
+    ///
+    /// Bytes already in the buffer are returned first; a parity, framing or
+    /// overflow error is reported by the first call that finds no new bytes.
+    pub fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        let count = self.available()?.min(buf.len());
+
+        for byte in &mut buf[..count] {
+            // SAFETY: `read` is below `len`, and the LDMA has finished writing this
+            // byte: it lies behind the LDMA position.
+            *byte = unsafe { self.buffer.add(self.read).read_volatile() };
+            self.read += 1;
+            if self.read == self.len {
+                self.read = 0;
+                self.laps -= 1;
+            }
+        }
+
+        if count == 0 {
+            take_error_flags(self.regs())?;
+        }
+
+        Ok(count)
+    }
+
+    /// Check whether the RX line has gone idle after a frame
+    ///
+    /// See [`Usart0::rx_idle`]. Read the buffer after this returns `true` to get
+    /// the complete packet.
+    pub fn rx_idle(&mut self) -> bool {
+        rx_idle(self.regs())
+    }
+
+    /// Stop reception and return the channel and buffer
+    pub fn stop(self) -> (Channel<N>, &'static mut [u8]) {
+        let mut this = core::mem::ManuallyDrop::new(self);
+        this.channel.stop();
+        this.channel.clear_done();
+
+        // SAFETY: `this` is never dropped, so the channel is moved out exactly
+        // once. The LDMA no longer writes the buffer, which came from a
+        // `&'static mut [u8]` of `len` bytes.
+        unsafe {
+            (
+                core::ptr::read(&this.channel),
+                core::slice::from_raw_parts_mut(this.buffer, this.len),
+            )
+        }
+    }
+}
+
+impl<const N: u8> Drop for CircularRx<'_, N> {
+    fn drop(&mut self) {
+        self.channel.stop();
+        self.channel.clear_done();
+    }
+}
+
+impl<const N: u8> embedded_io::ErrorType for CircularRx<'_, N> {
+    type Error = Error;
+}
+
+impl<const N: u8> embedded_io::Read for CircularRx<'_, N> {
+    /// Read received bytes
+    ///
+    /// Blocks only while no new bytes have been received.
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            let count = CircularRx::read(self, buf)?;
+            if count > 0 {
+                return Ok(count);
+            }
+            core::hint::spin_loop();
+        }
+    }
+}
+
+impl<const N: u8> embedded_io::ReadReady for CircularRx<'_, N> {
+    fn read_ready(&mut self) -> Result<bool, Self::Error> {
+        // A pending receive error is returned by the next read without blocking
+        let error = self.regs().if_().read().bits() & (IF_RXOF | IF_PERR | IF_FERR) != 0;
+        Ok(self.available()? > 0 || error)
+    }
+}
+
+/// Double-buffered USART0 transmission through LDMA channel `N`
+///
+/// Writes are copied into one half of the buffer. [`send`](Self::send), or
+/// filling that half, hands it to the LDMA and continues in the other half, so
+/// the CPU only waits when both halves are in use. Nothing is transmitted until
+/// a half is sent.
+///
+/// In RS-485 mode, the receiver stays blocked until [`flush`](Self::flush).
+///
+/// # Example
+///
+/// ```no_run
+/// static mut TX_BUFFER: [u8; 256] = [0; 256];
+///
+/// let (tx, rx) = usart.split();
+/// let buffer = unsafe { &mut *core::ptr::addr_of_mut!(TX_BUFFER) };
+/// let mut writer = DmaWriter::new(tx, dma.channel0(), buffer);
+/// writeln!(writer, "temperature: {} C", temperature).unwrap();
+/// writer.send();
+/// ```
+pub struct DmaWriter<const N: u8> {
+    tx: Tx,
+    channel: Channel<N>,
+    buffer: *mut u8,
+    /// Length of each half
+    half_len: usize,
+    /// Half being filled (0 or 1)
+    active: usize,
+    /// Bytes written to the active half
+    filled: usize,
+    /// The other half is being transmitted
+    in_flight: bool,
+}
+
+// SAFETY: The buffer pointer comes from a `&'static mut [u8]` that the writer
+// owns exclusively until `free`.
+unsafe impl<const N: u8> Send for DmaWriter<N> {}
+
+impl<const N: u8> DmaWriter<N> {
+    /// Transmit from `buffer`, split into two halves, through `channel`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buffer` is shorter than 2 or longer than 4096 bytes.
+    pub fn new(tx: Tx, channel: Channel<N>, buffer: &'static mut [u8]) -> Self {
+        assert!(
+            buffer.len() >= 2 && buffer.len() <= 2 * MAX_TRANSFER_UNITS,
+            "DMA writer buffer must hold 2 to 4096 bytes"
+        );
+
+        Self {
+            tx,
+            channel,
+            half_len: buffer.len() / 2,
+            buffer: buffer.as_mut_ptr(),
+            active: 0,
+            filled: 0,
+            in_flight: false,
+        }
+    }
+
+    /// Start of the half being filled
+    fn active_half(&self) -> *mut u8 {
+        // SAFETY: `active` is 0 or 1, so the offset stays within the buffer.
+        unsafe { self.buffer.add(self.active * self.half_len) }
+    }
+
+    /// Wait until the half being transmitted has been handed to the USART
+    fn wait(&mut self) {
+        if self.in_flight {
+            while !self.channel.is_done() {
+                core::hint::spin_loop();
+            }
+            self.channel.clear_done();
+            self.in_flight = false;
+        }
+    }
+
+    /// Copy `bytes` into the buffer, sending each half as it fills up
+    pub fn write(&mut self, mut bytes: &[u8]) {
+        while !bytes.is_empty() {
+            let count = (self.half_len - self.filled).min(bytes.len());
+            // SAFETY: The active half is not being read by the LDMA, and
+            // `filled + count` does not exceed its length.
+            unsafe {
+                core::ptr::copy_nonoverlapping(
+                    bytes.as_ptr(),
+                    self.active_half().add(self.filled),
+                    count,
+                );
+            }
+            self.filled += count;
+            bytes = &bytes[count..];
+
+            if self.filled == self.half_len {
+                self.send();
+            }
+        }
+    }
+
+    /// Start transmitting the bytes written so far (non-blocking)
+    ///
+    /// Waits only if the previous half is still being transmitted.
+    pub fn send(&mut self) {
+        if self.filled == 0 {
+            return;
+        }
+
+        self.wait();
+        let usart = self.tx.regs();
+        half_duplex_block_rx(usart);
+        let descriptor = Descriptor::peripheral(
+            TransferSize::Byte,
+            self.filled,
+            self.active_half() as u32,
+            true,
+            usart.txdata().as_ptr() as u32,
+            false,
+        );
+
+        // SAFETY: The buffer is `'static`, and this half is not written again
+        // until `wait` has seen the transfer complete.
+        unsafe {
+            self.channel
+                .start(Request::Usart0TxBufferLevel, descriptor, false)
+        };
+        self.in_flight = true;
+        self.active ^= 1;
+        self.filled = 0;
+    }
+
+    /// Send the bytes written so far and wait until they have been transmitted
+    pub fn flush(&mut self) {
+        self.send();
+        self.wait();
+        flush_tx(self.tx.regs());
+    }
+
+    /// Flush, then return the transmit half, channel and buffer
+    pub fn free(mut self) -> (Tx, Channel<N>, &'static mut [u8]) {
+        self.flush();
+        // SAFETY: No transfer is in flight, and the buffer came from a
+        // `&'static mut [u8]` of at least `2 * half_len` bytes.
+        let buffer = unsafe { core::slice::from_raw_parts_mut(self.buffer, 2 * self.half_len) };
+        (self.tx, self.channel, buffer)
+    }
+}
+
+impl<const N: u8> embedded_io::ErrorType for DmaWriter<N> {
+    type Error = Error;
+}
+
+impl<const N: u8> embedded_io::Write for DmaWriter<N> {
+    /// Copy all of `buf` into the buffer
+    ///
+    /// Blocks only while both halves are in use.
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        DmaWriter::write(self, buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        DmaWriter::flush(self);
+        Ok(())
+    }
+}
+
+/// `core::fmt::Write` implementation for the DMA writer
+///
+/// Copies the formatted text into the buffer; call [`DmaWriter::send`] to
+/// transmit it.
+impl<const N: u8> core::fmt::Write for DmaWriter<N> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        DmaWriter::write(self, s.as_bytes());
+        Ok(())
+    }
+}
@@ -0,0 +1,664 @@
+//! EUSART0/EUSART1 UART drivers
+//!
+//! The EUSART is the Series 2 "enhanced" USART: a UART (or SPI, see
+//! [`Spi1`](crate::spi::Spi1)/[`Spi2`](crate::spi::Spi2)) with 16-entry RX and
+//! TX FIFOs. It takes the same [`Config`] as [`Usart0`](super::Usart0).
+//!
+//! # RX FIFO Watermark
+//!
+//! STATUS.RXFL (and the RX interrupt) is raised once the RX FIFO holds
+//! [`Config::rx_fifo_watermark`] frames. The FIFO does not report a smaller
+//! fill level, so with a watermark above 1, received bytes become readable in
+//! blocks of that many frames. Use the default of 1 for byte streams, and a
+//! higher watermark for fixed-size messages so the core sleeps until a whole
+//! message has arrived.
+//!
+//! # Low-Frequency Mode
+//!
+//! EUSART0 can be clocked from LFXO or LFRCO ([`Eusart0::new_low_frequency`]).
+//! It then keeps receiving in EM2, and its RX interrupt wakes the core. Without
+//! oversampling, a 32.768 kHz clock supports up to 9600 baud.
+//!
+//! # Receive Errors
+//!
+//! The EUSART reports parity and framing errors in IF only, not per frame. The
+//! frame stays in the FIFO, and the error is reported by the next read that
+//! starts a new block of frames.
+
+use super::{
+    clkdiv_baudrate, Config, DataBits, Error, FlowControl, Instance, LowFrequencyClock, Parity,
+    Rs485, StopBits,
+};
+use crate::clock::FrozenClocks;
+use crate::pac;
+use crate::regs;
+
+/// EUSART IF bit positions
+const IF_RXOF: u32 = 1 << 4;
+const IF_PERR: u32 = 1 << 8;
+const IF_FERR: u32 = 1 << 9;
+const IF_AUTOBAUDDONE: u32 = 1 << 24;
+
+/// GPIO EUSARTn_ROUTEEN bit positions
+const ROUTEEN_CSPEN: u32 = 1 << 0;
+const ROUTEEN_RTSPEN: u32 = 1 << 1;
+
+/// LFRCO frequency
+const LFRCO_HZ: u32 = 32_768;
+
+/// Frames of free RX FIFO space below which RTS is deasserted
+///
+/// Leaves room for the frames the remote end sends before it sees RTS.
+const RTS_FIFO_SPACE: u8 = 4;
+
+/// CLKDIV.DIV field mask (bits 22:3)
+const CLKDIV_DIV_MASK: u32 = 0x007F_FFF8;
+
+/// UART driver on EUSART0 or EUSART1
+///
+/// Use the [`Eusart0`] and [`Eusart1`] aliases to name a concrete driver.
+/// Received data becomes readable once the RX FIFO holds
+/// [`Config::rx_fifo_watermark`] frames. Parity and framing errors are
+/// reported by the next read that starts a new block of frames.
+pub struct Uart<I: Instance> {
+    _eusart: I,
+    /// EUSART clock frequency
+    clock_hz: u32,
+    /// RX FIFO watermark, in frames
+    rx_watermark: u8,
+    /// Frames known to be in the RX FIFO
+    rx_pending: u8,
+}
+
+impl<I: Instance> Uart<I> {
+    /// Create a new UART clocked from EM01GRPCCLK
+    ///
+    /// # Arguments
+    ///
+    /// * `eusart` - EUSART0_S or EUSART1_S peripheral from PAC
+    /// * `config` - UART configuration (baud rate, frame format, flow
+    ///   control, RS-485 mode, RX FIFO watermark)
+    /// * `clocks` - Frozen clock configuration for baud rate calculation
+    pub fn new(eusart: I, config: Config, clocks: &FrozenClocks) -> Self {
+        clocks.enable_peripheral_clock(|cmu| I::enable_clock(cmu));
+        Self::select_clock(clocks, None);
+
+        Self::init(eusart, config, clocks.hfclk().to_Hz(), false)
+    }
+
+    /// Select the EUSART clock: EM01GRPCCLK, or a low-frequency oscillator
+    fn select_clock(clocks: &FrozenClocks, clock: Option<LowFrequencyClock>) {
+        clocks.enable_peripheral_clock(|cmu| I::select_clock(cmu, clock));
+    }
+
+    /// Configure and enable the peripheral
+    fn init(eusart: I, config: Config, clock_hz: u32, low_frequency: bool) -> Self {
+        // The configuration registers can only be written while disabled
+        let regs = I::regs();
+        Self::disable(regs);
+
+        let rx_watermark = config.rx_fifo_watermark.clamp(1, 16);
+
+        regs.cfg0().write(|w| {
+            w.sync().async_();
+            if low_frequency {
+                // Majority voting needs oversampling
+                w.ovs().disable();
+                w.mvdis().set_bit();
+            } else {
+                w.ovs().x16();
+            }
+            w
+        });
+
+        regs.cfg1().write(|w| {
+            w.rxfiw().set(rx_watermark - 1);
+            w.rtsrxfw().set(RTS_FIFO_SPACE - 1);
+            w.ctsen()
+                .bit(matches!(config.flow_control, FlowControl::RtsCts(_)))
+        });
+
+        regs.framecfg().write(|w| {
+            match config.data_bits {
+                DataBits::Eight => w.databits().eight(),
+                DataBits::Nine => w.databits().nine(),
+            };
+            match config.parity {
+                Parity::None => w.parity().none(),
+                Parity::Even => w.parity().even(),
+                Parity::Odd => w.parity().odd(),
+            };
+            match config.stop_bits {
+                StopBits::One => w.stopbits().one(),
+                StopBits::Two => w.stopbits().two(),
+            }
+        });
+
+        Self::configure_flow_control(config.flow_control);
+        Self::configure_rs485(regs, config.rs485);
+
+        regs.en().write(|w| w.en().set_bit());
+
+        // baud = fEUSART / (oversample * (1 + DIV / 256)), computed in 64
+        // bits since 256 * fEUSART overflows a u32
+        let oversample = if low_frequency { 1 } else { 16 };
+        let clkdiv = (256 * u64::from(clock_hz)
+            / (oversample * u64::from(config.baudrate.to_bps())))
+        .saturating_sub(256);
+        regs.clkdiv()
+            .write(|w| unsafe { w.bits(clkdiv as u32 & CLKDIV_DIV_MASK) });
+        Self::wait_sync(regs);
+        Self::enable_tx_rx(regs);
+
+        Self {
+            _eusart: eusart,
+            clock_hz,
+            rx_watermark,
+            rx_pending: 0,
+        }
+    }
+
+    /// Disable the peripheral, flushing the FIFOs
+    fn disable(eusart: &pac::eusart0_s::RegisterBlock) {
+        eusart.en().write(|w| w.en().clear_bit());
+        while eusart.en().read().disabling().bit_is_set() {
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Enable the transmitter and receiver
+    fn enable_tx_rx(eusart: &pac::eusart0_s::RegisterBlock) {
+        eusart.cmd().write(|w| w.txen().set_bit().rxen().set_bit());
+        Self::wait_sync(eusart);
+        while eusart.status().read().rxens().bit_is_clear()
+            || eusart.status().read().txens().bit_is_clear()
+        {
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Set or clear CFG0.AUTOBAUDEN
+    ///
+    /// CFG0 can only be written while disabled, which also empties the
+    /// FIFOs; CLKDIV is kept. Frames queued for transmission are sent first,
+    /// so they are not lost with the TX FIFO.
+    fn set_autobaud(&mut self, enable: bool) {
+        while I::regs().status().read().txfcnt().bits() != 0
+            || I::regs().status().read().txidle().bit_is_clear()
+        {
+            core::hint::spin_loop();
+        }
+
+        let clkdiv = I::regs().clkdiv().read().bits();
+        Self::disable(I::regs());
+        I::regs().cfg0().modify(|_, w| w.autobauden().bit(enable));
+        I::regs().en().write(|w| w.en().set_bit());
+        I::regs().clkdiv().write(|w| unsafe { w.bits(clkdiv) });
+        Self::wait_sync(I::regs());
+        Self::enable_tx_rx(I::regs());
+
+        self.rx_pending = 0;
+        regs::clear_bits(
+            I::regs().if_(),
+            IF_RXOF | IF_PERR | IF_FERR | IF_AUTOBAUDDONE,
+        );
+    }
+
+    /// Wait until CLKDIV and CMD writes have reached the EUSART clock domain
+    fn wait_sync(eusart: &pac::eusart0_s::RegisterBlock) {
+        while eusart.syncbusy().read().bits() != 0 {
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Route the CTS/RTS pins, or disable flow control
+    ///
+    /// CTS itself is enabled in CFG1 together with the RX FIFO watermarks.
+    fn configure_flow_control(flow_control: FlowControl) {
+        // SAFETY: The EUSART route registers belong to the EUSART owner, and
+        // ROUTEEN is updated through its SET/CLR aliases.
+        let gpio = unsafe { &*pac::GpioS::ptr() };
+
+        match flow_control {
+            FlowControl::None => I::disable_routes(gpio, ROUTEEN_RTSPEN),
+            FlowControl::RtsCts(pins) => {
+                I::route_cts_rts(gpio, pins.cts, pins.rts);
+                I::enable_routes(gpio, ROUTEEN_RTSPEN);
+            }
+        }
+    }
+
+    /// Route DE to the chip select output and enable automatic chip
+    /// select, or disable RS-485 mode
+    fn configure_rs485(eusart: &pac::eusart0_s::RegisterBlock, rs485: Option<Rs485>) {
+        // SAFETY: The EUSART CS route register belongs to the EUSART owner,
+        // and ROUTEEN is updated through its SET/CLR aliases.
+        let gpio = unsafe { &*pac::GpioS::ptr() };
+
+        match rs485 {
+            None => {
+                eusart
+                    .cfg2()
+                    .write(|w| w.autocs().clear_bit().csinv().clear_bit());
+                I::disable_routes(gpio, ROUTEEN_CSPEN);
+            }
+            Some(rs485) => {
+                // CSSETUP/CSHOLD count whole bit times, up to 7
+                eusart.timingcfg().write(|w| {
+                    w.cssetup().set(rs485.setup_bits.min(7));
+                    w.cshold().set(rs485.hold_bits.min(7))
+                });
+
+                I::route_cs(gpio, rs485.de);
+                I::enable_routes(gpio, ROUTEEN_CSPEN);
+
+                // DE is active high
+                eusart
+                    .cfg2()
+                    .write(|w| w.autocs().set_bit().csinv().set_bit());
+            }
+        }
+    }
+
+    /// Check whether RS-485 mode is enabled
+    fn rs485_enabled(&self) -> bool {
+        I::regs().cfg2().read().autocs().bit_is_set()
+    }
+
+    /// Block the receiver before transmitting in RS-485 mode
+    fn rs485_block_rx(&self) {
+        if self.rs485_enabled() && I::regs().status().read().rxblock().bit_is_clear() {
+            I::regs().cmd().write(|w| w.rxblocken().set_bit());
+            Self::wait_sync(I::regs());
+        }
+    }
+
+    /// Unblock the receiver once transmission is complete in RS-485 mode
+    pub(super) fn rs485_unblock_rx(&self) {
+        if self.rs485_enabled() {
+            I::regs().cmd().write(|w| w.rxblockdis().set_bit());
+            Self::wait_sync(I::regs());
+        }
+    }
+
+    /// Report and clear a pending receive error
+    fn take_error_flags(&self) -> Result<(), Error> {
+        let flags = I::regs().if_().read().bits() & (IF_RXOF | IF_PERR | IF_FERR);
+        if flags == 0 {
+            return Ok(());
+        }
+
+        regs::clear_bits(I::regs().if_(), flags);
+        if flags & IF_RXOF != 0 {
+            Err(Error::Overrun)
+        } else if flags & IF_PERR != 0 {
+            Err(Error::Parity)
+        } else {
+            Err(Error::Framing)
+        }
+    }
+
+    /// Check whether a read would return data or an error without blocking
+    pub(super) fn rx_ready(&self) -> bool {
+        self.rx_pending > 0
+            || I::regs().status().read().rxfl().bit_is_set()
+            || I::regs().if_().read().bits() & (IF_RXOF | IF_PERR | IF_FERR) != 0
+    }
+
+    /// Read a single byte (non-blocking)
+    ///
+    /// Returns `WouldBlock` until the RX FIFO reaches the watermark, then
+    /// one frame per call until that block has been read.
+    ///
+    /// # Errors
+    ///
+    /// Parity, framing and overrun errors are reported once, before the
+    /// next block of frames is read.
+    pub fn read_byte(&mut self) -> nb::Result<u8, Error> {
+        self.read_word().map(|word| word as u8)
+    }
+
+    /// Read a 9-bit frame (non-blocking)
+    ///
+    /// Like [`read_byte`](Self::read_byte), keeping the ninth data bit of
+    /// frames with [`DataBits::Nine`].
+    pub fn read_word(&mut self) -> nb::Result<u16, Error> {
+        if self.rx_pending == 0 {
+            self.take_error_flags()?;
+            if I::regs().status().read().rxfl().bit_is_clear() {
+                return Err(nb::Error::WouldBlock);
+            }
+            self.rx_pending = self.rx_watermark;
+        }
+
+        self.rx_pending -= 1;
+        Ok(I::regs().rxdata().read().rxdata().bits() & 0x1FF)
+    }
+
+    /// Read everything already received, up to `buf.len()` bytes
+    ///
+    /// Returns the bytes read before a receive error first, and the error on
+    /// the next call.
+    pub(super) fn receive_available(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        let mut count = 0;
+        while count < buf.len() {
+            if count > 0
+                && self.rx_pending == 0
+                && I::regs().if_().read().bits() & (IF_RXOF | IF_PERR | IF_FERR) != 0
+            {
+                break;
+            }
+
+            match self.read_byte() {
+                Ok(byte) => {
+                    buf[count] = byte;
+                    count += 1;
+                }
+                Err(nb::Error::WouldBlock) => break,
+                Err(nb::Error::Other(error)) => return Err(error),
+            }
+        }
+        Ok(count)
+    }
+
+    /// Queue as many of `bytes` as fit in the TX FIFO without waiting
+    pub(super) fn transmit_available(&mut self, bytes: &[u8]) -> usize {
+        if I::regs().status().read().txfl().bit_is_clear() {
+            return 0;
+        }
+
+        self.rs485_block_rx();
+        let mut count = 0;
+        for &byte in bytes {
+            if I::regs().status().read().txfl().bit_is_clear() {
+                break;
+            }
+            I::regs()
+                .txdata()
+                .write(|w| unsafe { w.bits(u32::from(byte)) });
+            count += 1;
+        }
+        count
+    }
+
+    /// Queue one frame of up to 9 bits if the TX FIFO has room
+    fn try_transmit(&mut self, word: u16) -> nb::Result<(), Error> {
+        if I::regs().status().read().txfl().bit_is_clear() {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        self.rs485_block_rx();
+        I::regs()
+            .txdata()
+            .write(|w| unsafe { w.bits(u32::from(word & 0x1FF)) });
+        Ok(())
+    }
+
+    /// Check for TX complete, then unblock the receiver in RS-485 mode
+    fn try_flush(&mut self) -> nb::Result<(), Error> {
+        if I::regs().status().read().txc().bit_is_set() {
+            self.rs485_unblock_rx();
+            Ok(())
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+
+    /// Write a 9-bit frame (blocking)
+    ///
+    /// Like [`write_byte`](Self::write_byte), for frames with
+    /// [`DataBits::Nine`]; bits above the ninth are ignored.
+    pub fn write_word(&mut self, word: u16) {
+        while self.try_transmit(word).is_err() {
+            core::hint::spin_loop();
+        }
+
+        if self.rs485_enabled() {
+            self.flush();
+        }
+    }
+
+    /// Write a single byte (blocking)
+    ///
+    /// Waits for room in the TX FIFO. In RS-485 mode, also waits until the
+    /// byte has been sent.
+    pub fn write_byte(&mut self, byte: u8) {
+        self.write(&[byte]);
+    }
+
+    /// Write a slice of bytes (blocking)
+    ///
+    /// In RS-485 mode, the receiver is blocked during the transmission, and
+    /// this returns once the last byte has been sent.
+    pub fn write(&mut self, bytes: &[u8]) {
+        let mut sent = 0;
+        while sent < bytes.len() {
+            sent += self.transmit_available(&bytes[sent..]);
+        }
+
+        if self.rs485_enabled() {
+            self.flush();
+        }
+    }
+
+    /// Flush the TX FIFO (blocking)
+    ///
+    /// Waits until all queued data has been sent (TX complete). In RS-485
+    /// mode, the receiver is unblocked afterwards.
+    pub fn flush(&mut self) {
+        while I::regs().status().read().txc().bit_is_clear() {
+            core::hint::spin_loop();
+        }
+        self.rs485_unblock_rx();
+    }
+
+    /// Measure the baud rate on the next received character
+    ///
+    /// Enables the EUSART autobaud detector: it times the next frame, which
+    /// must be the sync character 0x55 (`'U'`), and reprograms CLKDIV to
+    /// match. Poll for the result with [`poll_autobaud`](Self::poll_autobaud),
+    /// or use [`autobaud`](Self::autobaud) to wait for it.
+    ///
+    /// CFG0.AUTOBAUDEN can only be changed while the EUSART is disabled, so
+    /// this and the `poll_autobaud` call that returns the result briefly
+    /// disable it. Both first wait until the frames already written have been
+    /// sent (with CTS flow control, until the remote end lets them through);
+    /// data already in the RX FIFO is discarded.
+    pub fn start_autobaud(&mut self) {
+        self.set_autobaud(true);
+    }
+
+    /// Check whether the sync character has been measured (non-blocking)
+    ///
+    /// Returns `config` with the detected baud rate once CLKDIV has been
+    /// reprogrammed (STATUS.AUTOBAUDDONE), or `None` while still waiting.
+    /// The sync character itself is discarded.
+    pub fn poll_autobaud(&mut self, config: Config) -> Option<Config> {
+        if I::regs().status().read().autobauddone().bit_is_clear() {
+            return None;
+        }
+
+        let oversample = if I::regs().cfg0().read().ovs().is_disable() {
+            1
+        } else {
+            16
+        };
+        let div = I::regs().clkdiv().read().div().bits();
+        self.set_autobaud(false);
+
+        Some(config.baudrate(clkdiv_baudrate(self.clock_hz, oversample, div)))
+    }
+
+    /// Detect the baud rate from a received sync character (blocking)
+    ///
+    /// Waits for the remote end to send 0x55 (`'U'`), reprograms CLKDIV to
+    /// its baud rate and returns `config` with the detected rate.
+    pub fn autobaud(&mut self, config: Config) -> Config {
+        self.start_autobaud();
+        loop {
+            if let Some(config) = self.poll_autobaud(config) {
+                return config;
+            }
+            core::hint::spin_loop();
+        }
+    }
+}
+
+impl<I: Instance> embedded_hal_nb::serial::ErrorType for Uart<I> {
+    type Error = Error;
+}
+
+impl<I: Instance> embedded_io::ErrorType for Uart<I> {
+    type Error = Error;
+}
+
+/// embedded-hal-nb Write implementation
+impl<I: Instance> embedded_hal_nb::serial::Write<u8> for Uart<I> {
+    fn write(&mut self, word: u8) -> nb::Result<(), Self::Error> {
+        self.try_transmit(u16::from(word))
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        self.try_flush()
+    }
+}
+
+/// embedded-hal-nb Read implementation
+impl<I: Instance> embedded_hal_nb::serial::Read<u8> for Uart<I> {
+    fn read(&mut self) -> nb::Result<u8, Self::Error> {
+        self.read_byte()
+    }
+}
+
+/// embedded-hal-nb Write implementation for 9-bit frames
+impl<I: Instance> embedded_hal_nb::serial::Write<u16> for Uart<I> {
+    fn write(&mut self, word: u16) -> nb::Result<(), Self::Error> {
+        self.try_transmit(word)
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        self.try_flush()
+    }
+}
+
+/// embedded-hal-nb Read implementation for 9-bit frames
+impl<I: Instance> embedded_hal_nb::serial::Read<u16> for Uart<I> {
+    fn read(&mut self) -> nb::Result<u16, Self::Error> {
+        self.read_word()
+    }
+}
+
+/// embedded-io Read implementation
+///
+/// Blocks until at least one byte (or a receive error) is available, then
+/// returns everything already received, up to `buf.len()`.
+impl<I: Instance> embedded_io::Read for Uart<I> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            let count = self.receive_available(buf)?;
+            if count > 0 {
+                return Ok(count);
+            }
+            core::hint::spin_loop();
+        }
+    }
+}
+
+/// embedded-io ReadReady implementation
+impl<I: Instance> embedded_io::ReadReady for Uart<I> {
+    fn read_ready(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.rx_ready())
+    }
+}
+
+/// embedded-io Write implementation
+///
+/// Blocks until the TX FIFO has room, then queues as many bytes as fit.
+impl<I: Instance> embedded_io::Write for Uart<I> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            let count = self.transmit_available(buf);
+            if count > 0 {
+                return Ok(count);
+            }
+            core::hint::spin_loop();
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Uart::flush(self);
+        Ok(())
+    }
+}
+
+/// `core::fmt::Write` implementation
+///
+/// Writes the formatted text with `write` (blocking).
+impl<I: Instance> core::fmt::Write for Uart<I> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        Uart::write(self, s.as_bytes());
+        Ok(())
+    }
+}
+
+/// embedded-io WriteReady implementation
+impl<I: Instance> embedded_io::WriteReady for Uart<I> {
+    fn write_ready(&mut self) -> Result<bool, Self::Error> {
+        Ok(I::regs().status().read().txfl().bit_is_set())
+    }
+}
+
+/// EUSART0 UART
+pub type Eusart0 = Uart<pac::Eusart0S>;
+
+/// EUSART1 UART
+pub type Eusart1 = Uart<pac::Eusart1S>;
+
+impl Eusart0 {
+    /// Create a new EUSART0 UART clocked from a low-frequency oscillator
+    ///
+    /// Oversampling is disabled, so the EUSART runs directly from LFXO or LFRCO
+    /// and keeps receiving in EM2. With a 32.768 kHz clock, baud rates up to 9600
+    /// are supported.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the baud rate is above a third of the clock frequency.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let config = Config::new(9_600.bps());
+    /// let console = Eusart0::new_low_frequency(dp.eusart0_s, config, &clocks, LowFrequencyClock::Lfxo);
+    /// ```
+    pub fn new_low_frequency(
+        eusart: pac::Eusart0S,
+        config: Config,
+        clocks: &FrozenClocks,
+        clock: LowFrequencyClock,
+    ) -> Self {
+        let clock_hz = match clock {
+            LowFrequencyClock::Lfxo => clocks.lfclk().to_Hz(),
+            LowFrequencyClock::Lfrco => LFRCO_HZ,
+        };
+        assert!(
+            config.baudrate.to_bps() <= clock_hz / 3,
+            "baud rate too high for the low-frequency clock"
+        );
+
+        clocks.enable_peripheral_clock(|cmu| {
+            cmu.clken1().modify(|_, w| w.eusart0().set_bit());
+        });
+        Self::select_clock(clocks, Some(clock));
+
+        Self::init(eusart, config, clock_hz, true)
+    }
+}
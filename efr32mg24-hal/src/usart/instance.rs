@@ -0,0 +1,154 @@
+//! EUSART peripheral instances
+//!
+//! [`Instance`] is implemented for EUSART0 and EUSART1. It ties each of them to
+//! its register block, CMU clock enable and clock select bits, interrupt
+//! vectors, LDMA requests and GPIO route registers, so the [`Uart`](super::Uart)
+//! driver is written once for both.
+//!
+//! USART0 is not an [`Instance`] and keeps its own driver,
+//! [`Usart0`](super::Usart0). Unlike SPI, where both register layouts are
+//! reduced to a handful of status and data accesses, a UART driver is mostly
+//! configuration: USART0 sets up frames in CTRL/FRAME/TIMING with a 2-frame
+//! buffer, the EUSARTs in CFG0-CFG2/FRAMECFG with 16-entry FIFOs, written only
+//! while disabled, and the features built on them differ (smart card, IrDA,
+//! multiprocessor mode, split halves, buffered and DMA transfers are only
+//! implemented on USART0). A common driver would match on the layout in
+//! nearly every method, so the two drivers share [`Config`](super::Config),
+//! the error types and the baud-rate helpers instead.
+
+use super::LowFrequencyClock;
+use crate::dma::Request;
+use crate::interrupt::typelevel;
+use crate::pac;
+
+mod sealed {
+    use super::LowFrequencyClock;
+    use crate::dma::Request;
+    use crate::pac;
+
+    pub trait Instance {
+        /// LDMA request for a received frame (RXFL)
+        const RX_REQUEST: Request;
+
+        /// LDMA request for room in the TX FIFO (TXFL)
+        const TX_REQUEST: Request;
+
+        /// Register block
+        fn regs() -> &'static pac::eusart0_s::RegisterBlock;
+
+        /// Enable the peripheral clock in the CMU
+        fn enable_clock(cmu: &pac::CmuS);
+
+        /// Select the peripheral clock: EM01GRPCCLK, or a low-frequency oscillator
+        fn select_clock(cmu: &pac::CmuS, clock: Option<LowFrequencyClock>);
+
+        /// Route CTS and RTS to `(port, pin)`
+        fn route_cts_rts(gpio: &pac::gpio_s::RegisterBlock, cts: (u8, u8), rts: (u8, u8));
+
+        /// Route the chip select (RS-485 DE) to `(port, pin)`
+        fn route_cs(gpio: &pac::gpio_s::RegisterBlock, cs: (u8, u8));
+
+        /// Set the ROUTEEN bits in `mask`
+        fn enable_routes(gpio: &pac::gpio_s::RegisterBlock, mask: u32);
+
+        /// Clear the ROUTEEN bits in `mask`
+        fn disable_routes(gpio: &pac::gpio_s::RegisterBlock, mask: u32);
+    }
+}
+
+/// A peripheral that runs the [`Uart`](super::Uart) driver: EUSART0 or EUSART1
+pub trait Instance: sealed::Instance + 'static {
+    /// RX interrupt vector
+    type RxInterrupt: typelevel::Interrupt;
+
+    /// TX interrupt vector
+    type TxInterrupt: typelevel::Interrupt;
+}
+
+macro_rules! impl_instance {
+    (
+        $Periph:ident, $clk:ident, $routeen:ident, $ctsroute:ident, $rtsroute:ident,
+        $csroute:ident, $RX:ident, $TX:ident, $RxRequest:ident, $TxRequest:ident
+    ) => {
+        impl Instance for pac::$Periph {
+            type RxInterrupt = typelevel::$RX;
+            type TxInterrupt = typelevel::$TX;
+        }
+
+        impl sealed::Instance for pac::$Periph {
+            const RX_REQUEST: Request = Request::$RxRequest;
+            const TX_REQUEST: Request = Request::$TxRequest;
+
+            fn regs() -> &'static pac::eusart0_s::RegisterBlock {
+                // SAFETY: EUSART1 has the same register layout as EUSART0, and the
+                // register block is only accessed by the driver that owns the
+                // peripheral.
+                unsafe { &*(pac::$Periph::ptr() as *const pac::eusart0_s::RegisterBlock) }
+            }
+
+            fn enable_clock(cmu: &pac::CmuS) {
+                cmu.clken1().modify(|_, w| w.$clk().set_bit());
+            }
+
+            fn select_clock(cmu: &pac::CmuS, clock: Option<LowFrequencyClock>) {
+                impl_instance!(@select_clock $Periph, cmu, clock);
+            }
+
+            fn route_cts_rts(gpio: &pac::gpio_s::RegisterBlock, cts: (u8, u8), rts: (u8, u8)) {
+                gpio.$ctsroute()
+                    .write(|w| unsafe { w.port().bits(cts.0).pin().bits(cts.1) });
+                gpio.$rtsroute()
+                    .write(|w| unsafe { w.port().bits(rts.0).pin().bits(rts.1) });
+            }
+
+            fn route_cs(gpio: &pac::gpio_s::RegisterBlock, cs: (u8, u8)) {
+                gpio.$csroute()
+                    .write(|w| unsafe { w.port().bits(cs.0).pin().bits(cs.1) });
+            }
+
+            fn enable_routes(gpio: &pac::gpio_s::RegisterBlock, mask: u32) {
+                crate::regs::set_bits(gpio.$routeen(), mask);
+            }
+
+            fn disable_routes(gpio: &pac::gpio_s::RegisterBlock, mask: u32) {
+                crate::regs::clear_bits(gpio.$routeen(), mask);
+            }
+        }
+    };
+    (@select_clock Eusart0S, $cmu:ident, $clock:ident) => {
+        $cmu.eusart0clkctrl().write(|w| match $clock {
+            None => w.clksel().em01grpcclk(),
+            Some(LowFrequencyClock::Lfxo) => w.clksel().lfxo(),
+            Some(LowFrequencyClock::Lfrco) => w.clksel().lfrco(),
+        });
+    };
+    // EUSART1 is always clocked from EM01GRPCCLK
+    (@select_clock Eusart1S, $cmu:ident, $clock:ident) => {
+        let _ = ($cmu, $clock);
+    };
+}
+
+impl_instance!(
+    Eusart0S,
+    eusart0,
+    eusart0_routeen,
+    eusart0_ctsroute,
+    eusart0_rtsroute,
+    eusart0_csroute,
+    EUSART0_RX,
+    EUSART0_TX,
+    Eusart0RxFifoLevel,
+    Eusart0TxFifoLevel
+);
+impl_instance!(
+    Eusart1S,
+    eusart1,
+    eusart1_routeen,
+    eusart1_ctsroute,
+    eusart1_rtsroute,
+    eusart1_csroute,
+    EUSART1_RX,
+    EUSART1_TX,
+    Eusart1RxFifoLevel,
+    Eusart1TxFifoLevel
+);
@@ -7,7 +7,21 @@
 //! - 8 or 9 data bits
 //! - None, even, or odd parity
 //! - 1 or 2 stop bits
+//! - RTS/CTS hardware flow control ([`FlowControl`])
+//! - RS-485 half-duplex mode with automatic driver enable ([`Rs485`])
+//! - 9-bit frames ([`Usart0::read_word`], [`Usart0::write_word`]) and multiprocessor
+//!   address matching on a multidrop bus ([`Config::multiprocessor_address`])
+//! - ISO 7816 smart-card mode with ATR reception ([`SmartCard`]) and IrDA
+//!   modulation ([`Irda`]) on USART0
 //! - Blocking TX/RX operations
+//! - Async `embedded_io_async::{Read, Write}` (`async` feature)
+//! - Interrupt-driven operation with RX/TX ring buffers ([`BufferedUsart0`])
+//! - LDMA transfers, circular DMA reception ([`CircularRx`]) and RX idle detection
+//! - EUSART0/EUSART1 UARTs with 16-entry FIFOs ([`Eusart0`], [`Eusart1`]), aliases of
+//!   the generic [`Uart`] driver over the sealed [`Instance`] trait
+//! - Automatic baud-rate detection on a 0x55 sync character ([`Usart0::autobaud`])
+//! - `core::fmt::Write` for formatted output (`write!`), blocking or through a
+//!   double-buffered DMA writer ([`DmaWriter`])
 //!
 //! ## Hardware Registers
 //!
@@ -20,11 +34,105 @@
 //! - **STATUS**: Status flags (TXBL, RXDATAV, TXC, etc.)
 //! - **TXDATA**: Transmit data register (8-bit write-only)
 //! - **RXDATA**: Receive data register (8-bit read-only)
+//! - **RXDATAX/RXDATAXP**: Received frame with its PERR/FERR flags (pop/peek)
+//! - **IF**: Interrupt flags (RXOF receive overflow, PERR, FERR, TCMP1)
+//! - **TIMECMP1**: Timer comparator 1 (RX idle detection)
+//! - **IRCTRL**: IrDA modulation (enable, pulse width, receive filter)
+//!
+//! ## Receive Errors
+//!
+//! Every receive path checks the frame it reads:
+//!
+//! - A frame received with a parity or framing error is consumed and reported as
+//!   [`Error::Parity`] or [`Error::Framing`].
+//! - A receive overflow (IF.RXOF) is reported once as [`Error::Overrun`]; the
+//!   frames received before it are still returned by the following reads.
+//!
+//! Multi-byte reads return the bytes received before an error first, and the
+//! error on the next call.
+//!
+//! ## Flow Control
+//!
+//! With [`FlowControl::RtsCts`], the CTS and RTS signals are routed to the given
+//! pins. The transmitter waits while CTS is deasserted, and RTS is deasserted
+//! while the receive buffer is full. [`BufferedUsart0`] stops draining the
+//! receive buffer while its RX ring is full, so RTS also holds off the remote
+//! end instead of bytes being dropped.
+//!
+//! ## RS-485
+//!
+//! With [`Config::rs485`], the transceiver's DE pin is driven from the chip
+//! select output, asserted automatically around each transmission with the
+//! configured setup and hold times (see [`Rs485::setup_bits`] for the values
+//! each peripheral supports). The receiver is blocked from the first
+//! byte written until [`Usart0::flush`] sees the transmission complete, so the
+//! echo is discarded; the blocking writes flush by themselves.
+//! [`Config::modbus_rtu_frame_gap_bits`] gives the Modbus RTU 3.5 character
+//! gap for [`Usart0::enable_idle_detection`].
+//!
+//! ## 9-Bit Frames and Multiprocessor Mode
+//!
+//! With [`DataBits::Nine`], [`Usart0::read_word`] and [`Usart0::write_word`]
+//! (and the embedded-hal-nb `Read<u16>`/`Write<u16>` traits) carry the ninth
+//! bit; the byte APIs drop it. On a multidrop bus the ninth bit marks address
+//! frames: the master selects a node with [`Usart0::write_address`], and a node
+//! created with [`Config::multiprocessor_address`] ignores data frames until
+//! its own address arrives. [`Usart0::listen_for_address`] ignores data again
+//! until the next time it is addressed.
+//!
+//! ## Smart Card and IrDA
+//!
+//! [`Config::smart_card`] switches USART0 to the ISO 7816 character frame
+//! (even parity, 2 stop bits) at the baud rate given by the card clock and
+//! Fi/Di. Received characters with a parity error are NACKed, characters the
+//! card NACKs are retransmitted, and the receiver listens to TX internally, so
+//! both are routed to the card's open-drain I/O pin. The card clock comes from a
+//! TIMER PWM channel. [`Usart0::read_atr`] receives the Answer-to-Reset and
+//! follows the card's convention; [`Usart0::set_baudrate`] applies the rate
+//! agreed by PPS. Like RS-485, the receiver is blocked while transmitting.
+//!
+//! [`Config::irda`] enables the IrDA SIR modulator: 0 bits are sent as pulses
+//! of 1/16 to 4/16 of a bit time ([`IrdaPulseWidth`]), with an optional receive
+//! glitch filter.
+//!
+//! ## DMA and Idle Detection
+//!
+//! [`Usart0::write_dma`] and [`Usart0::read_dma`] move data through an LDMA
+//! channel paced by the USART0 TXBL/RXDATAV requests. [`Usart0::read_dma_circular`]
+//! keeps a channel receiving into a circular buffer whose position the
+//! application polls.
+//!
+//! The end of a variable-length packet is detected as a gap on the RX line:
+//! [`Usart0::enable_idle_detection`] starts timer comparator 1 at the end of
+//! every received frame, and [`Usart0::rx_idle`] reports when no new frame has
+//! started within the given number of bit times. [`Usart0::read_dma_until_idle`]
+//! combines both.
+//!
+//! DMA reception reads RXDATA directly, so receive errors are reported from the
+//! IF flags after the data they belong to has been transferred.
+//!
+//! ## EUSART UARTs
+//!
+//! [`Eusart0`] and [`Eusart1`] take the same [`Config`] as [`Usart0`], plus an
+//! RX FIFO watermark ([`Config::rx_fifo_watermark`]): received data becomes
+//! readable, and the RX interrupt fires, once the FIFO holds that many frames.
+//! With a watermark above 1, bytes are therefore read in blocks of that size.
+//!
+//! EUSART0 can also run from LFXO or LFRCO ([`Eusart0::new_low_frequency`]),
+//! keeping a 9600 baud console alive in EM2; its RX interrupt wakes the core.
+//!
+//! ## Split Halves
+//!
+//! [`Usart0::split`] returns a [`Tx`] and an [`Rx`] that can be owned by
+//! different tasks, each with its own blocking, DMA, embedded-hal-nb and
+//! embedded-io (and, with the `async` feature, embedded-io-async) API.
+//! [`Usart0::join`] returns the full driver.
 //!
 //! ## Example
 //!
 //! ```no_run
 //! use efr32mg24_hal::usart::{Usart0, Config};
+//! use efr32mg24_hal::prelude::*;
 //! use efr32mg24_hal::clock::Clocks;
 //!
 //! let dp = pac::Peripherals::take().unwrap();
@@ -37,19 +145,441 @@
 //! usart.write_byte(b'H');
 //!
 //! // Receive a byte
-//! if let Some(data) = usart.read_byte() {
+//! if let Ok(data) = usart.read_byte() {
 //!     // Process received data
 //! }
 //! ```
 
+#[cfg(feature = "async")]
+mod asynch;
+mod buffered;
+mod dma;
+mod eusart;
+mod instance;
+mod multiprocessor;
+mod smartcard;
+mod split;
 mod traits;
 mod types;
 
-pub use types::{Config, DataBits, Error, Parity, StopBits};
+#[cfg(feature = "async")]
+pub use asynch::InterruptHandler;
+pub use buffered::{BufferedInterruptHandler, BufferedUsart0};
+pub use dma::{CircularRx, DmaWriter};
+pub use eusart::{Eusart0, Eusart1, Uart};
+pub use instance::Instance;
+pub use smartcard::{Atr, MAX_ATR_LEN};
+pub use split::{Rx, Tx};
+pub use types::{
+    Config, DataBits, Error, FlowControl, Irda, IrdaPulseWidth, LowFrequencyClock, Parity, Rs485,
+    RtsCtsPins, SmartCard, StopBits,
+};
 
 use crate::clock::FrozenClocks;
+use crate::regs;
+use crate::time::Bps;
 use efr32mg24_pac as pac;
 
+/// USART IF receive error bits
+const IF_RXOF: u32 = 1 << 4;
+const IF_PERR: u32 = 1 << 8;
+const IF_FERR: u32 = 1 << 9;
+
+/// GPIO USART0_ROUTEEN CS and RTS pin enable bits
+const ROUTEEN_CSPEN: u32 = 1 << 0;
+const ROUTEEN_RTSPEN: u32 = 1 << 1;
+
+/// USART IF timer comparator 1 bit, used for RX idle detection
+const IF_TCMP1: u32 = 1 << 15;
+
+/// CLKDIV.AUTOBAUDEN bit position
+const CLKDIV_AUTOBAUDEN: u32 = 1 << 31;
+
+/// USART0 asynchronous oversampling factor (CTRL.OVS = X16)
+const OVERSAMPLE: u64 = 16;
+
+/// Receive one frame of up to 9 bits, checking for receive errors
+///
+/// Reports and clears a pending receive overflow first. A frame with a parity or
+/// framing error is consumed and reported; its IF flag is cleared. In
+/// multiprocessor mode, address frames are consumed to match our address.
+pub(super) fn receive_word(usart: &pac::usart0_s::RegisterBlock) -> nb::Result<u16, Error> {
+    if usart.if_().read().rxof().bit_is_set() {
+        regs::clear_bits(usart.if_(), IF_RXOF);
+        return Err(nb::Error::Other(Error::Overrun));
+    }
+
+    loop {
+        if usart.status().read().rxdatav().bit_is_clear() {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        let frame = usart.rxdatax().read();
+        if frame.perr().bit_is_set() {
+            regs::clear_bits(usart.if_(), IF_PERR);
+            return Err(nb::Error::Other(Error::Parity));
+        } else if frame.ferr().bit_is_set() {
+            regs::clear_bits(usart.if_(), IF_FERR);
+            return Err(nb::Error::Other(Error::Framing));
+        }
+
+        let word = frame.rxdata().bits();
+        if !multiprocessor::take_address_frame(usart, word) {
+            return Ok(word);
+        }
+    }
+}
+
+/// Receive one frame, checking for receive errors
+///
+/// See [`receive_word`]; the ninth data bit is dropped.
+pub(super) fn receive(usart: &pac::usart0_s::RegisterBlock) -> nb::Result<u8, Error> {
+    receive_word(usart).map(|word| word as u8)
+}
+
+/// Check whether the next [`receive`] would report an error
+///
+/// Peeks at the next frame without consuming it.
+pub(super) fn error_pending(usart: &pac::usart0_s::RegisterBlock) -> bool {
+    if usart.if_().read().rxof().bit_is_set() {
+        return true;
+    }
+
+    if usart.status().read().rxdatav().bit_is_clear() {
+        return false;
+    }
+
+    let frame = usart.rxdataxp().read();
+    frame.perrp().bit_is_set() || frame.ferrp().bit_is_set()
+}
+
+/// Report and clear receive error flags raised since they were last cleared
+///
+/// Used by the DMA receive paths, where the LDMA reads RXDATA directly and the
+/// per-frame PERR/FERR bits are not seen.
+pub(super) fn take_error_flags(usart: &pac::usart0_s::RegisterBlock) -> Result<(), Error> {
+    let flags = usart.if_().read().bits() & (IF_RXOF | IF_PERR | IF_FERR);
+    if flags == 0 {
+        return Ok(());
+    }
+
+    regs::clear_bits(usart.if_(), flags);
+    if flags & IF_RXOF != 0 {
+        Err(Error::Overrun)
+    } else if flags & IF_PERR != 0 {
+        Err(Error::Parity)
+    } else {
+        Err(Error::Framing)
+    }
+}
+
+/// Receive up to `buf.len()` frames that are already available
+///
+/// Stops before a frame that would report an error, so the bytes received before
+/// it are returned first. Returns `Ok(0)` if nothing has been received.
+pub(super) fn receive_available(
+    usart: &pac::usart0_s::RegisterBlock,
+    buf: &mut [u8],
+) -> Result<usize, Error> {
+    let mut count = 0;
+    while count < buf.len() {
+        if count > 0 && error_pending(usart) {
+            break;
+        }
+
+        match receive(usart) {
+            Ok(byte) => {
+                buf[count] = byte;
+                count += 1;
+            }
+            Err(nb::Error::WouldBlock) => break,
+            Err(nb::Error::Other(error)) => return Err(error),
+        }
+    }
+    Ok(count)
+}
+
+/// Route the CTS/RTS pins and enable CTS, or disable flow control
+fn configure_flow_control(usart: &pac::usart0_s::RegisterBlock, flow_control: FlowControl) {
+    // SAFETY: The USART0 CTS/RTS route registers belong to the USART0 owner, and
+    // ROUTEEN is updated through its SET/CLR aliases.
+    let gpio = unsafe { &*pac::GpioS::ptr() };
+
+    match flow_control {
+        FlowControl::None => {
+            usart.ctrlx().modify(|_, w| w.ctsen().clear_bit());
+            regs::clear_bits(gpio.usart0_routeen(), ROUTEEN_RTSPEN);
+        }
+        FlowControl::RtsCts(pins) => {
+            let (cts_port, cts_pin) = pins.cts;
+            let (rts_port, rts_pin) = pins.rts;
+            gpio.usart0_ctsroute()
+                .write(|w| unsafe { w.port().bits(cts_port).pin().bits(cts_pin) });
+            gpio.usart0_rtsroute()
+                .write(|w| unsafe { w.port().bits(rts_port).pin().bits(rts_pin) });
+            regs::set_bits(gpio.usart0_routeen(), ROUTEEN_RTSPEN);
+            usart.ctrlx().modify(|_, w| w.ctsen().set_bit());
+        }
+    }
+}
+
+/// Route DE to the chip select output and enable automatic chip select, or
+/// disable RS-485 mode
+fn configure_rs485(usart: &pac::usart0_s::RegisterBlock, rs485: Option<Rs485>) {
+    // SAFETY: The USART0 CS route register belongs to the USART0 owner, and
+    // ROUTEEN is updated through its SET/CLR aliases.
+    let gpio = unsafe { &*pac::GpioS::ptr() };
+
+    match rs485 {
+        None => {
+            usart
+                .ctrl()
+                .modify(|_, w| w.autocs().clear_bit().csinv().clear_bit());
+            regs::clear_bits(gpio.usart0_routeen(), ROUTEEN_CSPEN);
+        }
+        Some(rs485) => {
+            // CSSETUP/CSHOLD encode 0, 1, 2, 3 or 7 bit times
+            let delay = |bits: u8| match bits {
+                0..=3 => bits,
+                _ => 4,
+            };
+            usart.timing().modify(|_, w| {
+                w.cssetup().set(delay(rs485.setup_bits));
+                w.cshold().set(delay(rs485.hold_bits))
+            });
+
+            let (port, pin) = rs485.de;
+            gpio.usart0_csroute()
+                .write(|w| unsafe { w.port().bits(port).pin().bits(pin) });
+            regs::set_bits(gpio.usart0_routeen(), ROUTEEN_CSPEN);
+
+            // DE is active high
+            usart
+                .ctrl()
+                .modify(|_, w| w.autocs().set_bit().csinv().set_bit());
+        }
+    }
+}
+
+/// Enable IrDA modulation with the configured pulse width, or disable it
+fn configure_irda(usart: &pac::usart0_s::RegisterBlock, irda: Option<Irda>) {
+    match irda {
+        None => {
+            usart.irctrl().write(|w| w.iren().clear_bit());
+        }
+        Some(irda) => {
+            usart.irctrl().write(|w| {
+                match irda.pulse_width {
+                    IrdaPulseWidth::OneSixteenth => w.irpw().one(),
+                    IrdaPulseWidth::TwoSixteenths => w.irpw().two(),
+                    IrdaPulseWidth::ThreeSixteenths => w.irpw().three(),
+                    IrdaPulseWidth::FourSixteenths => w.irpw().four(),
+                };
+                w.irfilt().bit(irda.filter);
+                w.iren().set_bit()
+            });
+        }
+    }
+}
+
+/// Check whether the line is half duplex: RS-485 mode (DE driven by automatic
+/// chip select) or smart-card mode (RX looped back from TX)
+pub(super) fn half_duplex(usart: &pac::usart0_s::RegisterBlock) -> bool {
+    let ctrl = usart.ctrl().read();
+    ctrl.autocs().bit_is_set() || ctrl.scmode().bit_is_set()
+}
+
+/// Block the receiver before transmitting on a half-duplex line
+///
+/// Frames received while blocked, i.e. the echo of our own transmission, are
+/// discarded.
+pub(super) fn half_duplex_block_rx(usart: &pac::usart0_s::RegisterBlock) {
+    if half_duplex(usart) {
+        usart.cmd().write(|w| w.rxblocken().set_bit());
+    }
+}
+
+/// Unblock the receiver once transmission is complete on a half-duplex line
+///
+/// In multiprocessor mode the receiver stays blocked unless our address was the
+/// last one received.
+pub(super) fn half_duplex_unblock_rx(usart: &pac::usart0_s::RegisterBlock) {
+    if half_duplex(usart) && multiprocessor::receiver_addressed(usart) {
+        usart.cmd().write(|w| w.rxblockdis().set_bit());
+    }
+}
+
+/// Queue one byte for transmission once the TX buffer has space
+fn queue_byte(usart: &pac::usart0_s::RegisterBlock, byte: u8) {
+    // Wait for TX buffer to be ready
+    while !usart.status().read().txbl().bit_is_set() {
+        core::hint::spin_loop();
+    }
+
+    // Write byte to TXDATA
+    usart.txdata().write(|w| unsafe { w.txdata().bits(byte) });
+}
+
+/// Queue one frame of up to 9 bits once the TX buffer has space
+pub(super) fn queue_word(usart: &pac::usart0_s::RegisterBlock, word: u16) {
+    while try_transmit(usart, word).is_err() {
+        core::hint::spin_loop();
+    }
+}
+
+/// Write all of `bytes` (blocking), waiting for TX complete on a half-duplex line
+pub(super) fn transmit(usart: &pac::usart0_s::RegisterBlock, bytes: &[u8]) {
+    half_duplex_block_rx(usart);
+    for &byte in bytes {
+        queue_byte(usart, byte);
+    }
+
+    if half_duplex(usart) {
+        flush_tx(usart);
+    }
+}
+
+/// Queue as many of `bytes` as fit in the TX buffer without waiting
+pub(super) fn transmit_available(usart: &pac::usart0_s::RegisterBlock, bytes: &[u8]) -> usize {
+    if usart.status().read().txbl().bit_is_clear() {
+        return 0;
+    }
+
+    half_duplex_block_rx(usart);
+    let mut count = 0;
+    for &byte in bytes {
+        if usart.status().read().txbl().bit_is_clear() {
+            break;
+        }
+        usart.txdata().write(|w| unsafe { w.txdata().bits(byte) });
+        count += 1;
+    }
+    count
+}
+
+/// Queue one frame of up to 9 bits if the TX buffer has space (non-blocking)
+pub(super) fn try_transmit(
+    usart: &pac::usart0_s::RegisterBlock,
+    word: u16,
+) -> nb::Result<(), Error> {
+    if usart.status().read().txbl().bit_is_set() {
+        half_duplex_block_rx(usart);
+        // SAFETY: TXDATAX holds 9 data bits; the control bits above them stay clear
+        usart
+            .txdatax()
+            .write(|w| unsafe { w.txdatax().bits(word & 0x1FF) });
+        Ok(())
+    } else {
+        Err(nb::Error::WouldBlock)
+    }
+}
+
+/// Check for TX complete, then unblock the receiver on a half-duplex line
+/// (non-blocking)
+pub(super) fn try_flush_tx(usart: &pac::usart0_s::RegisterBlock) -> nb::Result<(), Error> {
+    if usart.status().read().txc().bit_is_set() {
+        half_duplex_unblock_rx(usart);
+        Ok(())
+    } else {
+        Err(nb::Error::WouldBlock)
+    }
+}
+
+/// Block until the TX buffer has room, then queue as many of `bytes` as fit
+pub(super) fn transmit_some(usart: &pac::usart0_s::RegisterBlock, bytes: &[u8]) -> usize {
+    if bytes.is_empty() {
+        return 0;
+    }
+
+    loop {
+        let count = transmit_available(usart, bytes);
+        if count > 0 {
+            return count;
+        }
+        core::hint::spin_loop();
+    }
+}
+
+/// Block until at least one byte (or a receive error) is available, then
+/// return everything already received
+pub(super) fn receive_some(
+    usart: &pac::usart0_s::RegisterBlock,
+    buf: &mut [u8],
+) -> Result<usize, Error> {
+    if buf.is_empty() {
+        return Ok(0);
+    }
+
+    loop {
+        let count = receive_available(usart, buf)?;
+        if count > 0 {
+            return Ok(count);
+        }
+        core::hint::spin_loop();
+    }
+}
+
+/// Wait for TX complete, then unblock the receiver on a half-duplex line
+pub(super) fn flush_tx(usart: &pac::usart0_s::RegisterBlock) {
+    while !usart.status().read().txc().bit_is_set() {
+        core::hint::spin_loop();
+    }
+    half_duplex_unblock_rx(usart);
+}
+
+/// Check whether a read would return data or an error without blocking
+pub(super) fn read_ready(usart: &pac::usart0_s::RegisterBlock) -> bool {
+    usart.status().read().rxdatav().bit_is_set() || usart.if_().read().rxof().bit_is_set()
+}
+
+/// Start timer comparator 1 at every RX end of frame, stop it at RX activity
+pub(super) fn enable_idle_detection(usart: &pac::usart0_s::RegisterBlock, bit_times: u8) {
+    usart.timecmp1().write(|w| {
+        unsafe { w.tcmpval().bits(bit_times) };
+        w.tstart().rxeof();
+        w.tstop().rxact()
+    });
+    regs::clear_bits(usart.if_(), IF_TCMP1);
+}
+
+/// Stop timer comparator 1
+pub(super) fn disable_idle_detection(usart: &pac::usart0_s::RegisterBlock) {
+    usart.timecmp1().write(|w| w.tstart().disable());
+    regs::clear_bits(usart.if_(), IF_TCMP1);
+}
+
+/// Set the baud rate divider for `baudrate` from PCLK
+fn write_clkdiv(usart: &pac::usart0_s::RegisterBlock, baudrate: Bps, clocks: &FrozenClocks) {
+    // CLKDIV = 256 * (fPCLK / (oversample * baudrate) - 1), of which the
+    // DIV field (bits 22:3) holds the upper 20 bits
+    // For async mode, oversample = 16
+    // (computed in 64 bits: 256 * fPCLK overflows a u32 above 16.7 MHz)
+    let pclk = u64::from(clocks.pclk().to_Hz());
+    let clkdiv = (256 * pclk / (OVERSAMPLE * u64::from(baudrate.to_bps()))) - 256;
+    usart
+        .clkdiv()
+        .write(|w| unsafe { w.div().bits((clkdiv >> 3) as u32 & 0xFFFFF) });
+}
+
+/// Baud rate set by a CLKDIV.DIV field value
+///
+/// `baudrate = fclk / (oversample * (1 + DIV / 32))`; the EUSART CLKDIV has the
+/// same layout.
+pub(super) fn clkdiv_baudrate(clock_hz: u32, oversample: u64, div: u32) -> Bps {
+    let baudrate = 32 * u64::from(clock_hz) / (oversample * (32 + u64::from(div)));
+    Bps::from_raw(baudrate as u32)
+}
+
+/// Report and clear IF.TCMP1 (RX idle)
+pub(super) fn rx_idle(usart: &pac::usart0_s::RegisterBlock) -> bool {
+    if usart.if_().read().tcmp1().bit_is_set() {
+        regs::clear_bits(usart.if_(), IF_TCMP1);
+        true
+    } else {
+        false
+    }
+}
+
 /// USART0 peripheral
 pub struct Usart0 {
     pub(crate) usart: pac::Usart0S,
@@ -65,13 +595,14 @@ impl Usart0 {
     /// # Arguments
     ///
     /// * `usart` - USART0_S peripheral from PAC
-    /// * `config` - USART configuration (baud rate, data bits, parity, stop bits)
+    /// * `config` - USART configuration (baud rate, data bits, parity, stop bits,
+    ///   flow control, RS-485, smart-card or IrDA mode)
     /// * `clocks` - Frozen clock configuration for baud rate calculation
     ///
     /// # Example
     ///
     /// ```no_run
-    /// let config = Config::new(115200)
+    /// let config = Config::new(115_200.bps())
     ///     .data_bits(DataBits::Eight)
     ///     .parity(Parity::None)
     ///     .stop_bits(StopBits::One);
@@ -113,27 +644,27 @@ impl Usart0 {
         });
 
         // Calculate and set baud rate
-        // CLKDIV = 256 * (fPCLK / (oversample * baudrate) - 1)
-        // For async mode, oversample = 16
-        let pclk: u32 = clocks.pclk().into();
-        let oversample = 16;
-        let clkdiv = (256 * pclk / (oversample * config.baudrate)) - 256;
-        usart
-            .clkdiv()
-            .write(|w| unsafe { w.div().bits(clkdiv & 0xFFFFF) });
+        write_clkdiv(&usart, config.baudrate, clocks);
+
+        configure_flow_control(&usart, config.flow_control);
+        configure_rs485(&usart, config.rs485);
+        smartcard::configure_smart_card(&usart, config.smart_card);
+        configure_irda(&usart, config.irda);
 
         // Enable TX and RX
         usart.cmd().write(|w| {
             w.txen().set_bit();
             w.rxen().set_bit()
         });
+        multiprocessor::configure_multiprocessor(&usart, config.multiprocessor_address);
 
         Self { usart }
     }
 
     /// Write a single byte (blocking)
     ///
-    /// Waits until the TX buffer has space, then writes the byte.
+    /// Waits until the TX buffer has space, then writes the byte. In RS-485
+    /// and smart-card mode, waits until the byte has been sent, like [`write`](Self::write).
     ///
     /// # Hardware Operation
     ///
@@ -146,44 +677,54 @@ impl Usart0 {
     /// usart.write_byte(b'A');
     /// ```
     pub fn write_byte(&mut self, byte: u8) {
-        // Wait for TX buffer to be ready
-        while !self.usart.status().read().txbl().bit_is_set() {
-            core::hint::spin_loop();
-        }
-
-        // Write byte to TXDATA
-        self.usart
-            .txdata()
-            .write(|w| unsafe { w.txdata().bits(byte) });
+        self.write(&[byte]);
     }
 
     /// Read a single byte (non-blocking)
     ///
-    /// Returns `Some(byte)` if data is available, `None` otherwise.
+    /// Returns the byte if one is available, `WouldBlock` otherwise, or the
+    /// receive error of the next frame.
     ///
     /// # Hardware Operation
     ///
-    /// 1. Checks STATUS.RXDATAV (RX data valid) flag
-    /// 2. If set, reads byte from RXDATA register
+    /// 1. Checks IF.RXOF (receive overflow) and clears it if set
+    /// 2. Checks STATUS.RXDATAV (RX data valid) flag
+    /// 3. If set, reads the frame and its PERR/FERR flags from RXDATAX
     ///
     /// # Example
     ///
     /// ```no_run
-    /// if let Some(byte) = usart.read_byte() {
-    ///     // Process received byte
+    /// match usart.read_byte() {
+    ///     Ok(byte) => { /* Process received byte */ }
+    ///     Err(nb::Error::WouldBlock) => { /* Nothing received yet */ }
+    ///     Err(nb::Error::Other(error)) => { /* Parity, framing or overrun error */ }
     /// }
     /// ```
-    pub fn read_byte(&mut self) -> Option<u8> {
-        if self.usart.status().read().rxdatav().bit_is_set() {
-            Some(self.usart.rxdata().read().rxdata().bits())
-        } else {
-            None
-        }
+    pub fn read_byte(&mut self) -> nb::Result<u8, Error> {
+        receive(&self.usart)
+    }
+
+    /// Write a 9-bit frame (blocking)
+    ///
+    /// Like [`write_byte`](Self::write_byte), for frames with
+    /// [`DataBits::Nine`]; bits above the ninth are ignored.
+    pub fn write_word(&mut self, word: u16) {
+        multiprocessor::transmit_word(&self.usart, word);
+    }
+
+    /// Read a 9-bit frame (non-blocking)
+    ///
+    /// Like [`read_byte`](Self::read_byte), keeping the ninth data bit. In
+    /// multiprocessor mode, only data frames addressed to us are returned.
+    pub fn read_word(&mut self) -> nb::Result<u16, Error> {
+        receive_word(&self.usart)
     }
 
     /// Write a slice of bytes (blocking)
     ///
-    /// Writes all bytes in the slice sequentially.
+    /// Writes all bytes in the slice sequentially. In RS-485 and smart-card
+    /// mode, the receiver is blocked during the transmission, and this returns once the last byte
+    /// has been sent, ready for the response.
     ///
     /// # Example
     ///
@@ -191,20 +732,124 @@ impl Usart0 {
     /// usart.write(b"Hello, world!\r\n");
     /// ```
     pub fn write(&mut self, bytes: &[u8]) {
-        for &byte in bytes {
-            self.write_byte(byte);
-        }
+        transmit(&self.usart, bytes);
     }
 
     /// Flush the TX buffer (blocking)
     ///
-    /// Waits until all transmitted data has been sent (TX complete).
+    /// Waits until all transmitted data has been sent (TX complete). In RS-485
+    /// and smart-card mode, the receiver is unblocked afterwards.
     ///
     /// # Hardware Operation
     ///
     /// Polls STATUS.TXC (TX complete) until set.
     pub fn flush(&mut self) {
-        while !self.usart.status().read().txc().bit_is_set() {
+        flush_tx(&self.usart);
+    }
+
+    /// Enable RX idle detection
+    ///
+    /// Timer comparator 1 is started at the end of every received frame and
+    /// stopped when the next frame starts. If the line stays idle for
+    /// `bit_times` bit periods after a frame, [`rx_idle`](Self::rx_idle) reports
+    /// the end of the packet.
+    ///
+    /// # Hardware Operation
+    ///
+    /// TIMECMP1: TSTART = RXEOF, TSTOP = RXACT, TCMPVAL = `bit_times`
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// // A gap of 3.5 characters (11 bit times each) ends a packet
+    /// usart.enable_idle_detection(39);
+    /// ```
+    pub fn enable_idle_detection(&mut self, bit_times: u8) {
+        enable_idle_detection(&self.usart, bit_times);
+    }
+
+    /// Disable RX idle detection
+    pub fn disable_idle_detection(&mut self) {
+        disable_idle_detection(&self.usart);
+    }
+
+    /// Check whether the RX line has gone idle after a frame
+    ///
+    /// Returns `true` once per idle period (IF.TCMP1 is cleared when read), and
+    /// only while idle detection is enabled.
+    pub fn rx_idle(&mut self) -> bool {
+        rx_idle(&self.usart)
+    }
+
+    /// Change the baud rate
+    ///
+    /// Takes effect immediately; call [`flush`](Self::flush) first if a
+    /// transmission may be in progress. `clocks` must be the clocks the USART was
+    /// created with.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// // Smart card: Fi = 512, Di = 8 accepted by PPS
+    /// usart.set_baudrate(smart_card.clock_rate(512, 8).baudrate(), &clocks);
+    /// ```
+    pub fn set_baudrate(&mut self, baudrate: Bps, clocks: &FrozenClocks) {
+        write_clkdiv(&self.usart, baudrate, clocks);
+    }
+
+    /// Measure the baud rate on the next received character
+    ///
+    /// Sets CLKDIV.AUTOBAUDEN: the USART times the next frame, which must be the
+    /// sync character 0x55 (`'U'`), and reprograms CLKDIV to match. Poll for the
+    /// result with [`poll_autobaud`](Self::poll_autobaud), or use
+    /// [`autobaud`](Self::autobaud) to wait for it.
+    pub fn start_autobaud(&mut self) {
+        // Only the sync character may complete the measurement
+        while self.usart.status().read().rxdatav().bit_is_set() {
+            let _ = self.usart.rxdata().read();
+        }
+        let _ = take_error_flags(&self.usart);
+        regs::set_bits(self.usart.clkdiv(), CLKDIV_AUTOBAUDEN);
+    }
+
+    /// Check whether the sync character has been measured (non-blocking)
+    ///
+    /// Returns `config` with the detected baud rate once CLKDIV has been
+    /// reprogrammed, or `None` while still waiting. The sync character itself is
+    /// discarded. `clocks` must be the clocks the USART was created with.
+    pub fn poll_autobaud(&mut self, config: Config, clocks: &FrozenClocks) -> Option<Config> {
+        if self.usart.status().read().rxdatav().bit_is_clear() {
+            return None;
+        }
+
+        regs::clear_bits(self.usart.clkdiv(), CLKDIV_AUTOBAUDEN);
+        let _ = self.usart.rxdata().read();
+        let _ = take_error_flags(&self.usart);
+
+        let div = self.usart.clkdiv().read().div().bits();
+        let baudrate = clkdiv_baudrate(clocks.pclk().to_Hz(), OVERSAMPLE, div);
+        Some(config.baudrate(baudrate))
+    }
+
+    /// Detect the baud rate from a received sync character (blocking)
+    ///
+    /// Waits for the remote end to send 0x55 (`'U'`), reprograms CLKDIV to its
+    /// baud rate and returns `config` with the detected rate.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let config = Config::new(115_200.bps());
+    /// let mut usart = Usart0::new(dp.usart0_s, config, &clocks);
+    /// let config = usart.autobaud(config, &clocks);
+    /// let detected = config.baudrate.to_bps();
+    /// ```
+    pub fn autobaud(&mut self, config: Config, clocks: &FrozenClocks) -> Config {
+        self.start_autobaud();
+        loop {
+            if let Some(config) = self.poll_autobaud(config, clocks) {
+                return config;
+            }
             core::hint::spin_loop();
         }
     }
@@ -0,0 +1,132 @@
+//! 9-bit multiprocessor addressing for USART0
+//!
+//! On a multidrop bus, the ninth data bit marks address frames. With
+//! [`Config::multiprocessor_address`](super::Config::multiprocessor_address),
+//! USART0 runs in multiprocessor mode (CTRL.MPM, with CTRL.MPAB = 1): the
+//! receiver starts blocked, and only address frames get through RXBLOCK. The
+//! receive paths compare each address frame with our address and unblock the
+//! receiver when it matches, or block it again when another node is addressed.
+//! Address frames themselves are consumed and never returned.
+//!
+//! The bus master leaves multiprocessor mode off and selects a node with
+//! [`Usart0::write_address`] before sending it data.
+//!
+//! On a half-duplex line (RS-485 or smart card), the receiver is blocked while
+//! transmitting. Afterwards it is only unblocked again if our address was the
+//! last one received, so a reply does not let other nodes' data through.
+//!
+//! DMA reception reads RXDATA directly and does not match addresses: it receives
+//! the low 8 bits of address frames, and data frames only while the receiver is
+//! unblocked.
+
+use core::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+use super::{flush_tx, half_duplex, half_duplex_block_rx, queue_word, Usart0};
+use crate::pac;
+
+/// Ninth data bit, set in address frames
+pub(super) const ADDRESS_BIT: u16 = 1 << 8;
+
+/// Our multiprocessor address
+static ADDRESS: AtomicU8 = AtomicU8::new(0);
+
+/// The last address frame carried our address: the receiver is meant to be
+/// unblocked
+static ADDRESSED: AtomicBool = AtomicBool::new(false);
+
+/// Enable multiprocessor mode with the receiver blocked until `address` is
+/// received, or disable it
+///
+/// Called with the receiver enabled, since enabling it clears RXBLOCK.
+pub(super) fn configure_multiprocessor(usart: &pac::usart0_s::RegisterBlock, address: Option<u8>) {
+    match address {
+        None => {
+            usart
+                .ctrl()
+                .modify(|_, w| w.mpm().clear_bit().mpab().clear_bit());
+        }
+        Some(address) => {
+            ADDRESS.store(address, Ordering::Relaxed);
+            ADDRESSED.store(false, Ordering::Relaxed);
+            usart
+                .ctrl()
+                .modify(|_, w| w.mpm().set_bit().mpab().set_bit());
+            usart.cmd().write(|w| w.rxblocken().set_bit());
+        }
+    }
+}
+
+/// Handle a received address frame in multiprocessor mode
+///
+/// Returns `false` for frames to be passed on: data frames, and all frames
+/// outside multiprocessor mode. Address frames unblock the receiver if they
+/// carry our address and block it otherwise.
+pub(super) fn take_address_frame(usart: &pac::usart0_s::RegisterBlock, frame: u16) -> bool {
+    if frame & ADDRESS_BIT == 0 || usart.ctrl().read().mpm().bit_is_clear() {
+        return false;
+    }
+
+    let matched = frame as u8 == ADDRESS.load(Ordering::Relaxed);
+    ADDRESSED.store(matched, Ordering::Relaxed);
+    if matched {
+        usart.cmd().write(|w| w.rxblockdis().set_bit());
+    } else {
+        usart.cmd().write(|w| w.rxblocken().set_bit());
+    }
+    true
+}
+
+/// Check whether the receiver may be unblocked after a half-duplex
+/// transmission
+///
+/// Always true outside multiprocessor mode. In multiprocessor mode, only if our
+/// address was the last one received.
+pub(super) fn receiver_addressed(usart: &pac::usart0_s::RegisterBlock) -> bool {
+    usart.ctrl().read().mpm().bit_is_clear() || ADDRESSED.load(Ordering::Relaxed)
+}
+
+/// Write one 9-bit frame (blocking), waiting for TX complete on a half-duplex line
+pub(super) fn transmit_word(usart: &pac::usart0_s::RegisterBlock, word: u16) {
+    half_duplex_block_rx(usart);
+    queue_word(usart, word);
+
+    if half_duplex(usart) {
+        flush_tx(usart);
+    }
+}
+
+impl Usart0 {
+    /// Select a node on a multidrop bus (blocking)
+    ///
+    /// Sends `address` as an address frame, with the ninth data bit set; the
+    /// frame format must have 9 data bits. Nodes in multiprocessor mode with this
+    /// address receive the data frames that follow, the others ignore them.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let config = Config::new(115_200.bps()).data_bits(DataBits::Nine);
+    /// let mut usart = Usart0::new(dp.usart0_s, config, &clocks);
+    /// usart.write_address(0x12);
+    /// usart.write(b"set 42\n");
+    /// ```
+    pub fn write_address(&mut self, address: u8) {
+        transmit_word(&self.usart, ADDRESS_BIT | u16::from(address));
+    }
+
+    /// Ignore received data until our address is received again
+    ///
+    /// Blocks the receiver, e.g. once the message addressed to us is complete.
+    /// Only has an effect in multiprocessor mode.
+    pub fn listen_for_address(&mut self) {
+        listen_for_address(&self.usart);
+    }
+}
+
+/// Block the receiver until an address frame with our address arrives
+pub(super) fn listen_for_address(usart: &pac::usart0_s::RegisterBlock) {
+    if usart.ctrl().read().mpm().bit_is_set() {
+        ADDRESSED.store(false, Ordering::Relaxed);
+        usart.cmd().write(|w| w.rxblocken().set_bit());
+    }
+}
@@ -0,0 +1,302 @@
+//! ISO 7816 smart-card mode for USART0
+//!
+//! [`Config::smart_card`](super::Config::smart_card) puts USART0 in smart-card
+//! mode (CTRL.SCMODE) with the card's I/O line on TX, looped back to the
+//! receiver. [`Usart0::read_atr`] then receives the Answer-to-Reset after the
+//! application releases the card's RST line.
+//!
+//! # Conventions
+//!
+//! The initial character TS selects the convention for the rest of the session.
+//! Direct convention TS (0x3B) keeps the LSB-first, non-inverted frame. Inverse
+//! convention TS (0x3F) reads as 0x03 in that frame; [`Usart0::read_atr`] then
+//! switches USART0 to MSB-first with inverted TX and RX (CTRL.MSBF, TXINV,
+//! RXINV), so every later byte, in both directions, is exchanged as its logical
+//! value. TS is received with SCMODE off: its parity is wrong in the direct
+//! frame, and a NACK would make the card repeat it.
+//!
+//! # Waiting Times
+//!
+//! The ATR is read with the ISO 7816-3 defaults: TS must arrive within 40 000
+//! card clock cycles of RST going high, and each later character within the
+//! initial waiting time of 9600 etu of the previous one. The receiver is polled
+//! once per etu with a [`DelayNs`], and [`Error::Timeout`] is returned when a
+//! character is late or the card is mute.
+//!
+//! # Card Clock
+//!
+//! USART0 does not drive its clock output in asynchronous mode, so the card
+//! clock is generated by a TIMER PWM channel rather than derived from the USART
+//! clock.
+
+use embedded_hal::delay::DelayNs;
+
+use super::{receive, Error, SmartCard, Usart0, IF_PERR};
+use crate::pac;
+use crate::regs;
+
+/// Maximum length of an Answer-to-Reset, in bytes
+pub const MAX_ATR_LEN: usize = 33;
+
+/// TS as received in direct convention (0x3B)
+const TS_DIRECT: u8 = 0x3B;
+
+/// Inverse convention TS (0x3F), as received in a direct-convention frame
+const TS_INVERSE_AS_DIRECT: u8 = 0x03;
+
+/// Latest start of TS after RST goes high, in card clock cycles (ISO 7816-3)
+const TS_MAX_CYCLES: u32 = 40_000;
+
+/// Initial waiting time between two ATR characters, in etu (ISO 7816-3)
+const INITIAL_WAITING_TIME: u32 = 9600;
+
+/// Length of one character (start, 8 data, parity, 2 guard bits), in etu
+const CHARACTER_ETU: u32 = 12;
+
+/// Set up smart-card mode, or disable it
+pub(super) fn configure_smart_card(
+    usart: &pac::usart0_s::RegisterBlock,
+    smart_card: Option<SmartCard>,
+) {
+    match smart_card {
+        None => {
+            usart.ctrl().modify(|_, w| {
+                w.scmode().clear_bit();
+                w.scretrans().clear_bit();
+                w.loopbk().clear_bit()
+            });
+            usart.timing().modify(|_, w| w.ics().zero());
+        }
+        Some(smart_card) => {
+            // Extra guard time between transmitted characters
+            usart.timing().modify(|_, w| match smart_card.guard_time {
+                0 => w.ics().zero(),
+                1 => w.ics().one(),
+                2 => w.ics().two(),
+                3 => w.ics().three(),
+                _ => w.ics().seven(),
+            });
+
+            usart.ctrl().modify(|_, w| {
+                w.scmode().set_bit();
+                w.scretrans().bit(smart_card.retransmit);
+                w.loopbk().set_bit()
+            });
+        }
+    }
+}
+
+/// Waits the ATR reception polls with
+struct Waiter<'a, D> {
+    delay: &'a mut D,
+    etu_ns: u32,
+}
+
+impl<D: DelayNs> Waiter<'_, D> {
+    /// Wait up to `etu` etu for a received frame to become available
+    fn wait_rxdatav(
+        &mut self,
+        usart: &pac::usart0_s::RegisterBlock,
+        etu: u32,
+    ) -> Result<(), Error> {
+        for _ in 0..etu {
+            if usart.status().read().rxdatav().bit_is_set() {
+                return Ok(());
+            }
+            self.delay.delay_ns(self.etu_ns);
+        }
+
+        if usart.status().read().rxdatav().bit_is_set() {
+            Ok(())
+        } else {
+            Err(Error::Timeout)
+        }
+    }
+
+    /// Receive one character within the initial waiting time
+    fn receive(&mut self, usart: &pac::usart0_s::RegisterBlock) -> Result<u8, Error> {
+        loop {
+            match receive(usart) {
+                Ok(byte) => return Ok(byte),
+                Err(nb::Error::WouldBlock) => {
+                    self.wait_rxdatav(usart, INITIAL_WAITING_TIME + CHARACTER_ETU)?
+                }
+                Err(nb::Error::Other(error)) => return Err(error),
+            }
+        }
+    }
+}
+
+/// Answer-to-Reset received from a smart card
+///
+/// Returned by [`Usart0::read_atr`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Atr {
+    bytes: [u8; MAX_ATR_LEN],
+    len: u8,
+    historical_start: u8,
+    historical_len: u8,
+    inverse: bool,
+}
+
+impl Atr {
+    /// The ATR bytes, starting with TS (as its logical value, 0x3B or 0x3F)
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..usize::from(self.len)]
+    }
+
+    /// The historical bytes T1 to TK
+    pub fn historical_bytes(&self) -> &[u8] {
+        let start = usize::from(self.historical_start);
+        &self.bytes[start..start + usize::from(self.historical_len)]
+    }
+
+    /// Whether the card uses the inverse convention
+    pub fn inverse_convention(&self) -> bool {
+        self.inverse
+    }
+
+    /// Append a byte, returning `false` once the ATR is full
+    fn push(&mut self, byte: u8) -> bool {
+        if usize::from(self.len) == MAX_ATR_LEN {
+            return false;
+        }
+        self.bytes[usize::from(self.len)] = byte;
+        self.len += 1;
+        true
+    }
+}
+
+impl Usart0 {
+    /// Receive the Answer-to-Reset (blocking)
+    ///
+    /// Call in smart-card mode, with the card clock running, right after
+    /// releasing the card's RST line. Reads TS and switches to the inverse
+    /// convention if the card asks for it, then reads as many bytes as T0 and the
+    /// interface bytes TD1, TD2, ... announce: interface bytes, historical bytes,
+    /// and the check byte TCK when a protocol other than T=0 is offered. Bytes
+    /// beyond [`MAX_ATR_LEN`] are not read.
+    ///
+    /// TS must arrive within 40 000 card clock cycles, and every later
+    /// character within 9600 etu of the previous one (the ISO 7816-3 defaults).
+    /// `smart_card` is the configuration USART0 was created with; its card
+    /// clock and Fi/Di give the etu. `delay` paces the polling, once per etu.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Timeout`] if TS or a later character does not arrive in time
+    /// - [`Error::Framing`] if TS is neither direct nor inverse convention
+    /// - The receive error of any later byte
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let smart_card = SmartCard::new(card_clock);
+    /// let mut usart = Usart0::new(dp.usart0_s, Config::default().smart_card(smart_card), &clocks);
+    /// card_reset.set_high();
+    /// let atr = usart.read_atr(&smart_card, &mut delay)?;
+    /// let historical = atr.historical_bytes();
+    /// ```
+    pub fn read_atr(
+        &mut self,
+        smart_card: &SmartCard,
+        delay: &mut impl DelayNs,
+    ) -> Result<Atr, Error> {
+        let usart = &self.usart;
+        let mut waiter = Waiter {
+            delay,
+            etu_ns: smart_card.etu_ns(),
+        };
+        let mut atr = Atr {
+            bytes: [0; MAX_ATR_LEN],
+            len: 0,
+            historical_start: 0,
+            historical_len: 0,
+            inverse: false,
+        };
+
+        // Start from the direct convention, in case a previous card used the
+        // inverse one
+        usart.ctrl().modify(|_, w| {
+            w.msbf().clear_bit();
+            w.txinv().clear_bit();
+            w.rxinv().clear_bit()
+        });
+
+        // TS is read raw and with SCMODE off: in the direct-convention frame,
+        // inverse convention TS has the wrong parity, and a NACK would make the
+        // card repeat it
+        let scmode = usart.ctrl().read().scmode().bit_is_set();
+        usart.ctrl().modify(|_, w| w.scmode().clear_bit());
+        let ts = waiter.wait_rxdatav(
+            usart,
+            smart_card.cycles_to_etu(TS_MAX_CYCLES) + CHARACTER_ETU,
+        );
+        let ts = ts.map(|()| usart.rxdatax().read().rxdata().bits() as u8);
+        regs::clear_bits(usart.if_(), IF_PERR);
+        usart.ctrl().modify(|_, w| w.scmode().bit(scmode));
+        let ts = ts?;
+
+        match ts {
+            TS_DIRECT => {}
+            TS_INVERSE_AS_DIRECT => {
+                usart.ctrl().modify(|_, w| {
+                    w.msbf().set_bit();
+                    w.txinv().set_bit();
+                    w.rxinv().set_bit()
+                });
+                atr.inverse = true;
+            }
+            _ => return Err(Error::Framing),
+        }
+        atr.push(if atr.inverse { 0x3F } else { TS_DIRECT });
+
+        // T0: Y1 in the high nibble, K in the low nibble
+        let t0 = waiter.receive(usart)?;
+        atr.push(t0);
+        let historical_len = t0 & 0x0F;
+        let mut indicator = t0 >> 4;
+        let mut check_byte = false;
+
+        // TAi, TBi, TCi and TDi, as flagged by Yi
+        while indicator != 0 {
+            let mut td = None;
+            for bit in 0..4 {
+                if indicator & (1 << bit) != 0 {
+                    let byte = waiter.receive(usart)?;
+                    if !atr.push(byte) {
+                        return Ok(atr);
+                    }
+                    if bit == 3 {
+                        td = Some(byte);
+                    }
+                }
+            }
+
+            indicator = match td {
+                Some(td) => {
+                    // TCK is present if any protocol other than T=0 is offered
+                    check_byte |= td & 0x0F != 0;
+                    td >> 4
+                }
+                None => 0,
+            };
+        }
+
+        atr.historical_start = atr.len;
+        for _ in 0..historical_len {
+            let byte = waiter.receive(usart)?;
+            if !atr.push(byte) {
+                break;
+            }
+        }
+        atr.historical_len = atr.len - atr.historical_start;
+
+        if check_byte {
+            let byte = waiter.receive(usart)?;
+            atr.push(byte);
+        }
+
+        Ok(atr)
+    }
+}
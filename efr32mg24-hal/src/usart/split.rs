@@ -0,0 +1,305 @@
+//! Independently owned USART0 transmit and receive halves
+//!
+//! [`Usart0::split`] hands out a [`Tx`] and an [`Rx`] that can be moved to
+//! different tasks or contexts, e.g. a logger writing while a command parser
+//! reads. [`Usart0::join`] puts them back together.
+//!
+//! # Register Ownership
+//!
+//! The halves share the USART0 register block but touch disjoint parts of it.
+//! [`Tx`] writes TXDATA and the RXBLOCKEN/RXBLOCKDIS commands, [`Rx`] reads
+//! RXDATA and owns TIMECMP1. Both only clear their own IF flags and set their own
+//! IEN bits, through the SET/CLR aliases, so neither can undo the other's
+//! read-modify-write.
+
+use super::{
+    disable_idle_detection, dma, enable_idle_detection, flush_tx, multiprocessor, read_ready,
+    receive, receive_some, receive_word, rx_idle, transmit, transmit_some, try_flush_tx,
+    try_transmit, CircularRx, Error, Usart0,
+};
+use crate::dma::Channel;
+use crate::pac;
+
+/// USART0 transmit half
+///
+/// Created by [`Usart0::split`].
+pub struct Tx {
+    _private: (),
+}
+
+/// USART0 receive half
+///
+/// Created by [`Usart0::split`].
+pub struct Rx {
+    _private: (),
+}
+
+impl Usart0 {
+    /// Split into independently owned transmit and receive halves
+    ///
+    /// The configuration (baud rate, frame format, flow control, RS-485) is
+    /// kept. In RS-485 mode, [`Tx`] still blocks the receiver while it
+    /// transmits.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let (mut tx, mut rx) = usart.split();
+    /// tx.write(b"ready\r\n");
+    /// if let Ok(byte) = rx.read_byte() {
+    ///     // Process received data
+    /// }
+    /// let usart = Usart0::join(tx, rx);
+    /// ```
+    pub fn split(self) -> (Tx, Rx) {
+        (Tx { _private: () }, Rx { _private: () })
+    }
+
+    /// Reunite the halves returned by [`split`](Self::split)
+    pub fn join(tx: Tx, rx: Rx) -> Self {
+        let _ = (tx, rx);
+        Self {
+            // SAFETY: Only one `Tx` and one `Rx` exist, created from the `Usart0`
+            // that owned the peripheral, and both are consumed here.
+            usart: unsafe { pac::Usart0S::steal() },
+        }
+    }
+}
+
+impl Tx {
+    /// USART0 registers
+    pub(super) fn regs(&self) -> &pac::usart0_s::RegisterBlock {
+        // SAFETY: See the module docs; `Tx` only touches the transmit side.
+        unsafe { &*pac::Usart0S::ptr() }
+    }
+
+    /// Write a single byte (blocking)
+    ///
+    /// See [`Usart0::write_byte`].
+    pub fn write_byte(&mut self, byte: u8) {
+        transmit(self.regs(), &[byte]);
+    }
+
+    /// Write a slice of bytes (blocking)
+    ///
+    /// See [`Usart0::write`].
+    pub fn write(&mut self, bytes: &[u8]) {
+        transmit(self.regs(), bytes);
+    }
+
+    /// Write a 9-bit frame (blocking)
+    ///
+    /// See [`Usart0::write_word`].
+    pub fn write_word(&mut self, word: u16) {
+        multiprocessor::transmit_word(self.regs(), word);
+    }
+
+    /// Select a node on a multidrop bus (blocking)
+    ///
+    /// See [`Usart0::write_address`].
+    pub fn write_address(&mut self, address: u8) {
+        multiprocessor::transmit_word(
+            self.regs(),
+            multiprocessor::ADDRESS_BIT | u16::from(address),
+        );
+    }
+
+    /// Flush the TX buffer (blocking)
+    ///
+    /// See [`Usart0::flush`].
+    pub fn flush(&mut self) {
+        flush_tx(self.regs());
+    }
+
+    /// Write bytes through LDMA channel `N` (blocking)
+    ///
+    /// See [`Usart0::write_dma`].
+    pub fn write_dma<const N: u8>(&mut self, channel: &mut Channel<N>, data: &[u8]) {
+        dma::write_dma(self.regs(), channel, data);
+    }
+}
+
+impl Rx {
+    /// USART0 registers
+    pub(super) fn regs(&self) -> &pac::usart0_s::RegisterBlock {
+        // SAFETY: See the module docs; `Rx` only touches the receive side.
+        unsafe { &*pac::Usart0S::ptr() }
+    }
+
+    /// Read a single byte (non-blocking)
+    ///
+    /// See [`Usart0::read_byte`].
+    pub fn read_byte(&mut self) -> nb::Result<u8, Error> {
+        receive(self.regs())
+    }
+
+    /// Read a 9-bit frame (non-blocking)
+    ///
+    /// See [`Usart0::read_word`].
+    pub fn read_word(&mut self) -> nb::Result<u16, Error> {
+        receive_word(self.regs())
+    }
+
+    /// Ignore received data until our address is received again
+    ///
+    /// See [`Usart0::listen_for_address`].
+    pub fn listen_for_address(&mut self) {
+        multiprocessor::listen_for_address(self.regs());
+    }
+
+    /// Enable RX idle detection
+    ///
+    /// See [`Usart0::enable_idle_detection`].
+    pub fn enable_idle_detection(&mut self, bit_times: u8) {
+        enable_idle_detection(self.regs(), bit_times);
+    }
+
+    /// Disable RX idle detection
+    pub fn disable_idle_detection(&mut self) {
+        disable_idle_detection(self.regs());
+    }
+
+    /// Check whether the RX line has gone idle after a frame
+    ///
+    /// See [`Usart0::rx_idle`].
+    pub fn rx_idle(&mut self) -> bool {
+        rx_idle(self.regs())
+    }
+
+    /// Fill `buffer` through LDMA channel `N` (blocking)
+    ///
+    /// See [`Usart0::read_dma`].
+    pub fn read_dma<const N: u8>(
+        &mut self,
+        channel: &mut Channel<N>,
+        buffer: &mut [u8],
+    ) -> Result<(), Error> {
+        dma::receive_dma(self.regs(), channel, buffer, false).map(|_| ())
+    }
+
+    /// Receive a variable-length packet through LDMA channel `N` (blocking)
+    ///
+    /// See [`Usart0::read_dma_until_idle`].
+    pub fn read_dma_until_idle<const N: u8>(
+        &mut self,
+        channel: &mut Channel<N>,
+        buffer: &mut [u8],
+    ) -> Result<usize, Error> {
+        dma::receive_dma(self.regs(), channel, buffer, true)
+    }
+
+    /// Start receiving continuously into `buffer` through LDMA channel `N`
+    ///
+    /// See [`Usart0::read_dma_circular`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buffer` is empty or longer than 2048 bytes.
+    pub fn read_dma_circular<const N: u8>(
+        &mut self,
+        channel: Channel<N>,
+        buffer: &'static mut [u8],
+    ) -> CircularRx<'_, N> {
+        dma::start_circular(self.regs(), channel, buffer)
+    }
+}
+
+impl embedded_hal_nb::serial::ErrorType for Tx {
+    type Error = Error;
+}
+
+impl embedded_hal_nb::serial::ErrorType for Rx {
+    type Error = Error;
+}
+
+impl embedded_io::ErrorType for Tx {
+    type Error = Error;
+}
+
+impl embedded_io::ErrorType for Rx {
+    type Error = Error;
+}
+
+/// embedded-hal-nb Write implementation for the USART0 transmit half
+impl embedded_hal_nb::serial::Write<u8> for Tx {
+    fn write(&mut self, word: u8) -> nb::Result<(), Self::Error> {
+        try_transmit(self.regs(), u16::from(word))
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        try_flush_tx(self.regs())
+    }
+}
+
+/// embedded-hal-nb Read implementation for the USART0 receive half
+impl embedded_hal_nb::serial::Read<u8> for Rx {
+    fn read(&mut self) -> nb::Result<u8, Self::Error> {
+        receive(self.regs())
+    }
+}
+
+/// embedded-hal-nb Write implementation for 9-bit frames on the transmit half
+impl embedded_hal_nb::serial::Write<u16> for Tx {
+    fn write(&mut self, word: u16) -> nb::Result<(), Self::Error> {
+        try_transmit(self.regs(), word)
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        try_flush_tx(self.regs())
+    }
+}
+
+/// embedded-hal-nb Read implementation for 9-bit frames on the receive half
+impl embedded_hal_nb::serial::Read<u16> for Rx {
+    fn read(&mut self) -> nb::Result<u16, Self::Error> {
+        receive_word(self.regs())
+    }
+}
+
+/// embedded-io Write implementation for the USART0 transmit half
+///
+/// Blocks until the TX buffer has room, then queues as many bytes as fit.
+impl embedded_io::Write for Tx {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        Ok(transmit_some(self.regs(), buf))
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        flush_tx(self.regs());
+        Ok(())
+    }
+}
+
+/// embedded-io WriteReady implementation for the USART0 transmit half
+impl embedded_io::WriteReady for Tx {
+    fn write_ready(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.regs().status().read().txbl().bit_is_set())
+    }
+}
+
+/// embedded-io Read implementation for the USART0 receive half
+///
+/// Blocks until at least one byte (or a receive error) is available, then
+/// returns everything already received, up to `buf.len()`.
+impl embedded_io::Read for Rx {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        receive_some(self.regs(), buf)
+    }
+}
+
+/// embedded-io ReadReady implementation for the USART0 receive half
+impl embedded_io::ReadReady for Rx {
+    fn read_ready(&mut self) -> Result<bool, Self::Error> {
+        Ok(read_ready(self.regs()))
+    }
+}
+
+/// `core::fmt::Write` implementation for the USART0 transmit half
+///
+/// Writes the formatted text with [`Tx::write`] (blocking).
+impl core::fmt::Write for Tx {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        Tx::write(self, s.as_bytes());
+        Ok(())
+    }
+}
@@ -1,8 +1,12 @@
 //! embedded-hal trait implementations for USART
 //!
-//! This module implements the embedded-hal v1.0 and embedded-hal-nb traits for USART peripherals.
+//! This module implements the embedded-hal v1.0, embedded-hal-nb, embedded-io and `core::fmt`
+//! traits for USART peripherals.
 
-use super::{Error, Usart0};
+use super::{
+    read_ready, receive_some, receive_word, transmit_some, try_flush_tx, try_transmit, Error,
+    Usart0,
+};
 
 /// Error type for USART operations
 impl core::fmt::Display for Error {
@@ -11,6 +15,7 @@ impl core::fmt::Display for Error {
             Error::Framing => write!(f, "Framing error"),
             Error::Parity => write!(f, "Parity error"),
             Error::Overrun => write!(f, "Overrun error"),
+            Error::Timeout => write!(f, "Timeout"),
         }
     }
 }
@@ -20,29 +25,21 @@ impl embedded_hal_nb::serial::ErrorType for Usart0 {
     type Error = Error;
 }
 
+/// embedded-io ErrorType implementation for USART0
+impl embedded_io::ErrorType for Usart0 {
+    type Error = Error;
+}
+
 /// embedded-hal-nb Write trait implementation for USART0
 ///
 /// Provides non-blocking write operations compatible with embedded-hal-nb.
 impl embedded_hal_nb::serial::Write<u8> for Usart0 {
     fn write(&mut self, word: u8) -> nb::Result<(), Self::Error> {
-        // Check if TX buffer is ready
-        if self.usart.status().read().txbl().bit_is_set() {
-            self.usart
-                .txdata()
-                .write(|w| unsafe { w.txdata().bits(word) });
-            Ok(())
-        } else {
-            Err(nb::Error::WouldBlock)
-        }
+        try_transmit(&self.usart, u16::from(word))
     }
 
     fn flush(&mut self) -> nb::Result<(), Self::Error> {
-        // Check if transmission is complete
-        if self.usart.status().read().txc().bit_is_set() {
-            Ok(())
-        } else {
-            Err(nb::Error::WouldBlock)
-        }
+        try_flush_tx(&self.usart)
     }
 }
 
@@ -51,6 +48,74 @@ impl embedded_hal_nb::serial::Write<u8> for Usart0 {
 /// Provides non-blocking read operations compatible with embedded-hal-nb.
 impl embedded_hal_nb::serial::Read<u8> for Usart0 {
     fn read(&mut self) -> nb::Result<u8, Self::Error> {
-        self.read_byte().ok_or(nb::Error::WouldBlock)
+        self.read_byte()
+    }
+}
+
+/// embedded-hal-nb Write implementation for 9-bit frames on USART0
+impl embedded_hal_nb::serial::Write<u16> for Usart0 {
+    fn write(&mut self, word: u16) -> nb::Result<(), Self::Error> {
+        try_transmit(&self.usart, word)
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        try_flush_tx(&self.usart)
+    }
+}
+
+/// embedded-hal-nb Read implementation for 9-bit frames on USART0
+impl embedded_hal_nb::serial::Read<u16> for Usart0 {
+    fn read(&mut self) -> nb::Result<u16, Self::Error> {
+        receive_word(&self.usart)
+    }
+}
+
+/// embedded-io Read implementation for USART0
+///
+/// Blocks until at least one byte (or a receive error) is available, then
+/// returns everything already received, up to `buf.len()`.
+impl embedded_io::Read for Usart0 {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        receive_some(&self.usart, buf)
+    }
+}
+
+/// embedded-io ReadReady implementation for USART0
+///
+/// A pending receive error counts as ready, so that the next read reports it.
+impl embedded_io::ReadReady for Usart0 {
+    fn read_ready(&mut self) -> Result<bool, Self::Error> {
+        Ok(read_ready(&self.usart))
+    }
+}
+
+/// embedded-io Write implementation for USART0
+///
+/// Blocks until the TX buffer has room, then queues as many bytes as fit.
+impl embedded_io::Write for Usart0 {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        Ok(transmit_some(&self.usart, buf))
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Usart0::flush(self);
+        Ok(())
+    }
+}
+
+/// embedded-io WriteReady implementation for USART0
+impl embedded_io::WriteReady for Usart0 {
+    fn write_ready(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.usart.status().read().txbl().bit_is_set())
+    }
+}
+
+/// `core::fmt::Write` implementation for USART0
+///
+/// Writes the formatted text with [`Usart0::write`] (blocking).
+impl core::fmt::Write for Usart0 {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        Usart0::write(self, s.as_bytes());
+        Ok(())
     }
 }
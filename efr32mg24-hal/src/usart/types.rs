@@ -2,6 +2,9 @@
 //!
 //! This module defines the types used for configuring USART peripherals.
 
+use crate::gpio::{Input, Output, Pin};
+use crate::time::{Bps, Hertz, MicroSeconds};
+
 /// USART data bits configuration
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DataBits {
@@ -31,32 +34,350 @@ pub enum StopBits {
     Two,
 }
 
+/// Hardware flow control
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowControl {
+    /// No flow control
+    None,
+    /// RTS/CTS handshaking on the given pins
+    ///
+    /// The transmitter holds the next frame while CTS is deasserted (high), and
+    /// RTS is deasserted while the receive buffer is full.
+    RtsCts(RtsCtsPins),
+}
+
+/// CTS and RTS pins for [`FlowControl::RtsCts`]
+///
+/// Records where the pins are, so the USART signals can be routed to them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RtsCtsPins {
+    /// CTS (port, pin)
+    pub(crate) cts: (u8, u8),
+    /// RTS (port, pin)
+    pub(crate) rts: (u8, u8),
+}
+
+impl RtsCtsPins {
+    /// Use `cts` (input) and `rts` (push-pull output) for flow control
+    ///
+    /// Consumes the pins, so they cannot be used for anything else.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let pins = RtsCtsPins::new(
+    ///     gpio.porta.pa4.into_floating_input(),
+    ///     gpio.porta.pa3.into_push_pull_output(),
+    /// );
+    /// let config = Config::new(921_600.bps()).flow_control(FlowControl::RtsCts(pins));
+    /// ```
+    pub fn new<
+        const CTS_PORT: char,
+        const CTS_PIN: u8,
+        PULL,
+        const RTS_PORT: char,
+        const RTS_PIN: u8,
+        const DRIVE: u8,
+    >(
+        cts: Pin<CTS_PORT, CTS_PIN, Input<PULL>>,
+        rts: Pin<RTS_PORT, RTS_PIN, Output<DRIVE>>,
+    ) -> Self {
+        Self {
+            cts: cts.route_location(),
+            rts: rts.route_location(),
+        }
+    }
+}
+
+/// RS-485 half-duplex configuration
+///
+/// The transceiver's driver-enable (DE) input is driven from the USART chip
+/// select output, which is asserted automatically around each transmission
+/// (CTRL.AUTOCS). The receiver is blocked while transmitting, so the echo of
+/// the driver's own frames is discarded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rs485 {
+    /// DE (port, pin)
+    pub(crate) de: (u8, u8),
+    /// DE setup time before the start bit, in bit times
+    pub(crate) setup_bits: u8,
+    /// DE hold time after the last stop bit, in bit times
+    pub(crate) hold_bits: u8,
+}
+
+impl Rs485 {
+    /// Drive DE (active high) on `de`, with 1 bit time of setup and hold
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let rs485 = Rs485::new(gpio.portc.pc3.into_push_pull_output())
+    ///     .setup_bits(1)
+    ///     .hold_bits(2);
+    /// let config = Config::new(19_200.bps()).parity(Parity::Even).rs485(rs485);
+    /// ```
+    pub fn new<const PORT: char, const PIN: u8, const DRIVE: u8>(
+        de: Pin<PORT, PIN, Output<DRIVE>>,
+    ) -> Self {
+        Self {
+            de: de.route_location(),
+            setup_bits: 1,
+            hold_bits: 1,
+        }
+    }
+
+    /// Set the time DE is asserted before the start bit, in bit times
+    ///
+    /// The supported values differ between the peripherals:
+    ///
+    /// - USART0: 0, 1, 2, 3 or 7 bit times; 4 to 6 are rounded up to 7
+    /// - EUSART0/EUSART1: every value from 0 to 7 exactly
+    ///
+    /// Values above 7 are limited to 7 on both.
+    pub const fn setup_bits(mut self, bits: u8) -> Self {
+        self.setup_bits = bits;
+        self
+    }
+
+    /// Set the time DE stays asserted after the last stop bit, in bit times
+    ///
+    /// The supported values differ between the peripherals:
+    ///
+    /// - USART0: 0, 1, 2, 3 or 7 bit times; 4 to 6 are rounded up to 7
+    /// - EUSART0/EUSART1: every value from 0 to 7 exactly
+    ///
+    /// Values above 7 are limited to 7 on both.
+    pub const fn hold_bits(mut self, bits: u8) -> Self {
+        self.hold_bits = bits;
+        self
+    }
+}
+
+/// Low-frequency clock for an EUSART in low-frequency mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LowFrequencyClock {
+    /// Low-frequency crystal oscillator, at the frequency given in the clock
+    /// configuration
+    Lfxo,
+    /// Low-frequency RC oscillator (32.768 kHz)
+    Lfrco,
+}
+
+/// ISO 7816 smart-card mode configuration (USART0 only)
+///
+/// The card's I/O line is shared by TX and RX: route both to the same pin,
+/// configured as open-drain with a pull-up. The receiver listens to the
+/// transmitter internally (CTRL.LOOPBK), a received character with a parity
+/// error is NACKed, and a character NACKed by the card is retransmitted.
+///
+/// USART0 cannot output its clock in asynchronous mode, so the card clock comes
+/// from a TIMER PWM channel. The elementary time unit (etu) is derived from the
+/// card clock frequency, so give the frequency the timer actually generates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SmartCard {
+    /// Card clock (CLK pin) frequency
+    pub(crate) card_clock: Hertz,
+    /// Clock rate conversion factor Fi
+    pub(crate) fi: u16,
+    /// Baud rate adjustment factor Di
+    pub(crate) di: u8,
+    /// Extra guard time N, in etu
+    pub(crate) guard_time: u8,
+    /// Retransmit characters NACKed by the card
+    pub(crate) retransmit: bool,
+}
+
+impl SmartCard {
+    /// Smart-card mode with a card clocked at `card_clock`
+    ///
+    /// Starts with the ATR defaults: Fi = 372, Di = 1 (1 etu = 372 card clock
+    /// cycles), no extra guard time, and retransmission of NACKed characters.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// // 39 MHz / 8 = 4.875 MHz card clock, divided exactly by the timer
+    /// let card_clock = clocks.hfclk() / 8;
+    /// let mut timer = Timer0::new(
+    ///     dp.timer0_s,
+    ///     timer::Config::new(card_clock).with_pwm(PwmMode::EdgeAligned),
+    ///     &clocks,
+    /// );
+    /// timer.set_duty_cycle(PwmChannel::Channel0, 50).unwrap();
+    /// timer.enable_channel(PwmChannel::Channel0);
+    /// timer.start();
+    ///
+    /// let config = Config::default().smart_card(SmartCard::new(card_clock));
+    /// let mut usart = Usart0::new(dp.usart0_s, config, &clocks);
+    /// ```
+    pub const fn new(card_clock: Hertz) -> Self {
+        Self {
+            card_clock,
+            fi: 372,
+            di: 1,
+            guard_time: 0,
+            retransmit: true,
+        }
+    }
+
+    /// Set the clock rate conversion and baud rate adjustment factors
+    ///
+    /// 1 etu = `fi / di` card clock cycles, e.g. after the card's TA1 byte has
+    /// been accepted with a PPS exchange.
+    pub const fn clock_rate(mut self, fi: u16, di: u8) -> Self {
+        self.fi = fi;
+        self.di = di;
+        self
+    }
+
+    /// Set the extra guard time N (TC1), in etu
+    ///
+    /// Added between transmitted characters. USART0 supports 0, 1, 2, 3 and 7
+    /// etu and rounds other values up; values above 7 are limited to 7.
+    pub const fn guard_time(mut self, etu: u8) -> Self {
+        self.guard_time = etu;
+        self
+    }
+
+    /// Enable or disable retransmission of characters NACKed by the card
+    ///
+    /// Enabled by default, as the T=0 protocol requires.
+    pub const fn retransmit(mut self, enable: bool) -> Self {
+        self.retransmit = enable;
+        self
+    }
+
+    /// Duration of one etu in nanoseconds: `fi / (di * card_clock)`, rounded up
+    pub(crate) const fn etu_ns(&self) -> u32 {
+        let clock_hz = self.card_clock.to_Hz() as u64 * self.di as u64;
+        (self.fi as u64 * 1_000_000_000).div_ceil(clock_hz) as u32
+    }
+
+    /// Card clock cycles converted to etu, rounded up
+    pub(crate) const fn cycles_to_etu(&self, cycles: u32) -> u32 {
+        (cycles as u64 * self.di as u64).div_ceil(self.fi as u64) as u32
+    }
+
+    /// Baud rate for one bit per etu: `card_clock * di / fi`
+    pub const fn baudrate(&self) -> Bps {
+        let baudrate = self.card_clock.to_Hz() as u64 * self.di as u64 / self.fi as u64;
+        Bps::from_raw(baudrate as u32)
+    }
+}
+
+/// IrDA transmit pulse width, in sixteenths of a bit time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IrdaPulseWidth {
+    /// 1/16 bit time
+    OneSixteenth,
+    /// 2/16 bit time
+    TwoSixteenths,
+    /// 3/16 bit time, as the IrDA SIR specification uses
+    ThreeSixteenths,
+    /// 4/16 bit time
+    FourSixteenths,
+}
+
+/// IrDA SIR modulation configuration (USART0 only)
+///
+/// Each 0 bit is transmitted as a high pulse of the configured width, and 1 bits
+/// leave TX low. The receiver expects the same pulses from the IR receiver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Irda {
+    /// Transmit pulse width
+    pub(crate) pulse_width: IrdaPulseWidth,
+    /// Ignore received pulses shorter than 5 USART clock cycles
+    pub(crate) filter: bool,
+}
+
+impl Irda {
+    /// IrDA modulation with 3/16 bit pulses and no receive filter
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let irda = Irda::new().pulse_width(IrdaPulseWidth::FourSixteenths).filter(true);
+    /// let config = Config::new(9_600.bps()).irda(irda);
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            pulse_width: IrdaPulseWidth::ThreeSixteenths,
+            filter: false,
+        }
+    }
+
+    /// Set the transmit pulse width
+    pub const fn pulse_width(mut self, pulse_width: IrdaPulseWidth) -> Self {
+        self.pulse_width = pulse_width;
+        self
+    }
+
+    /// Enable or disable the receive glitch filter
+    ///
+    /// With the filter, a pulse must last at least 5 USART clock cycles to be
+    /// detected.
+    pub const fn filter(mut self, enable: bool) -> Self {
+        self.filter = enable;
+        self
+    }
+}
+
+impl Default for Irda {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// USART configuration structure
 #[derive(Debug, Clone, Copy)]
 pub struct Config {
-    /// Baud rate in bits per second
-    pub baudrate: u32,
+    /// Baud rate
+    pub baudrate: Bps,
     /// Number of data bits
     pub data_bits: DataBits,
     /// Parity configuration
     pub parity: Parity,
     /// Number of stop bits
     pub stop_bits: StopBits,
+    /// Hardware flow control
+    pub flow_control: FlowControl,
+    /// RS-485 half-duplex mode
+    pub rs485: Option<Rs485>,
+    /// RX FIFO level, in frames, that signals received data (EUSART only)
+    pub rx_fifo_watermark: u8,
+    /// ISO 7816 smart-card mode (USART0 only)
+    pub smart_card: Option<SmartCard>,
+    /// IrDA modulation (USART0 only)
+    pub irda: Option<Irda>,
+    /// Multiprocessor mode address (USART0 only)
+    pub multiprocessor_address: Option<u8>,
 }
 
 impl Config {
     /// Create a new USART configuration with common settings
     ///
-    /// Default: 8 data bits, no parity, 1 stop bit
-    pub const fn new(baudrate: u32) -> Self {
+    /// Default: 8 data bits, no parity, 1 stop bit, no flow control
+    pub const fn new(baudrate: Bps) -> Self {
         Self {
             baudrate,
             data_bits: DataBits::Eight,
             parity: Parity::None,
             stop_bits: StopBits::One,
+            flow_control: FlowControl::None,
+            rs485: None,
+            rx_fifo_watermark: 1,
+            smart_card: None,
+            irda: None,
+            multiprocessor_address: None,
         }
     }
 
+    /// Set the baud rate
+    pub const fn baudrate(mut self, baudrate: Bps) -> Self {
+        self.baudrate = baudrate;
+        self
+    }
+
     /// Set the number of data bits
     pub const fn data_bits(mut self, data_bits: DataBits) -> Self {
         self.data_bits = data_bits;
@@ -74,12 +395,132 @@ impl Config {
         self.stop_bits = stop_bits;
         self
     }
+
+    /// Set the hardware flow control
+    pub const fn flow_control(mut self, flow_control: FlowControl) -> Self {
+        self.flow_control = flow_control;
+        self
+    }
+
+    /// Enable RS-485 half-duplex mode
+    pub const fn rs485(mut self, rs485: Rs485) -> Self {
+        self.rs485 = Some(rs485);
+        self
+    }
+
+    /// Set the RX FIFO watermark, in frames (1 to 16)
+    ///
+    /// The EUSARTs signal received data, and raise their RX interrupt, once
+    /// their 16-entry RX FIFO holds this many frames. USART0 has no RX FIFO and
+    /// ignores it. Values outside 1 to 16 are limited to that range.
+    pub const fn rx_fifo_watermark(mut self, frames: u8) -> Self {
+        self.rx_fifo_watermark = frames;
+        self
+    }
+
+    /// Enable ISO 7816 smart-card mode (USART0 only)
+    ///
+    /// Also sets the frame format smart cards use: the baud rate of
+    /// [`SmartCard::baudrate`], 8 data bits, even parity and 2 stop bits, the
+    /// second of which is the guard time. Disables IrDA modulation.
+    pub const fn smart_card(mut self, smart_card: SmartCard) -> Self {
+        self.baudrate = smart_card.baudrate();
+        self.data_bits = DataBits::Eight;
+        self.parity = Parity::Even;
+        self.stop_bits = StopBits::Two;
+        self.smart_card = Some(smart_card);
+        self.irda = None;
+        self
+    }
+
+    /// Enable IrDA modulation (USART0 only)
+    ///
+    /// Disables smart-card mode.
+    pub const fn irda(mut self, irda: Irda) -> Self {
+        self.irda = Some(irda);
+        self.smart_card = None;
+        self
+    }
+
+    /// Receive only the data addressed to `address` on a multidrop bus (USART0
+    /// only)
+    ///
+    /// Selects 9 data bits and multiprocessor mode: frames with the ninth bit set
+    /// are addresses, and the receiver ignores data frames until an address
+    /// frame carrying `address` arrives, then until another node is addressed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let config = Config::new(115_200.bps()).multiprocessor_address(0x12);
+    /// let mut usart = Usart0::new(dp.usart0_s, config, &clocks);
+    /// // Returns the data frames sent after the master wrote address 0x12
+    /// let byte = nb::block!(usart.read_byte())?;
+    /// ```
+    pub const fn multiprocessor_address(mut self, address: u8) -> Self {
+        self.data_bits = DataBits::Nine;
+        self.multiprocessor_address = Some(address);
+        self
+    }
+
+    /// Bit times per character: start, data, parity and stop bits
+    pub const fn character_bits(&self) -> u32 {
+        let data = match self.data_bits {
+            DataBits::Eight => 8,
+            DataBits::Nine => 9,
+        };
+        let parity = match self.parity {
+            Parity::None => 0,
+            Parity::Even | Parity::Odd => 1,
+        };
+        let stop = match self.stop_bits {
+            StopBits::One => 1,
+            StopBits::Two => 2,
+        };
+        1 + data + parity + stop
+    }
+
+    /// Modbus RTU inter-frame gap, in bit times
+    ///
+    /// 3.5 character times, or 1.75 ms above 19200 baud as the Modbus RTU
+    /// specification recommends. The result saturates at 255, the range of
+    /// [`Usart0::enable_idle_detection`](super::Usart0::enable_idle_detection).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let config = Config::new(19_200.bps()).parity(Parity::Even);
+    /// let mut usart = Usart0::new(dp.usart0_s, config, &clocks);
+    /// usart.enable_idle_detection(config.modbus_rtu_frame_gap_bits());
+    /// ```
+    pub fn modbus_rtu_frame_gap_bits(&self) -> u8 {
+        let baudrate = self.baudrate.to_bps();
+        let bits = if baudrate <= 19_200 {
+            (7 * self.character_bits()).div_ceil(2)
+        } else {
+            (1_750 * u64::from(baudrate)).div_ceil(1_000_000) as u32
+        };
+        bits.min(u32::from(u8::MAX)) as u8
+    }
+
+    /// Modbus RTU inter-frame gap, as a duration
+    ///
+    /// 3.5 character times, or 1.75 ms above 19200 baud.
+    pub fn modbus_rtu_frame_gap(&self) -> MicroSeconds {
+        let baudrate = self.baudrate.to_bps();
+        if baudrate <= 19_200 {
+            let bits = u64::from(7 * self.character_bits());
+            MicroSeconds::from_ticks((bits * 1_000_000).div_ceil(2 * u64::from(baudrate)) as u32)
+        } else {
+            MicroSeconds::from_ticks(1_750)
+        }
+    }
 }
 
 impl Default for Config {
     /// Default configuration: 115200 baud, 8N1
     fn default() -> Self {
-        Self::new(115200)
+        Self::new(Bps::from_raw(115_200))
     }
 }
 
@@ -92,6 +533,8 @@ pub enum Error {
     Parity,
     /// Overrun error (data lost)
     Overrun,
+    /// No character received within the waiting time (smart-card ATR)
+    Timeout,
 }
 
 impl embedded_hal_nb::serial::Error for Error {
@@ -100,6 +543,19 @@ impl embedded_hal_nb::serial::Error for Error {
             Error::Framing => embedded_hal_nb::serial::ErrorKind::FrameFormat,
             Error::Parity => embedded_hal_nb::serial::ErrorKind::Parity,
             Error::Overrun => embedded_hal_nb::serial::ErrorKind::Overrun,
+            Error::Timeout => embedded_hal_nb::serial::ErrorKind::Other,
+        }
+    }
+}
+
+impl core::error::Error for Error {}
+
+impl embedded_io::Error for Error {
+    fn kind(&self) -> embedded_io::ErrorKind {
+        match self {
+            Error::Framing | Error::Parity => embedded_io::ErrorKind::InvalidData,
+            Error::Overrun => embedded_io::ErrorKind::Other,
+            Error::Timeout => embedded_io::ErrorKind::TimedOut,
         }
     }
 }
@@ -54,6 +54,7 @@ extern "C" {
     fn SW3();
     fn SEMBRX();
     fn SEMBTX();
+    fn SYSRTC_APP();
     fn KEYSCAN();
 }
 #[doc(hidden)]
@@ -154,7 +155,9 @@ pub static __INTERRUPTS: [Vector; 70] = [
     Vector { _reserved: 0 },
     Vector { _handler: SEMBRX },
     Vector { _handler: SEMBTX },
-    Vector { _reserved: 0 },
+    Vector {
+        _handler: SYSRTC_APP,
+    },
     Vector { _reserved: 0 },
     Vector { _handler: KEYSCAN },
 ];
@@ -252,6 +255,8 @@ pub enum Interrupt {
     SEMBRX = 65,
     #[doc = "66 - SEMBTX"]
     SEMBTX = 66,
+    #[doc = "67 - SYSRTC_APP"]
+    SYSRTC_APP = 67,
     #[doc = "69 - KEYSCAN"]
     KEYSCAN = 69,
 }